@@ -1,8 +1,8 @@
 use clap::Parser;
-use mdq::run::{CliOptions, Error, OsFacade};
 use mdq::repl::Repl;
+use mdq::run::{CliOptions, Error, OsFacade, RunOptions};
 use std::io;
-use std::io::{stdin, stdout, Read};
+use std::io::{stdin, stdout, BufReader, IsTerminal, Read};
 use std::process::ExitCode;
 
 struct RealOs;
@@ -26,6 +26,25 @@ impl OsFacade for RealOs {
     fn write_error(&mut self, err: Error) {
         eprint!("{err}")
     }
+
+    fn write_file(&mut self, path: &str, contents: &str) -> io::Result<()> {
+        std::fs::write(path, contents)
+    }
+
+    fn read_dir(&self, path: &str) -> io::Result<Option<Vec<String>>> {
+        // Symbolic links to directories aren't followed, so that a link to a parent directory can't cause a cycle.
+        let Ok(metadata) = std::fs::symlink_metadata(path) else {
+            return Ok(None); // let read_file report the error
+        };
+        if !metadata.is_dir() {
+            return Ok(None);
+        }
+        let mut names = Vec::new();
+        for entry in std::fs::read_dir(path)? {
+            names.push(entry?.file_name().to_string_lossy().into_owned());
+        }
+        Ok(Some(names))
+    }
 }
 
 fn main() -> ExitCode {
@@ -36,9 +55,10 @@ fn main() -> ExitCode {
     }
 
     // Check if REPL mode is requested
-    if cli.repl() {
+    if cli.repl() || cli.repl_script().is_some() {
         return match run_repl_mode(&cli) {
-            Ok(_) => ExitCode::SUCCESS,
+            Ok(true) => ExitCode::SUCCESS,
+            Ok(false) => ExitCode::FAILURE,
             Err(e) => {
                 eprintln!("REPL error: {}", e);
                 ExitCode::FAILURE
@@ -53,14 +73,21 @@ fn main() -> ExitCode {
     }
 }
 
-/// Runs the REPL mode
-fn run_repl_mode(cli: &CliOptions) -> io::Result<()> {
-    let run_options = cli.clone().into();
-    let mut repl = Repl::new(run_options)?;
-    
+/// Runs the REPL mode, or a REPL script. Returns whether the script succeeded (or true, for the interactive REPL).
+fn run_repl_mode(cli: &CliOptions) -> io::Result<bool> {
+    let run_options: RunOptions = cli.clone().into();
+    let markdown_file_paths = run_options.markdown_file_paths.clone();
+    // Without a terminal, stdin is a script
+    let script_mode = cli.repl_script().is_some() || !stdin().is_terminal();
+    let mut repl = if script_mode {
+        Repl::for_script(run_options)?
+    } else {
+        Repl::new(run_options)?
+    };
+
     // If files are provided, load the first one
-    if !cli.markdown_file_paths().is_empty() {
-        let first_file = &cli.markdown_file_paths()[0];
+    let mut read_stdin = false;
+    if let Some(first_file) = markdown_file_paths.first() {
         if first_file != "-" {
             // Load from file
             let content = std::fs::read_to_string(first_file)
@@ -69,13 +96,30 @@ fn run_repl_mode(cli: &CliOptions) -> io::Result<()> {
                 .map_err(|e| io::Error::other(format!("Failed to load document: {}", e)))?;
         } else {
             // Load from stdin
+            read_stdin = true;
             let mut content = String::new();
             stdin().read_to_string(&mut content)?;
             repl.load_document(content)
                 .map_err(|e| io::Error::other(format!("Failed to load document: {}", e)))?;
         }
     }
-    
+
+    if let Some(script) = cli.repl_script() {
+        let script = std::fs::File::open(script)
+            .map_err(|e| io::Error::other(format!("Failed to read script {}: {}", script, e)))?;
+        return repl.run_script(BufReader::new(script), &mut stdout());
+    }
+
+    if script_mode {
+        if read_stdin {
+            return Err(io::Error::other(
+                "Can't read both the document and the script from stdin",
+            ));
+        }
+        return repl.run_script(stdin().lock(), &mut stdout());
+    }
+
     // Start REPL
-    repl.run()
+    repl.run()?;
+    Ok(true)
 }
@@ -4,11 +4,14 @@
 mod tree;
 mod tree_ref;
 
+pub use source_spans::{SourcePoint, SourceSpan};
 pub use tree::*;
 
 mod concatenate;
 mod flat_inlines;
 pub(crate) mod inline_regex_replace;
+pub(crate) mod slugs;
+pub(crate) mod source_spans;
 #[cfg(test)]
 pub(crate) mod tree_test_utils;
 
@@ -0,0 +1,102 @@
+use crate::md_elem::tree::elem::Inline;
+use crate::output::{inlines_to_plain_string, FootnoteToString, InlineToStringOpts};
+use std::collections::HashMap;
+
+/// Converts a section's title to a slug, without any disambiguating suffix.
+///
+/// Like GitHub, this lowercases the title's text, turns spaces into hyphens, and drops everything other than letters,
+/// numbers, hyphens and underscores.
+pub(crate) fn title_slug(title: &[Inline]) -> String {
+    let text = inlines_to_plain_string(
+        title,
+        InlineToStringOpts {
+            footnotes: FootnoteToString::OnlyFootnoteId,
+        },
+    );
+    text.to_lowercase()
+        .chars()
+        .filter_map(|ch| match ch {
+            ' ' => Some('-'),
+            '-' | '_' => Some(ch),
+            ch if ch.is_alphanumeric() => Some(ch),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Hands out slugs for section titles, in document order.
+///
+/// GitHub disambiguates sections that would have the same slug by adding `-1`, `-2` and so on to the later ones, so a
+/// section's slug depends on the sections before it. This keeps track of the slugs it's handed out so far.
+#[derive(Default, Debug)]
+pub(crate) struct Slugger {
+    occurrences: HashMap<String, usize>,
+}
+
+impl Slugger {
+    /// The slug for the next section, given its title.
+    pub(crate) fn next_slug(&mut self, title: &[Inline]) -> String {
+        self.unique_slug(title_slug(title))
+    }
+
+    /// Adds a `-1`, `-2` etc suffix if needed. This is the same algorithm as GitHub's, including for the case where a
+    /// suffixed slug collides with an actual title: `foo`, `foo` and `foo-1` become `foo`, `foo-1` and `foo-1-1`.
+    fn unique_slug(&mut self, base: String) -> String {
+        let mut result = base.clone();
+        while self.occurrences.contains_key(&result) {
+            let count = self.occurrences.entry(base.clone()).or_default();
+            *count += 1;
+            result = format!("{base}-{count}");
+        }
+        self.occurrences.insert(result.clone(), 0);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::md_elem::elem::*;
+    use crate::md_elem::*;
+
+    #[test]
+    fn slug_punctuation_and_case() {
+        assert_eq!(title_slug(&inlines!["Running the Tests!"]), "running-the-tests");
+        assert_eq!(title_slug(&inlines!["What's new in v1.2?"]), "whats-new-in-v12");
+        assert_eq!(
+            title_slug(&inlines!["snake_case and kebab-case"]),
+            "snake_case-and-kebab-case"
+        );
+        assert_eq!(title_slug(&inlines!["Ünïcödé"]), "ünïcödé");
+    }
+
+    #[test]
+    fn slug_of_formatted_title() {
+        let title = vec![
+            mdq_inline!("Using "),
+            Inline::Text(Text {
+                variant: TextVariant::Code,
+                value: "mdq --help".to_string(),
+            }),
+        ];
+        assert_eq!(title_slug(&title), "using-mdq---help");
+    }
+
+    #[test]
+    fn duplicates() {
+        let mut slugger = Slugger::default();
+
+        assert_eq!(slugger.next_slug(&inlines!["Foo"]), "foo");
+        assert_eq!(slugger.next_slug(&inlines!["Foo"]), "foo-1");
+        assert_eq!(slugger.next_slug(&inlines!["Foo"]), "foo-2");
+    }
+
+    #[test]
+    fn duplicate_collides_with_title() {
+        let mut slugger = Slugger::default();
+
+        assert_eq!(slugger.next_slug(&inlines!["Foo"]), "foo");
+        assert_eq!(slugger.next_slug(&inlines!["Foo"]), "foo-1");
+        assert_eq!(slugger.next_slug(&inlines!["Foo 1"]), "foo-1-1");
+    }
+}
@@ -0,0 +1,354 @@
+use crate::md_elem::slugs::Slugger;
+use crate::md_elem::tree::elem::{List, Section};
+use crate::md_elem::tree::{MdContext, MdElem};
+use markdown::unist::Position;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+/// A point in the Markdown source.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SourcePoint {
+    /// The 1-based line.
+    pub line: usize,
+    /// The 1-based column, in characters.
+    pub column: usize,
+    /// The 0-based byte offset.
+    pub offset: usize,
+}
+
+/// Where an element came from in the Markdown source.
+///
+/// The end is exclusive: it's the point just after the element's last character.
+///
+/// ```
+/// use mdq::md_elem::{MdDoc, ParseOptions};
+///
+/// let doc = MdDoc::parse("# Title\n\nSome text.", &ParseOptions::default()).unwrap();
+/// let spans = doc.ctx.get_source_spans(&doc.roots[0]);
+/// assert_eq!((spans[0].start.line, spans[0].start.column), (1, 1));
+/// assert_eq!((spans[0].end.line, spans[0].end.column, spans[0].end.offset), (3, 11, 19));
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SourceSpan {
+    pub start: SourcePoint,
+    pub end: SourcePoint,
+}
+
+impl From<&Position> for SourceSpan {
+    fn from(position: &Position) -> Self {
+        let point = |point: &markdown::unist::Point| SourcePoint {
+            line: point.line,
+            column: point.column,
+            offset: point.offset,
+        };
+        Self {
+            start: point(&position.start),
+            end: point(&position.end),
+        }
+    }
+}
+
+/// The source spans of each element in a document.
+///
+/// Elements don't know where they are in the document, so we key them by their contents. If several elements are
+/// identical, each of their spans is kept.
+///
+/// This also keeps each section's GitHub-style anchor slug, keyed by where the section starts; and the span of each
+/// reference-style link's or image's definition, keyed by where the link or image starts.
+#[derive(Clone, Default, Debug, PartialEq)]
+pub(crate) struct SourceSpans {
+    by_elem: HashMap<u64, Vec<SourceSpan>>,
+    section_slugs: HashMap<usize, String>,
+    link_definitions: HashMap<usize, SourceSpan>,
+}
+
+impl SourceSpans {
+    pub(crate) fn record(&mut self, elem: &MdElem, span: SourceSpan) {
+        let spans = self.by_elem.entry(elem_key(elem)).or_default();
+        if !spans.contains(&span) {
+            spans.push(span);
+        }
+    }
+
+    /// Records each list item as its own, single-item list. That's how list item selectors select them.
+    pub(crate) fn record_list_item(&mut self, list: &List, idx: usize, span: SourceSpan) {
+        let item_list = MdElem::List(List {
+            starting_index: list.starting_index.map(|start| start + idx as u32),
+            items: vec![list.items[idx].clone()],
+        });
+        self.record(&item_list, span);
+    }
+
+    /// Records where a reference-style link's or image's definition is, as in the `[1]: https://example.com` of
+    /// `[text][1]`.
+    pub(crate) fn record_link_definition(&mut self, link: SourceSpan, definition: SourceSpan) {
+        self.link_definitions.insert(link.start.offset, definition);
+    }
+
+    /// The span of the definition for the reference-style link or image at this span.
+    pub(crate) fn link_definition(&self, link: &SourceSpan) -> Option<SourceSpan> {
+        self.link_definitions.get(&link.start.offset).copied()
+    }
+
+    /// The spans of all the elements identical to this one, or an empty slice if it isn't in the document.
+    pub(crate) fn get(&self, elem: &MdElem) -> &[SourceSpan] {
+        self.by_elem.get(&elem_key(elem)).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// The slugs of the sections in the document that are identical to this one, or an empty `Vec` if it isn't in the
+    /// document (for example, because a replacement changed its title).
+    pub(crate) fn section_slugs(&self, elem: &MdElem) -> Vec<&str> {
+        self.get(elem)
+            .iter()
+            .filter_map(|span| self.section_slugs.get(&span.start.offset))
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// The first span for this element that starts at or after the given byte offset.
+    pub(crate) fn first_from(&self, elem: &MdElem, offset: usize) -> Option<SourceSpan> {
+        self.get(elem)
+            .iter()
+            .filter(|span| span.start.offset >= offset)
+            .min_by_key(|span| span.start.offset)
+            .copied()
+    }
+
+    /// Adds the spans of the sections within these elements, and their slugs.
+    ///
+    /// mdast only knows about headings, so this has to wait until the sections have been built from them. Each
+    /// section's span goes from the start of its heading to the end of its body. Slugs depend on the sections that came
+    /// before, so the slugger should be the same for all of a document's sections, in document order.
+    pub(crate) fn add_sections(&mut self, elems: &[MdElem], slugger: &mut Slugger) {
+        self.add_sections_within(elems, 0, slugger);
+    }
+
+    /// Moves every span later in the source, for when the Markdown was parsed without some leading text.
+    pub(crate) fn shift(&mut self, leading: &str) {
+        let lines = leading.matches('\n').count();
+        let shift_span = |span: &mut SourceSpan| {
+            for point in [&mut span.start, &mut span.end] {
+                point.line += lines;
+                point.offset += leading.len();
+            }
+        };
+        self.by_elem.values_mut().flatten().for_each(shift_span);
+        self.link_definitions.values_mut().for_each(shift_span);
+        self.section_slugs = self
+            .section_slugs
+            .drain()
+            .map(|(offset, slug)| (offset + leading.len(), slug))
+            .collect();
+        self.link_definitions = self
+            .link_definitions
+            .drain()
+            .map(|(offset, definition)| (offset + leading.len(), definition))
+            .collect();
+    }
+
+    /// Returns the end of the last of these elements that has a span.
+    fn add_sections_within(
+        &mut self,
+        elems: &[MdElem],
+        mut offset: usize,
+        slugger: &mut Slugger,
+    ) -> Option<SourcePoint> {
+        let mut end = None;
+        for elem in elems {
+            let span = match elem {
+                MdElem::Section(section) => {
+                    let heading = MdElem::Section(Section {
+                        depth: section.depth,
+                        title: section.title.clone(),
+                        body: Vec::new(),
+                    });
+                    let Some(heading_span) = self.first_from(&heading, offset) else {
+                        continue;
+                    };
+                    let slug = slugger.next_slug(&section.title);
+                    self.section_slugs.insert(heading_span.start.offset, slug);
+                    let body_end = self.add_sections_within(&section.body, heading_span.end.offset, slugger);
+                    let span = SourceSpan {
+                        start: heading_span.start,
+                        end: body_end.unwrap_or(heading_span.end),
+                    };
+                    self.record(elem, span);
+                    Some(span)
+                }
+                MdElem::BlockQuote(block) => {
+                    let span = self.first_from(elem, offset);
+                    if let Some(span) = span {
+                        self.add_sections_within(&block.body, span.start.offset, slugger);
+                    }
+                    span
+                }
+                MdElem::List(list) => {
+                    let span = self.first_from(elem, offset);
+                    if let Some(span) = span {
+                        for item in &list.items {
+                            self.add_sections_within(&item.item, span.start.offset, slugger);
+                        }
+                    }
+                    span
+                }
+                _ => self.first_from(elem, offset),
+            };
+            if let Some(span) = span {
+                offset = span.end.offset;
+                end = Some(span.end);
+            }
+        }
+        end
+    }
+}
+
+/// Finds the spans of elements as they're visited in document order.
+///
+/// Identical elements share their spans, so this uses that order to tell them apart: each element's span is the first
+/// one that starts after the previous element ended, or within its parent.
+pub(crate) struct SpanCursor<'md> {
+    ctx: &'md MdContext,
+    offset: usize,
+}
+
+impl<'md> SpanCursor<'md> {
+    pub(crate) fn new(ctx: &'md MdContext) -> Self {
+        Self { ctx, offset: 0 }
+    }
+
+    /// Finds the element's span, and moves to its start so that its children are found within it.
+    pub(crate) fn enter(&mut self, elem: &MdElem) -> Option<SourceSpan> {
+        let span = self
+            .ctx
+            .get_source_spans(elem)
+            .iter()
+            .filter(|span| span.start.offset >= self.offset)
+            .min_by_key(|span| span.start.offset)
+            .copied()?;
+        self.offset = span.start.offset;
+        Some(span)
+    }
+
+    /// Moves past the element, once its children have been visited.
+    pub(crate) fn exit(&mut self, span: Option<SourceSpan>) {
+        if let Some(span) = span {
+            self.offset = span.end.offset;
+        }
+    }
+
+    /// Goes back to the start of the source, for elements (like footnotes) that aren't in document order.
+    pub(crate) fn restart(&mut self) {
+        self.offset = 0;
+    }
+
+    /// Moves to the given byte offset, for an element that's known to start there or after it. Unlike the other moves,
+    /// this can go backwards.
+    pub(crate) fn seek(&mut self, offset: usize) {
+        self.offset = offset;
+    }
+}
+
+fn elem_key(elem: &MdElem) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    elem.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::md_elem::*;
+    use indoc::indoc;
+
+    #[test]
+    fn paragraphs() {
+        let doc = parse("one\n\ntwo");
+
+        assert_eq!(offsets(&doc, &doc.roots[0]), vec![(0, 3)]);
+        assert_eq!(offsets(&doc, &doc.roots[1]), vec![(5, 8)]);
+    }
+
+    #[test]
+    fn section_includes_body() {
+        let doc = parse(indoc! {r"
+            # Title
+
+            Some text.
+
+            ## Subsection
+
+            More text.
+
+            # Next"});
+
+        assert_eq!(offsets(&doc, &doc.roots[0]), vec![(0, 46)]);
+        let MdElem::Section(section) = &doc.roots[0] else {
+            panic!("expected a section: {:?}", doc.roots[0]);
+        };
+        assert_eq!(offsets(&doc, &section.body[1]), vec![(21, 46)]);
+        assert_eq!(offsets(&doc, &doc.roots[1]), vec![(48, 54)]);
+    }
+
+    #[test]
+    fn list_items() {
+        let doc = parse("- one\n- two");
+        let MdElem::List(list) = &doc.roots[0] else {
+            panic!("expected a list: {:?}", doc.roots[0]);
+        };
+
+        assert_eq!(offsets(&doc, &doc.roots[0]), vec![(0, 11)]);
+        let second = MdElem::List(List {
+            starting_index: None,
+            items: vec![list.items[1].clone()],
+        });
+        assert_eq!(offsets(&doc, &second), vec![(6, 11)]);
+    }
+
+    #[test]
+    fn identical_elements() {
+        let doc = parse("same\n\nsame");
+
+        assert_eq!(offsets(&doc, &doc.roots[0]), vec![(0, 4), (6, 10)]);
+    }
+
+    #[test]
+    fn cursor_tells_identical_elements_apart() {
+        let doc = parse("same\n\n> same\n\nsame");
+        let mut cursor = SpanCursor::new(&doc.ctx);
+
+        let first = cursor.enter(&doc.roots[0]);
+        cursor.exit(first);
+        let quote = cursor.enter(&doc.roots[1]);
+        let MdElem::BlockQuote(block) = &doc.roots[1] else {
+            panic!("expected a block quote: {:?}", doc.roots[1]);
+        };
+        let within_quote = cursor.enter(&block.body[0]);
+        cursor.exit(within_quote);
+        cursor.exit(quote);
+        let last = cursor.enter(&doc.roots[2]);
+
+        let offsets = [first, within_quote, last].map(|span| span.map(|span| span.start.offset));
+        assert_eq!(offsets, [Some(0), Some(8), Some(14)]);
+    }
+
+    #[test]
+    fn leading_blank_lines() {
+        let doc = parse("\n\nhello");
+        let span = doc.ctx.get_source_spans(&doc.roots[0])[0];
+
+        assert_eq!((span.start.line, span.start.column, span.start.offset), (3, 1, 2));
+    }
+
+    fn parse(md: &str) -> MdDoc {
+        MdDoc::parse(md, &ParseOptions::default()).unwrap()
+    }
+
+    fn offsets(doc: &MdDoc, elem: &MdElem) -> Vec<(usize, usize)> {
+        doc.ctx
+            .get_source_spans(elem)
+            .iter()
+            .map(|span| (span.start.offset, span.end.offset))
+            .collect()
+    }
+}
@@ -1,6 +1,9 @@
 use crate::md_elem::concatenate::Concatenate;
+use crate::md_elem::slugs::Slugger;
+use crate::md_elem::source_spans::{SourceSpan, SourceSpans};
 use crate::util::str_utils::TrimmedEmptyLines;
 use std::backtrace::Backtrace;
+use std::cell::RefCell;
 use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 use std::fmt::{Debug, Display, Formatter, Write};
@@ -32,6 +35,8 @@ use markdown::unist::Position;
 pub struct MdContext {
     footnotes: HashMap<FootnoteId, Vec<MdElem>>,
 
+    source_spans: SourceSpans,
+
     /// useful as a default value
     empty_md_elems: Vec<MdElem>,
 }
@@ -47,10 +52,57 @@ impl MdContext {
         self.footnotes.get(footnote_id).unwrap_or(&self.empty_md_elems)
     }
 
+    /// Gets where the element came from in the Markdown source, or an empty slice if it isn't in the document as-is
+    /// (for example, because a replacement changed it).
+    ///
+    /// Elements don't know where they are in the document, so if there are several identical elements, this returns
+    /// each of their spans, in document order. List items have spans as single-item lists, which is how list item
+    /// selectors select them.
+    pub fn get_source_spans(&self, elem: &MdElem) -> &[SourceSpan] {
+        self.source_spans.get(elem)
+    }
+
+    /// Gets where a reference-style link's or image's definition is in the source, given the span of the link or image
+    /// itself.
+    pub(crate) fn get_link_definition_span(&self, link: &SourceSpan) -> Option<SourceSpan> {
+        self.source_spans.link_definition(link)
+    }
+
+    /// All the footnotes' contents, in no particular order.
+    pub(crate) fn footnote_bodies(&self) -> impl Iterator<Item = &Vec<MdElem>> {
+        self.footnotes.values()
+    }
+
+    /// Gets the GitHub-style anchor slugs of the sections in the document that are identical to this one, or an empty
+    /// `Vec` if it isn't in the document as-is.
+    ///
+    /// The slugs are for the whole document: a section whose title is the same as an earlier one's gets a `-1`, `-2` etc
+    /// suffix, even if the earlier one isn't among the elements being selected from.
+    pub(crate) fn get_section_slugs(&self, section: &MdElem) -> Vec<&str> {
+        self.source_spans.section_slugs(section)
+    }
+
+    /// Adds another document's footnotes to this one's, for when elements from that document get added to this one.
+    ///
+    /// If both documents have a footnote with the same id but different contents, this returns that footnote's id.
+    pub(crate) fn add_footnotes(&mut self, other: &MdContext) -> Result<(), FootnoteId> {
+        for (id, body) in &other.footnotes {
+            match self.footnotes.get(id) {
+                Some(existing) if existing != body => return Err(id.clone()),
+                Some(_) => {}
+                None => {
+                    self.footnotes.insert(id.clone(), body.clone());
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Creates a new MdContext with a default guess as to allocations and
     fn new() -> Self {
         Self {
             footnotes: HashMap::with_capacity(4), // total guess
+            source_spans: SourceSpans::default(),
             empty_md_elems: Vec::new(),
         }
     }
@@ -62,6 +114,7 @@ impl MdContext {
     pub(crate) fn empty() -> Self {
         Self {
             footnotes: HashMap::with_capacity(0),
+            source_spans: SourceSpans::default(),
             empty_md_elems: Vec::new(),
         }
     }
@@ -89,6 +142,8 @@ impl MdDoc {
         if result.is_err() && !trimmed.trimmed.is_empty() {
             // re-parse on the original text, so that we get the correct offsets
             result = parse0(text, options);
+        } else if let Ok(doc) = &mut result {
+            doc.ctx.source_spans.shift(trimmed.trimmed);
         }
         result
     }
@@ -144,12 +199,14 @@ pub enum MdElem {
 
     // Container blocks
     BlockQuote(BlockQuote),
+    FootnoteDefinition(FootnoteDefinition),
     List(List),
     Section(Section),
 
     // Leaf blocks
     CodeBlock(CodeBlock),
     FrontMatter(FrontMatter),
+    FrontMatterValue(FrontMatterValue),
     Paragraph(Paragraph),
     Table(Table),
     /// A thematic break:
@@ -177,6 +234,11 @@ pub struct ParseOptions {
     /// it or error out. If this field is set to `true`, mdq will ignore the unexpected state. Otherwise,
     /// [`MdDoc::parse`] will return an `Err` containing [`InvalidMd::UnknownMarkdown`].
     pub allow_unknown_markdown: bool,
+    /// Whether to parse `$inline math$` and `$$` math blocks. Defaults to `false`.
+    ///
+    /// This is off by default because with it, any text with two dollar signs is math: in `costs $5 or $10`, the
+    /// `5 or ` would be inline math.
+    pub math: bool,
 }
 
 impl Default for ParseOptions {
@@ -192,13 +254,29 @@ impl ParseOptions {
         Self {
             mdast_options: markdown::ParseOptions::gfm(),
             allow_unknown_markdown: true,
+            math: false,
         }
     }
 }
 
 /// Parse some Markdown text.
 fn parse0(text: &str, options: &ParseOptions) -> Result<MdDoc, InvalidMd> {
-    let ast = markdown::to_mdast(text, &options.mdast_options).map_err(|e| InvalidMd::ParseError(format!("{e}")))?;
+    let ast = if options.math {
+        let mdast_options = markdown::ParseOptions {
+            constructs: markdown::Constructs {
+                math_flow: true,
+                math_text: true,
+                ..options.mdast_options.constructs.clone()
+            },
+            gfm_strikethrough_single_tilde: options.mdast_options.gfm_strikethrough_single_tilde,
+            math_text_single_dollar: options.mdast_options.math_text_single_dollar,
+            ..markdown::ParseOptions::default()
+        };
+        markdown::to_mdast(text, &mdast_options)
+    } else {
+        markdown::to_mdast(text, &options.mdast_options)
+    };
+    let ast = ast.map_err(|e| InvalidMd::ParseError(format!("{e}")))?;
     let read_options = ReadOptions {
         validate_no_conflicting_links: false,
         allow_unknown_markdown: options.allow_unknown_markdown,
@@ -503,6 +581,38 @@ pub mod elem {
         pub body: String,
     }
 
+    /// A single value from within front matter, as selected by a key path like `+++ .title`.
+    ///
+    /// [`MdDoc::parse`] never produces these; only the front matter key selectors do. Sequences and mappings within the
+    /// front matter are selected as [`MdElem::List`]s and [`MdElem::FrontMatter`]s instead.
+    #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+    pub enum FrontMatterValue {
+        Null,
+        Bool(bool),
+        /// A number, as its text (for example, `42` or `1.5`)
+        Number(String),
+        String(String),
+    }
+
+    impl FrontMatterValue {
+        /// Gets the value's text: `null`, `true` or `false`, the number, or the string itself.
+        ///
+        /// ```
+        /// use mdq::md_elem::elem::FrontMatterValue;
+        ///
+        /// assert_eq!(FrontMatterValue::Bool(false).text(), "false");
+        /// assert_eq!(FrontMatterValue::String("Hello".to_string()).text(), "Hello");
+        /// ```
+        pub fn text(&self) -> &str {
+            match self {
+                FrontMatterValue::Null => "null",
+                FrontMatterValue::Bool(true) => "true",
+                FrontMatterValue::Bool(false) => "false",
+                FrontMatterValue::Number(text) | FrontMatterValue::String(text) => text,
+            }
+        }
+    }
+
     #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
     pub enum FrontMatterVariant {
         /// ```markdown
@@ -883,6 +993,20 @@ pub mod elem {
         }
     }
 
+    /// A footnote's definition, as selected by a footnote selector like `[^1]`:
+    ///
+    /// ```markdown
+    /// [^1]: the footnote text
+    /// ```
+    ///
+    /// [`MdDoc::parse`] never produces these; a document's footnote definitions are in its [`MdContext`], which
+    /// [`MdContext::get_footnote`] gets by [`FootnoteId`].
+    #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+    pub struct FootnoteDefinition {
+        pub id: FootnoteId,
+        pub body: Vec<MdElem>,
+    }
+
     /// Supporting struct representing the link in an [`Link`] or [`Image`].
     ///
     /// See those two types for examples.
@@ -1351,6 +1475,7 @@ pub mod elem {
     }
 
     from_for_md_elem! { BlockQuote }
+    from_for_md_elem! { FootnoteDefinition }
     from_for_md_elem! { List }
     from_for_md_elem! { Section }
     from_for_md_elem! { CodeBlock }
@@ -1378,6 +1503,24 @@ pub mod elem {
         }
     }
 
+    impl From<FootnoteId> for MdElem {
+        fn from(value: FootnoteId) -> Self {
+            MdElem::Inline(Inline::Footnote(value))
+        }
+    }
+
+    impl From<Span> for MdElem {
+        fn from(value: Span) -> Self {
+            MdElem::Inline(Inline::Span(value))
+        }
+    }
+
+    impl From<Text> for MdElem {
+        fn from(value: Text) -> Self {
+            MdElem::Inline(Inline::Text(value))
+        }
+    }
+
     impl From<Vec<MdElem>> for MdElem {
         fn from(elems: Vec<MdElem>) -> Self {
             Self::Doc(elems)
@@ -1452,12 +1595,20 @@ impl MdDoc {
         let lookups = Lookups::new(&node, opts, source)?;
         let mut ctx = MdContext::new();
         let roots = MdElem::from_mdast_0(node, &lookups, &mut ctx)?;
+        let mut source_spans = lookups.source_spans.take();
+        let mut slugger = Slugger::default();
+        source_spans.add_sections(&roots, &mut slugger);
+        for footnote in ctx.footnotes.values() {
+            source_spans.add_sections(footnote, &mut slugger);
+        }
+        ctx.source_spans = source_spans;
         Ok(Self { roots, ctx })
     }
 }
 
 impl MdElem {
     fn from_mdast_0(node: mdast::Node, lookups: &Lookups<'_>, ctx: &mut MdContext) -> Result<Vec<Self>, InvalidMd> {
+        let span = node.position().map(SourceSpan::from);
         let result = match node {
             mdast::Node::Root(node) => return MdElem::all(node.children, lookups, ctx),
             mdast::Node::Blockquote(node) => m_node!(MdElem::BlockQuote {
@@ -1465,22 +1616,39 @@ impl MdElem {
             }),
             mdast::Node::List(node) => {
                 let mut li_nodes = Vec::with_capacity(node.children.len());
+                let mut li_spans = Vec::with_capacity(node.children.len());
                 for node in node.children {
                     let mdast::Node::ListItem(li_node) = node else {
                         return Err(InvalidMd::NonListItemDirectlyUnderList(MarkdownPart {
                             node: Box::new(node),
                         }));
                     };
+                    let li_span = li_node.position.as_ref().map(|position| {
+                        let mut span = SourceSpan::from(position);
+                        // mdast ends an item that's followed by a blank line after that line, so use its contents' end.
+                        if let Some(last) = li_node.children.last().and_then(mdast::Node::position) {
+                            span.end = SourceSpan::from(last).end;
+                        }
+                        span
+                    });
+                    li_spans.push(li_span);
                     let li_mdq = ListItem {
                         checked: li_node.checked,
                         item: MdElem::all(li_node.children, lookups, ctx)?,
                     };
                     li_nodes.push(li_mdq);
                 }
-                m_node!(MdElem::List {
+                let list = List {
                     starting_index: node.start,
                     items: li_nodes,
-                })
+                };
+                let mut source_spans = lookups.source_spans.borrow_mut();
+                for (idx, li_span) in li_spans.into_iter().enumerate() {
+                    if let Some(li_span) = li_span {
+                        source_spans.record_list_item(&list, idx, li_span);
+                    }
+                }
+                MdElem::List(list)
             }
             mdast::Node::Break(_) => MdElem::Inline(Inline::Text(Text {
                 variant: TextVariant::Plain,
@@ -1510,10 +1678,13 @@ impl MdElem {
                     reference: LinkReference::Inline,
                 },
             })),
-            mdast::Node::ImageReference(node) => MdElem::Inline(Inline::Image(Image {
-                alt: node.alt,
-                link: lookups.resolve_link(node.identifier, node.label, node.reference_kind, lookups)?,
-            })),
+            mdast::Node::ImageReference(node) => {
+                lookups.record_definition_span(span, &node.identifier);
+                MdElem::Inline(Inline::Image(Image {
+                    alt: node.alt,
+                    link: lookups.resolve_link(node.identifier, node.label, node.reference_kind, lookups)?,
+                }))
+            }
             mdast::Node::Link(node) => {
                 let mdast::Link {
                     children,
@@ -1536,10 +1707,13 @@ impl MdElem {
                 };
                 MdElem::Inline(Inline::Link(link))
             }
-            mdast::Node::LinkReference(node) => MdElem::Inline(Inline::Link(Link::Standard(StandardLink {
-                display: MdElem::inlines(node.children, lookups, ctx)?,
-                link: lookups.resolve_link(node.identifier, node.label, node.reference_kind, lookups)?,
-            }))),
+            mdast::Node::LinkReference(node) => {
+                lookups.record_definition_span(span, &node.identifier);
+                MdElem::Inline(Inline::Link(Link::Standard(StandardLink {
+                    display: MdElem::inlines(node.children, lookups, ctx)?,
+                    link: lookups.resolve_link(node.identifier, node.label, node.reference_kind, lookups)?,
+                })))
+            }
             mdast::Node::FootnoteReference(node) => {
                 MdElem::Inline(Inline::Footnote(FootnoteId::new(node.identifier, node.label)))
             }
@@ -1548,6 +1722,13 @@ impl MdElem {
                 return if ctx.footnotes.contains_key(&footnote_id) {
                     Err(InvalidMd::ConflictingReferenceDefinition(footnote_id.id))
                 } else {
+                    // like list items, definitions can end after the line that follows them
+                    let span = span.map(|mut span| {
+                        if let Some(last) = node.children.last().and_then(mdast::Node::position) {
+                            span.end = SourceSpan::from(last).end;
+                        }
+                        span
+                    });
                     let children = MdElem::all(node.children, lookups, ctx)?;
 
                     // Can't use HashMap::entry without cloning footnote_id.
@@ -1555,6 +1736,14 @@ impl MdElem {
                     if ctx.footnotes.contains_key(&footnote_id) {
                         Err(InvalidMd::ConflictingReferenceDefinition(footnote_id.id))
                     } else {
+                        if let Some(span) = span {
+                            // for selected definitions; see FootnoteDefinition
+                            let definition = MdElem::FootnoteDefinition(FootnoteDefinition {
+                                id: footnote_id.clone(),
+                                body: children.clone(),
+                            });
+                            lookups.source_spans.borrow_mut().record(&definition, span);
+                        }
                         ctx.footnotes.insert(footnote_id, children);
                         Ok(Vec::new())
                     }
@@ -1645,6 +1834,9 @@ impl MdElem {
                 return Err(InvalidMd::Unsupported(MarkdownPart { node: Box::new(node) }));
             }
         };
+        if let Some(span) = span {
+            lookups.source_spans.borrow_mut().record(&result, span);
+        }
         Ok(vec![result])
     }
 
@@ -1776,10 +1968,18 @@ impl MdElem {
             // return it as a block, but we can just extract the String and convert it to an Inline.
             let inline = match child {
                 MdElem::Inline(inline) => inline,
-                MdElem::BlockHtml(html) => Inline::Text(Text {
-                    variant: TextVariant::InlineHtml,
-                    value: html.value,
-                }),
+                MdElem::BlockHtml(html) => {
+                    let inline = Inline::Text(Text {
+                        variant: TextVariant::InlineHtml,
+                        value: html.value.clone(),
+                    });
+                    // Its spans were recorded for the block, so record them for the inline, too.
+                    let mut source_spans = lookups.source_spans.borrow_mut();
+                    for span in source_spans.get(&MdElem::BlockHtml(html)).to_vec() {
+                        source_spans.record(&MdElem::Inline(inline.clone()), span);
+                    }
+                    inline
+                }
                 _ => return Err(InvalidMd::NonInlineWhereInlineExpected(child)),
             };
             result.push(inline);
@@ -1894,6 +2094,8 @@ struct Lookups<'a> {
     footnote_definitions: HashMap<String, mdast::FootnoteDefinition>,
     allow_unknown_markdown: bool,
     source: &'a str,
+    /// Filled in as the elements get read, and then moved into the [MdContext] once the sections have been built.
+    source_spans: RefCell<SourceSpans>,
 }
 
 impl<'a> Lookups<'a> {
@@ -1905,6 +2107,7 @@ impl<'a> Lookups<'a> {
             footnote_definitions: HashMap::with_capacity(DEFAULT_CAPACITY),
             allow_unknown_markdown: read_opts.allow_unknown_markdown,
             source,
+            source_spans: RefCell::default(),
         };
 
         result.build_lookups(node, read_opts)?;
@@ -1947,6 +2150,19 @@ impl<'a> Lookups<'a> {
         })
     }
 
+    /// Records where a reference-style link's or image's definition is, so that in-place edits can change its URL.
+    fn record_definition_span(&self, reference: Option<SourceSpan>, identifier: &str) {
+        let definition = self
+            .link_definitions
+            .get(identifier)
+            .and_then(|def| def.position.as_ref());
+        if let (Some(reference), Some(definition)) = (reference, definition) {
+            self.source_spans
+                .borrow_mut()
+                .record_link_definition(reference, SourceSpan::from(definition));
+        }
+    }
+
     fn build_lookups(&mut self, node: &mdast::Node, read_opts: &ReadOptions) -> Result<(), InvalidMd> {
         let x = format!("{node:?}");
         let _ = x;
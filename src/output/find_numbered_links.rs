@@ -30,6 +30,7 @@ impl<'md> ReservedLinkNumbers<'md> {
             match node {
                 MdElem::Doc(doc) => self.build_from_nodes(doc),
                 MdElem::BlockQuote(block) => self.build_from_nodes(&block.body),
+                MdElem::FootnoteDefinition(def) => self.build_from_nodes(&def.body),
                 MdElem::List(list) => {
                     for li in &list.items {
                         self.build_from_nodes(&li.item);
@@ -48,7 +49,11 @@ impl<'md> ReservedLinkNumbers<'md> {
                     }
                 }
                 MdElem::Inline(inline) => self.build_from_inlines(std::iter::once(inline)),
-                MdElem::ThematicBreak | MdElem::CodeBlock(_) | MdElem::FrontMatter(_) | MdElem::BlockHtml(_) => {}
+                MdElem::ThematicBreak
+                | MdElem::CodeBlock(_)
+                | MdElem::FrontMatter(_)
+                | MdElem::FrontMatterValue(_)
+                | MdElem::BlockHtml(_) => {}
             }
         }
     }
@@ -191,6 +191,7 @@ impl<'md> MdWriterState<'_, 'md> {
             MdElem::CodeBlock(block) => self.write_code_block(out, block),
             MdElem::Paragraph(para) => self.write_paragraph(out, para),
             MdElem::BlockQuote(block) => self.write_block_quote(out, block),
+            MdElem::FootnoteDefinition(def) => self.write_footnote_definition(out, def),
             MdElem::List(list) => self.write_list(out, list),
             MdElem::Table(table) => self.write_table(out, table),
             MdElem::Inline(inline) => self.inlines_writer.write_inline_element(out, inline),
@@ -198,6 +199,9 @@ impl<'md> MdWriterState<'_, 'md> {
                 out.write_str(&html.value);
             }),
             MdElem::FrontMatter(front_matter) => self.write_front_matter(out, front_matter),
+            MdElem::FrontMatterValue(value) => out.with_block(Block::Plain, |out| {
+                out.write_str(value.text());
+            }),
         }
     }
 
@@ -227,6 +231,18 @@ impl<'md> MdWriterState<'_, 'md> {
         });
     }
 
+    fn write_footnote_definition<W: SimpleWrite>(&mut self, out: &mut Output<W>, def: &'md FootnoteDefinition) {
+        out.with_block(Block::Plain, |out| {
+            let label = self.inlines_writer.footnote_definition_label(&def.id);
+            out.write_str("[^");
+            out.write_str(&label);
+            out.write_str("]: ");
+            out.with_block(Block::Indent(2), |out| {
+                self.write_md(out, def.body.iter(), false);
+            });
+        });
+    }
+
     fn write_list<W: SimpleWrite>(&mut self, out: &mut Output<W>, list: &'md List) {
         out.with_block(Block::Plain, |out| {
             let mut index = list.starting_index;
@@ -587,8 +603,10 @@ pub(crate) mod tests {
         FrontMatter(FrontMatter{variant: FrontMatterVariant::Toml, ..}),
         FrontMatter(FrontMatter{variant: FrontMatterVariant::Yaml, ..}),
         FrontMatter(FrontMatter{variant: FrontMatterVariant::Json, ..}),
+        FrontMatterValue(_),
         Paragraph(_),
         BlockQuote(_),
+        FootnoteDefinition(_),
         List(_),
         Table(_),
     });
@@ -1413,6 +1431,14 @@ pub(crate) mod tests {
                 }"#},
             );
         }
+
+        #[test]
+        fn value() {
+            check_render(
+                vec![MdElem::FrontMatterValue(FrontMatterValue::Bool(false))],
+                indoc! {"false"},
+            );
+        }
     }
 
     mod inline {
@@ -2183,6 +2209,46 @@ pub(crate) mod tests {
             );
         }
 
+        #[test]
+        fn definition() {
+            check_render(
+                vec![MdElem::FootnoteDefinition(FootnoteDefinition {
+                    id: "a".into(),
+                    body: md_elems!["Hello,\nworld."],
+                })],
+                indoc! {r#"
+                    [^a]: Hello,
+                      world."#},
+            )
+        }
+
+        #[test]
+        fn definition_renumbered_with_its_references() {
+            let ctx = MdContext::empty()
+                .with("a", md_elems!["alpha"])
+                .with("b", md_elems!["bravo"]);
+            check_render_refs_with_ctx(
+                MdWriterOptions::new_with(|mdo| mdo.inline_options.renumber_footnotes = true),
+                (
+                    ctx,
+                    vec![MdElem::FootnoteDefinition(FootnoteDefinition {
+                        id: "b".into(),
+                        body: md_elems![Paragraph {
+                            body: vec![
+                                mdq_inline!("see"),
+                                Inline::Footnote("a".into()),
+                                Inline::Footnote("b".into()),
+                            ],
+                        }],
+                    })],
+                ),
+                indoc! {r#"
+                    [^1]: see[^2][^1]
+
+                    [^2]: alpha"#},
+            );
+        }
+
         fn footnote_a_in_paragraph() -> (MdContext, Paragraph) {
             (
                 MdContext::empty().with("a", md_elems!("the footnote text")),
@@ -118,6 +118,15 @@ impl<'md> MdInlinesWriter<'md> {
         result
     }
 
+    /// The label for a selected footnote definition, renumbered the same way as its references. The footnote then
+    /// counts as seen, so references to it (including from its own text) don't write its definition a second time.
+    pub(crate) fn footnote_definition_label(&mut self, footnote_id: &'md FootnoteId) -> String {
+        self.seen_footnotes.insert(footnote_id.as_str());
+        self.footnote_transformer
+            .new_to_stringer()
+            .transform(footnote_id.as_str())
+    }
+
     pub(crate) fn write_line<I, W>(&mut self, out: &mut Output<W>, elems: I)
     where
         I: IntoIterator<Item = &'md Inline>,
@@ -212,6 +221,9 @@ impl<'md> MdInlinesWriter<'md> {
                 MdElem::BlockQuote(block) => {
                     self.find_references_in_footnote_elems(&block.body);
                 }
+                MdElem::FootnoteDefinition(def) => {
+                    self.find_references_in_footnote_elems(&def.body);
+                }
                 MdElem::List(list) => {
                     for li in &list.items {
                         self.find_references_in_footnote_elems(&li.item);
@@ -234,7 +246,11 @@ impl<'md> MdInlinesWriter<'md> {
                 MdElem::Inline(inline) => {
                     self.find_references_in_footnote_inlines([inline]);
                 }
-                MdElem::CodeBlock(_) | MdElem::FrontMatter(_) | MdElem::BlockHtml(_) | MdElem::ThematicBreak => {
+                MdElem::CodeBlock(_)
+                | MdElem::FrontMatter(_)
+                | MdElem::FrontMatterValue(_)
+                | MdElem::BlockHtml(_)
+                | MdElem::ThematicBreak => {
                     // nothing
                 }
             }
@@ -366,10 +382,8 @@ impl<'md> MdInlinesWriter<'md> {
                 '[' => {
                     unbalanced_opening_brackets.push(index);
                 }
-                ']' => {
-                    if unbalanced_opening_brackets.pop().is_none() {
-                        unbalanced_closing_brackets.push(index);
-                    }
+                ']' if unbalanced_opening_brackets.pop().is_none() => {
+                    unbalanced_closing_brackets.push(index);
                 }
                 _ => (),
             };
@@ -82,6 +82,10 @@ where
     match node {
         MdElem::Doc(doc) => write_plain_result(out, doc.iter()),
         MdElem::BlockQuote(block) => write_plain_result(out, block.body.iter()),
+        MdElem::FootnoteDefinition(def) => {
+            write!(out, "[^{}]: ", def.id.as_str())?;
+            write_plain_result(out, def.body.iter())
+        }
         MdElem::CodeBlock(CodeBlock { value: body, .. }) | MdElem::FrontMatter(FrontMatter { body, .. }) => {
             if !body.is_empty() {
                 writeln!(out, "{body}")?;
@@ -122,6 +126,10 @@ where
             writeln!(out, "{}", h.value)?;
             writeln!(out)
         }
+        MdElem::FrontMatterValue(value) => {
+            writeln!(out, "{}", value.text())?;
+            writeln!(out)
+        }
         MdElem::ThematicBreak => Ok(()),
     }
 }
@@ -181,8 +189,10 @@ mod test {
     variants_checker!(VARIANTS_CHECKER = MdElem {
         Doc(_),
         BlockQuote(_),
+        FootnoteDefinition(_),
         CodeBlock(_),
         FrontMatter(_),
+        FrontMatterValue(_),
         Inline(_),
         List(_),
         Paragraph(_),
@@ -244,6 +254,20 @@ mod test {
         )
     }
 
+    #[test]
+    fn footnote_definition() {
+        check_plain(
+            MdElem::FootnoteDefinition(FootnoteDefinition {
+                id: "a".into(),
+                body: md_elems!("hello, world"),
+            }),
+            Expect {
+                with_breaks: "[^a]: hello, world\n",
+                no_breaks: "[^a]: hello, world\n",
+            },
+        )
+    }
+
     #[test]
     fn code_block_empty() {
         let md_elem = md_elem!(CodeBlock {
@@ -304,6 +328,17 @@ mod test {
         )
     }
 
+    #[test]
+    fn front_matter_value() {
+        check_plain(
+            MdElem::FrontMatterValue(FrontMatterValue::Number("42".to_string())),
+            Expect {
+                with_breaks: "42\n",
+                no_breaks: "42\n",
+            },
+        )
+    }
+
     #[test]
     fn inline() {
         let inline =
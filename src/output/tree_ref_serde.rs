@@ -1,4 +1,5 @@
 use crate::md_elem::elem::*;
+use crate::md_elem::source_spans::SpanCursor;
 use crate::md_elem::*;
 use crate::output::fmt_md_inlines::{InlineElemOptions, MdInlinesWriter, UrlAndTitle};
 use crate::output::link_transform::LinkLabel;
@@ -10,18 +11,60 @@ use std::collections::HashMap;
 /// A wrapper around [`&[MdElem]`](MdElem) that implements [`Serialize`].
 #[derive(Clone, Default, Debug, Serialize)]
 pub struct SerializableMd<'md> {
-    items: Vec<SerdeElem<'md>>,
+    items: Vec<PositionedElem<'md>>,
     #[serde(skip_serializing_if = "HashMap::is_empty")]
     links: HashMap<Cow<'md, str>, UrlAndTitle<'md>>,
     #[serde(skip_serializing_if = "HashMap::is_empty")]
-    footnotes: HashMap<String, Vec<SerdeElem<'md>>>,
+    footnotes: HashMap<String, Vec<PositionedElem<'md>>>,
+}
+
+/// A [SerdeElem], along with where it came from in the Markdown source (if that was requested, and the element is in the
+/// source as-is).
+#[derive(Clone, Debug, Serialize)]
+pub(crate) struct PositionedElem<'md> {
+    #[serde(flatten)]
+    elem: SerdeElem<'md>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    position: Option<SpanSerde>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub(crate) struct SpanSerde {
+    start: PointSerde,
+    end: PointSerde,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub(crate) struct PointSerde {
+    line: usize,
+    column: usize,
+    offset: usize,
+}
+
+impl From<SourceSpan> for SpanSerde {
+    fn from(value: SourceSpan) -> Self {
+        let point = |point: SourcePoint| PointSerde {
+            line: point.line,
+            column: point.column,
+            offset: point.offset,
+        };
+        Self {
+            start: point(value.start),
+            end: point(value.end),
+        }
+    }
 }
 
 #[derive(Clone, Debug, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub(crate) enum SerdeElem<'md> {
-    Document(Vec<SerdeElem<'md>>),
-    BlockQuote(Vec<SerdeElem<'md>>),
+    Document(Vec<PositionedElem<'md>>),
+    BlockQuote(Vec<PositionedElem<'md>>),
+    FootnoteDefinition {
+        label: String,
+        body: Vec<PositionedElem<'md>>,
+    },
     CodeBlock {
         code: &'md String,
 
@@ -38,6 +81,8 @@ pub(crate) enum SerdeElem<'md> {
         body: &'md String,
         variant: &'static str,
     },
+    #[serde(serialize_with = "serialize_front_matter_value")]
+    FrontMatterValue(&'md FrontMatterValue),
     Paragraph(String),
     Link {
         display: String,
@@ -53,7 +98,7 @@ pub(crate) enum SerdeElem<'md> {
     Section {
         depth: u8,
         title: String,
-        body: Vec<SerdeElem<'md>>,
+        body: Vec<PositionedElem<'md>>,
     },
     #[serde(serialize_with = "serialize_thematic_break")]
     ThematicBreak,
@@ -70,6 +115,19 @@ fn serialize_thematic_break<S: Serializer>(ser: S) -> Result<S::Ok, S::Error> {
     ser.serialize_none()
 }
 
+/// Front matter values keep their JSON types, so that `draft: false` comes out as `false` rather than `"false"`.
+fn serialize_front_matter_value<S: Serializer>(value: &&FrontMatterValue, ser: S) -> Result<S::Ok, S::Error> {
+    match value {
+        FrontMatterValue::Null => ser.serialize_none(),
+        FrontMatterValue::Bool(b) => ser.serialize_bool(*b),
+        FrontMatterValue::Number(text) => match text.parse::<serde_json::Number>() {
+            Ok(number) => number.serialize(ser),
+            Err(_) => ser.serialize_str(text), // like YAML's .inf and .nan, which JSON doesn't have
+        },
+        FrontMatterValue::String(text) => ser.serialize_str(text),
+    }
+}
+
 #[derive(Clone, Debug, Serialize)]
 pub(crate) struct LinkSerde<'md> {
     url: &'md String,
@@ -125,13 +183,16 @@ impl From<Option<ColumnAlignment>> for AlignSerde {
 
 #[derive(Clone, Debug, Serialize)]
 pub(crate) struct LiSerde<'md> {
-    item: Vec<SerdeElem<'md>>,
+    item: Vec<PositionedElem<'md>>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
     index: Option<u32>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
     checked: &'md Option<bool>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    position: Option<SpanSerde>,
 }
 
 #[derive(Clone, Debug, Serialize)]
@@ -150,6 +211,39 @@ pub(crate) enum CodeBlockType {
 
 impl<'md> SerializableMd<'md> {
     pub fn new(elems: &'md [MdElem], ctx: &'md MdContext, opts: InlineElemOptions) -> Self {
+        Self::build(elems, ctx, opts, None, &[])
+    }
+
+    /// Like [`Self::new`], but each block element and link also has a `"position"`: where it came from in the Markdown
+    /// source, with the `"line"`, `"column"` and byte `"offset"` of its `"start"` and `"end"`.
+    ///
+    /// Elements that aren't in the source as-is (for example, because a replacement changed them) don't have a position.
+    pub fn with_positions(elems: &'md [MdElem], ctx: &'md MdContext, opts: InlineElemOptions) -> Self {
+        Self::build(elems, ctx, opts, Some(SpanCursor::new(ctx)), &[])
+    }
+
+    /// Like [`Self::with_positions`], but each element's position is the first one that starts at or after the
+    /// corresponding byte offset in `starts`.
+    ///
+    /// Identical elements share their positions, so without these, each element gets the first position after the
+    /// previous element's. That's only right if the elements are in document order, and there aren't identical ones in
+    /// between that weren't selected.
+    pub(crate) fn with_positions_from(
+        elems: &'md [MdElem],
+        ctx: &'md MdContext,
+        opts: InlineElemOptions,
+        starts: &[usize],
+    ) -> Self {
+        Self::build(elems, ctx, opts, Some(SpanCursor::new(ctx)), starts)
+    }
+
+    fn build(
+        elems: &'md [MdElem],
+        ctx: &'md MdContext,
+        opts: InlineElemOptions,
+        mut spans: Option<SpanCursor<'md>>,
+        starts: &[usize],
+    ) -> Self {
         let mut inlines_writer = MdInlinesWriter::new(ctx, opts, elems);
         const DEFAULT_CAPACITY: usize = 16; // we could compute these, but it's not really worth it
         let mut result = SerializableMd {
@@ -157,8 +251,11 @@ impl<'md> SerializableMd<'md> {
             links: HashMap::with_capacity(DEFAULT_CAPACITY),
             footnotes: HashMap::with_capacity(DEFAULT_CAPACITY),
         };
-        for elem in elems {
-            let top = SerdeElem::build(elem, &mut inlines_writer);
+        for (idx, elem) in elems.iter().enumerate() {
+            if let (Some(spans), Some(start)) = (spans.as_mut(), starts.get(idx)) {
+                spans.seek(*start);
+            }
+            let top = SerdeElem::build(elem, &mut inlines_writer, &mut spans);
             result.items.push(top);
         }
         for (link_label, url) in inlines_writer.drain_pending_links() {
@@ -169,9 +266,12 @@ impl<'md> SerializableMd<'md> {
             result.links.insert(link_to_str, url);
         }
         for (footnote_name, footnote_contents) in inlines_writer.drain_pending_footnotes() {
+            if let Some(spans) = spans.as_mut() {
+                spans.restart();
+            }
             result.footnotes.insert(
                 footnote_name,
-                SerdeElem::build_multi(footnote_contents, &mut inlines_writer),
+                SerdeElem::build_multi(footnote_contents, &mut inlines_writer, &mut spans),
             );
         }
         result
@@ -179,21 +279,49 @@ impl<'md> SerializableMd<'md> {
 }
 
 impl<'md> SerdeElem<'md> {
-    fn build_multi<M>(elems: &'md [M], inlines_writer: &mut MdInlinesWriter<'md>) -> Vec<Self>
+    fn build_multi<M>(
+        elems: &'md [M],
+        inlines_writer: &mut MdInlinesWriter<'md>,
+        spans: &mut Option<SpanCursor<'md>>,
+    ) -> Vec<PositionedElem<'md>>
     where
         M: Borrow<MdElem>,
     {
         let mut result = Vec::with_capacity(elems.len());
         for elem in elems {
-            result.push(Self::build(elem.borrow(), inlines_writer));
+            result.push(Self::build(elem.borrow(), inlines_writer, spans));
         }
         result
     }
 
-    fn build(elem: &'md MdElem, inlines_writer: &mut MdInlinesWriter<'md>) -> Self {
+    fn build(
+        elem: &'md MdElem,
+        inlines_writer: &mut MdInlinesWriter<'md>,
+        spans: &mut Option<SpanCursor<'md>>,
+    ) -> PositionedElem<'md> {
+        let span = spans.as_mut().and_then(|spans| spans.enter(elem));
+        let elem = Self::build_elem(elem, inlines_writer, spans);
+        if let Some(spans) = spans.as_mut() {
+            spans.exit(span);
+        }
+        PositionedElem {
+            elem,
+            position: span.map(SpanSerde::from),
+        }
+    }
+
+    fn build_elem(
+        elem: &'md MdElem,
+        inlines_writer: &mut MdInlinesWriter<'md>,
+        spans: &mut Option<SpanCursor<'md>>,
+    ) -> Self {
         match elem {
-            MdElem::Doc(doc) => Self::Document(Self::build_multi(doc, inlines_writer)),
-            MdElem::BlockQuote(bq) => Self::BlockQuote(Self::build_multi(&bq.body, inlines_writer)),
+            MdElem::Doc(doc) => Self::Document(Self::build_multi(doc, inlines_writer, spans)),
+            MdElem::BlockQuote(bq) => Self::BlockQuote(Self::build_multi(&bq.body, inlines_writer, spans)),
+            MdElem::FootnoteDefinition(def) => Self::FootnoteDefinition {
+                label: inlines_writer.footnote_definition_label(&def.id),
+                body: Self::build_multi(&def.body, inlines_writer, spans),
+            },
             MdElem::CodeBlock(cb) => {
                 let CodeBlock { variant, value } = cb;
                 let (code_type, metadata, language) = match variant {
@@ -216,6 +344,7 @@ impl<'md> SerdeElem<'md> {
                 variant: fm.variant.name(),
                 body: &fm.body,
             },
+            MdElem::FrontMatterValue(value) => Self::FrontMatterValue(value),
             MdElem::Inline(Inline::Link(link)) => match link {
                 crate::md_elem::elem::Link::Standard(standard_link) => Self::Link {
                     display: inlines_to_string(&standard_link.display, inlines_writer),
@@ -251,10 +380,22 @@ impl<'md> SerdeElem<'md> {
                             Some(old)
                         }
                     };
+                    // Each item's span is keyed as a single-item list; see MdContext::get_source_spans.
+                    let span = spans.as_mut().and_then(|spans| {
+                        spans.enter(&MdElem::List(List {
+                            starting_index: index,
+                            items: vec![li.clone()],
+                        }))
+                    });
+                    let item = Self::build_multi(&li.item, inlines_writer, spans);
+                    if let Some(spans) = spans.as_mut() {
+                        spans.exit(span);
+                    }
                     li_refs.push(LiSerde {
-                        item: Self::build_multi(&li.item, inlines_writer),
+                        item,
                         checked: &li.checked,
                         index,
+                        position: span.map(SpanSerde::from),
                     })
                 }
                 Self::List(li_refs)
@@ -267,7 +408,7 @@ impl<'md> SerdeElem<'md> {
                 let Section { depth, title, body } = section;
                 let depth = *depth;
                 let title = inlines_to_string(title, inlines_writer);
-                let body = Self::build_multi(body, inlines_writer);
+                let body = Self::build_multi(body, inlines_writer, spans);
                 Self::Section { depth, title, body }
             }
             MdElem::Table(table) => {
@@ -311,8 +452,10 @@ mod tests {
         Doc(_),
 
         BlockQuote(_),
+        FootnoteDefinition(_),
         CodeBlock(_),
         FrontMatter(_),
+        FrontMatterValue(_),
         Inline(_),
         List(_),
         Paragraph(_),
@@ -401,6 +544,26 @@ mod tests {
         );
     }
 
+    #[test]
+    fn footnote_definition() {
+        check(
+            MdElem::FootnoteDefinition(FootnoteDefinition {
+                id: "a".into(),
+                body: md_elems!("alpha"),
+            }),
+            json_str!(
+                {"items": [
+                    {"footnote_definition": {
+                        "label": "a",
+                        "body": [
+                            {"paragraph": "alpha"}
+                        ]
+                    }}
+                ]}
+            ),
+        );
+    }
+
     #[test]
     fn code_block_simple() {
         check(
@@ -442,6 +605,27 @@ mod tests {
         );
     }
 
+    #[test]
+    fn code_block_math() {
+        check(
+            MdElem::CodeBlock(CodeBlock {
+                variant: CodeVariant::Math {
+                    metadata: Some("my-metadata".to_string()),
+                },
+                value: "x = 1".to_string(),
+            }),
+            json_str!(
+                {"items": [
+                    {"code_block": {
+                        "code": "x = 1",
+                        "type": "math",
+                        "metadata": "my-metadata"
+                    }}
+                ]}
+            ),
+        );
+    }
+
     #[test]
     fn front_matter() {
         check(
@@ -460,6 +644,34 @@ mod tests {
         );
     }
 
+    #[test]
+    fn front_matter_values() {
+        check(
+            MdElem::FrontMatterValue(FrontMatterValue::Bool(false)),
+            json_str!({"items": [{"front_matter_value": false}]}),
+        );
+        check(
+            MdElem::FrontMatterValue(FrontMatterValue::Null),
+            json_str!({"items": [{"front_matter_value": null}]}),
+        );
+        check(
+            MdElem::FrontMatterValue(FrontMatterValue::Number("42".to_string())),
+            json_str!({"items": [{"front_matter_value": 42}]}),
+        );
+        check(
+            MdElem::FrontMatterValue(FrontMatterValue::Number("1.5".to_string())),
+            json_str!({"items": [{"front_matter_value": 1.5}]}),
+        );
+        check(
+            MdElem::FrontMatterValue(FrontMatterValue::Number(".inf".to_string())),
+            json_str!({"items": [{"front_matter_value": ".inf"}]}),
+        );
+        check(
+            MdElem::FrontMatterValue(FrontMatterValue::String("42".to_string())),
+            json_str!({"items": [{"front_matter_value": "42"}]}),
+        );
+    }
+
     #[test]
     fn image() {
         check_md_ref(
@@ -663,6 +875,39 @@ mod tests {
         );
     }
 
+    #[test]
+    fn with_positions() {
+        let doc = MdDoc::parse("one\n\n> one", &ParseOptions::default()).unwrap();
+        let opts = InlineElemOptions {
+            link_format: LinkTransform::Keep,
+            renumber_footnotes: false,
+        };
+
+        let actual = serde_json::to_string(&SerializableMd::with_positions(&doc.roots[1..], &doc.ctx, opts)).unwrap();
+        assert_eq!(
+            actual,
+            json_str!(
+                {"items":[
+                    {
+                        "block_quote":[
+                            {
+                                "paragraph":"one",
+                                "position":{
+                                    "start":{"line":3,"column":3,"offset":7},
+                                    "end":{"line":3,"column":6,"offset":10}
+                                }
+                            }
+                        ],
+                        "position":{
+                            "start":{"line":3,"column":1,"offset":5},
+                            "end":{"line":3,"column":6,"offset":10}
+                        }
+                    }
+                ]}
+            )
+        );
+    }
+
     fn check(given: MdElem, expect: &str) {
         let opts = InlineElemOptions {
             link_format: LinkTransform::Keep,
@@ -8,9 +8,31 @@ fn rule_to_string(rule: &crate::query::pest::Rule) -> &'static str {
         crate::query::pest::Rule::WHITESPACE => "whitespace",
         crate::query::pest::Rule::top => "valid query",
         crate::query::pest::Rule::selector_chain => "one or more selectors",
+        crate::query::pest::Rule::selector_stage => "selector",
+        crate::query::pest::Rule::selector_group => "(",
+        crate::query::pest::Rule::select_position => ":first, :last, :nth(n), or [start..end]",
+        crate::query::pest::Rule::position_first => ":first",
+        crate::query::pest::Rule::position_last => ":last",
+        crate::query::pest::Rule::position_nth => ":nth(n)",
+        crate::query::pest::Rule::position_slice => "[start..end]",
+        crate::query::pest::Rule::position_nth_index
+        | crate::query::pest::Rule::position_slice_start
+        | crate::query::pest::Rule::position_slice_end => "position",
+        crate::query::pest::Rule::task_action => "check, uncheck, or toggle",
+        crate::query::pest::Rule::task_action_check => "check",
+        crate::query::pest::Rule::task_action_uncheck => "uncheck",
+        crate::query::pest::Rule::task_action_toggle => "toggle",
         crate::query::pest::Rule::selector => "selector",
         crate::query::pest::Rule::selector_delim | crate::query::pest::Rule::explicit_space => "space",
         crate::query::pest::Rule::select_section | crate::query::pest::Rule::section_start => "#",
+        crate::query::pest::Rule::section_path_delim | crate::query::pest::Rule::section_path_ahead => "> or /",
+        crate::query::pest::Rule::select_section_slug => "#slug",
+        crate::query::pest::Rule::section_slug => "section slug",
+        crate::query::pest::Rule::section_hashes => "# through ######",
+        crate::query::pest::Rule::section_depth_range => "#{min,max}",
+        crate::query::pest::Rule::section_depth_min | crate::query::pest::Rule::section_depth_max => "section depth",
+        crate::query::pest::Rule::section_depth_comma => ",",
+        crate::query::pest::Rule::select_thematic_break | crate::query::pest::Rule::thematic_break_start => "---",
         crate::query::pest::Rule::select_list_item | crate::query::pest::Rule::list_start => "- or 1.",
         crate::query::pest::Rule::list_ordered => "-",
         crate::query::pest::Rule::list_task_options => "[ ], [x], or [?]",
@@ -18,18 +40,35 @@ fn rule_to_string(rule: &crate::query::pest::Rule) -> &'static str {
         crate::query::pest::Rule::task_unchecked => "[ ]",
         crate::query::pest::Rule::task_either => "[?]",
         crate::query::pest::Rule::task_end => "]",
+        crate::query::pest::Rule::select_footnote | crate::query::pest::Rule::footnote_start => "[^",
         crate::query::pest::Rule::select_link | crate::query::pest::Rule::link_start => "[ or ![",
         crate::query::pest::Rule::image_start => "![",
         crate::query::pest::Rule::select_block_quote | crate::query::pest::Rule::select_block_quote_start => ">",
         crate::query::pest::Rule::select_code_block | crate::query::pest::Rule::code_block_start => "```",
+        crate::query::pest::Rule::select_math_block | crate::query::pest::Rule::math_block_start => "$$",
+        crate::query::pest::Rule::select_inline_code | crate::query::pest::Rule::inline_code_start => "`",
+        crate::query::pest::Rule::select_inline_math | crate::query::pest::Rule::inline_math_start => "$",
+        crate::query::pest::Rule::select_span => "*, **, or ~~",
+        crate::query::pest::Rule::select_strong | crate::query::pest::Rule::strong_start => "**",
+        crate::query::pest::Rule::select_emphasis | crate::query::pest::Rule::emphasis_start => "*",
+        crate::query::pest::Rule::select_delete | crate::query::pest::Rule::delete_start => "~~",
         crate::query::pest::Rule::select_front_matter | crate::query::pest::Rule::front_matter_start => "+++",
+        crate::query::pest::Rule::front_matter_path => "front matter key path",
+        crate::query::pest::Rule::front_matter_key => "front matter key",
+        crate::query::pest::Rule::front_matter_index => "front matter index",
         crate::query::pest::Rule::select_html | crate::query::pest::Rule::html_start => "</>",
         crate::query::pest::Rule::select_paragraph | crate::query::pest::Rule::select_paragraph_start => "P:",
         crate::query::pest::Rule::select_table | crate::query::pest::Rule::table_start => ":-:",
+        crate::query::pest::Rule::select_not | crate::query::pest::Rule::not_start => "not(...)",
+        crate::query::pest::Rule::select_has | crate::query::pest::Rule::has_start => "has(...)",
         crate::query::pest::Rule::string
+        | crate::query::pest::Rule::section_title
         | crate::query::pest::Rule::string_for_unit_tests__do_not_use_angle
         | crate::query::pest::Rule::string_for_unit_tests__do_not_use_pipe => "string",
-        crate::query::pest::Rule::unquoted_string => "unquoted string",
+        crate::query::pest::Rule::unquoted_string | crate::query::pest::Rule::section_unquoted_string => {
+            "unquoted string"
+        }
+        crate::query::pest::Rule::alternative_ahead => ",",
         crate::query::pest::Rule::regex => "regex",
         crate::query::pest::Rule::regex_char => "regex character",
         crate::query::pest::Rule::regex_escaped_slash => "/",
@@ -110,11 +149,11 @@ impl ParseError {
     ///
     /// ```
     /// use mdq::select::Selector;
-    /// let query_text = "$ ! invalid query string ! $";
+    /// let query_text = "@ ! invalid query string ! @";
     /// let parse_error = Selector::try_from(query_text).expect_err("expected an error");
     /// let expected_error = r" --> 1:1
     ///   |
-    /// 1 | $ ! invalid query string ! $
+    /// 1 | @ ! invalid query string ! @
     ///   | ^---
     ///   |
     ///   = expected valid query";
@@ -139,7 +178,7 @@ impl ParseError {
     ///
     /// ```
     /// use mdq::select::Selector;
-    /// let query_text = "$ ! invalid query string ! $";
+    /// let query_text = "@ ! invalid query string ! @";
     /// let parse_error = Selector::try_from(query_text).expect_err("expected an error");
     /// let output = parse_error.to_string_with_suggestions(query_text);
     /// assert!(output.contains("expected valid query"));
@@ -157,15 +196,39 @@ impl ParseError {
                     // For custom errors, provide general suggestions
                     error_string.push_str("\n\nSuggestions:");
                     error_string.push_str("\n  • Use # for sections (e.g., '# My Section')");
+                    error_string
+                        .push_str("\n  • Use / between titles for a section within a section (e.g., '# API / Errors')");
+                    error_string.push_str("\n  • Use #slug for sections by their anchor (e.g., '#my-section')");
                     error_string.push_str("\n  • Use - for list items (e.g., '- List item')");
+                    error_string
+                        .push_str("\n  • Use --- for thematic breaks, or the text between them (e.g., '--- text')");
                     error_string.push_str("\n  • Use [] for links (e.g., '[text](url)')");
+                    error_string.push_str("\n  • Use [^] for footnotes (e.g., '[^label] footnote text')");
                     error_string.push_str("\n  • Use > for blockquotes (e.g., '> Quote text')");
                     error_string.push_str("\n  • Use ``` for code blocks (e.g., '```rust code')");
+                    error_string.push_str("\n  • Use $$ for math blocks (e.g., '$$ formula')");
+                    error_string.push_str("\n  • Use ` for inline code (e.g., '`code`')");
+                    error_string.push_str("\n  • Use $ for inline math (e.g., '$formula$')");
+                    error_string
+                        .push_str("\n  • Use *, ** or ~~ for emphasis, strong or deleted text (e.g., '**bold**')");
                     error_string.push_str("\n  • Use +++ for front matter (e.g., '+++ toml')");
+                    error_string.push_str("\n  • Use +++ .key for values within front matter (e.g., '+++ .tags[0]')");
                     error_string.push_str("\n  • Use </> for HTML (e.g., '</> <div>')");
                     error_string.push_str("\n  • Use P: for paragraphs (e.g., 'P: paragraph text')");
                     error_string.push_str("\n  • Use :-: for tables (e.g., ':-: column | row')");
-                    error_string.push_str("\n  • Use | to separate multiple selectors (e.g., '# Section | - List item')");
+                    error_string
+                        .push_str("\n  • Use | to separate multiple selectors (e.g., '# Section | - List item')");
+                    error_string.push_str(
+                        "\n  • Use (..., ...) to select any of several selectors (e.g., '(# Usage, # Examples)')",
+                    );
+                    error_string
+                        .push_str("\n  • Use not(...) to drop elements that match (e.g., '# * | not(# Deprecated)')");
+                    error_string.push_str(
+                        "\n  • Use has(...) to keep elements that contain a match (e.g., '# * | has(- [ ])')",
+                    );
+                    error_string.push_str("\n  • Use :first, :last, :nth(n) or [start..end] to pick results by position (e.g., '# Changelog | - | :first')");
+                    error_string
+                        .push_str("\n  • Use check, uncheck or toggle to change tasks (e.g., '- [ ] deploy | check')");
                 }
                 error_string
             }
@@ -219,10 +282,13 @@ impl From<&crate::query::Pair<'_>> for DetachedSpan {
 fn extract_failed_rule_from_pest_error(error: &crate::query::Error) -> Option<&str> {
     // Access the inner pest error to extract rule information
     let pest_error = &error.pest_error;
-    
+
     // Try to extract the expected rule from the error variant
     match &pest_error.variant {
-        pest::error::ErrorVariant::ParsingError { positives, negatives: _ } => {
+        pest::error::ErrorVariant::ParsingError {
+            positives,
+            negatives: _,
+        } => {
             // Return the first positive rule that was expected
             positives.first().map(|rule| rule_to_string(rule))
         }
@@ -62,9 +62,29 @@ impl Query {
                 Rule::WHITESPACE => "whitespace",
                 Rule::top => "valid query",
                 Rule::selector_chain => "one or more selectors",
+                Rule::selector_stage => "selector",
+                Rule::selector_group => "_(_",
+                Rule::select_position => "_:first_, _:last_, _:nth(n)_, or _[start..end]_",
+                Rule::position_first => "_:first_",
+                Rule::position_last => "_:last_",
+                Rule::position_nth => "_:nth(n)_",
+                Rule::position_slice => "_[start..end]_",
+                Rule::position_nth_index | Rule::position_slice_start | Rule::position_slice_end => "position",
+                Rule::task_action => "_check_, _uncheck_, or _toggle_",
+                Rule::task_action_check => "_check_",
+                Rule::task_action_uncheck => "_uncheck_",
+                Rule::task_action_toggle => "_toggle_",
                 Rule::selector => "selector",
                 Rule::selector_delim | Rule::explicit_space => "space",
                 Rule::select_section | Rule::section_start => "_#_",
+                Rule::section_path_delim | Rule::section_path_ahead => "_>_ or _/_",
+                Rule::select_section_slug => "_#slug_",
+                Rule::section_slug => "section slug",
+                Rule::section_hashes => "_#_ through _######_",
+                Rule::section_depth_range => "_#{min,max}_",
+                Rule::section_depth_min | Rule::section_depth_max => "section depth",
+                Rule::section_depth_comma => "_,_",
+                Rule::select_thematic_break | Rule::thematic_break_start => "_---_",
                 Rule::select_list_item | Rule::list_start => "_-_ or _1._",
                 Rule::list_ordered => "_-_",
                 Rule::list_task_options => "_[ ]_, _[x]_, or _[?]_",
@@ -72,18 +92,33 @@ impl Query {
                 Rule::task_unchecked => "_[x]_",
                 Rule::task_either => "_[?]_",
                 Rule::task_end => "_]_",
+                Rule::select_footnote | Rule::footnote_start => "_[^_",
                 Rule::select_link | Rule::link_start => "_[_ or _![_",
                 Rule::image_start => "_![_",
                 Rule::select_block_quote | Rule::select_block_quote_start => "_>_",
                 Rule::select_code_block | Rule::code_block_start => "_```_",
+                Rule::select_math_block | Rule::math_block_start => "_$$_",
+                Rule::select_inline_code | Rule::inline_code_start => "_`_",
+                Rule::select_inline_math | Rule::inline_math_start => "_$_",
+                Rule::select_span => "_*_, _**_, or _~~_",
+                Rule::select_strong | Rule::strong_start => "_**_",
+                Rule::select_emphasis | Rule::emphasis_start => "_*_",
+                Rule::select_delete | Rule::delete_start => "_~~_",
                 Rule::select_front_matter | Rule::front_matter_start => "_+++_",
+                Rule::front_matter_path => "front matter key path",
+                Rule::front_matter_key => "front matter key",
+                Rule::front_matter_index => "front matter index",
                 Rule::select_html | Rule::html_start => "_</>_",
                 Rule::select_paragraph | Rule::select_paragraph_start => "_P:_",
                 Rule::select_table | Rule::table_start => "_:-:_",
+                Rule::select_not | Rule::not_start => "_not(...)_",
+                Rule::select_has | Rule::has_start => "_has(...)_",
                 Rule::string
+                | Rule::section_title
                 | Rule::string_for_unit_tests__do_not_use_angle
                 | Rule::string_for_unit_tests__do_not_use_pipe => "string",
-                Rule::unquoted_string => "unquoted string",
+                Rule::unquoted_string | Rule::section_unquoted_string => "unquoted string",
+                Rule::alternative_ahead => "_,_",
                 Rule::regex => "regex",
                 Rule::regex_char => "regex character",
                 Rule::regex_escaped_slash => "_/_",
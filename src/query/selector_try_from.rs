@@ -1,27 +1,39 @@
-use crate::md_elem::elem::FrontMatterVariant;
+use crate::md_elem::elem::{FrontMatterVariant, SpanVariant};
 use crate::query::pest::Rule;
 use crate::query::traversal::{ByRule, OneOf, PairMatcher};
 use crate::query::traversal_composites::{
-    BlockQuoteTraverser, CodeBlockTraverser, FrontMatterTraverser, HtmlTraverser, LinkTraverser, ListItemTraverser,
-    ParagraphTraverser, SectionResults, SectionTraverser, TableTraverser,
+    BlockQuoteTraverser, CodeBlockTraverser, FootnoteTraverser, FrontMatterTraverser, HtmlTraverser,
+    InlineCodeTraverser, InlineMathTraverser, LinkTraverser, ListItemTraverser, MathBlockTraverser, ParagraphTraverser,
+    PositionTraverser, SectionResults, SectionTraverser, SpanTraverser, TableTraverser, ThematicBreakTraverser,
 };
 use crate::query::{DetachedSpan, InnerParseError, Pair, Pairs, Query};
 use crate::select::{
-    BlockQuoteMatcher, CodeBlockMatcher, FrontMatterMatcher, HtmlMatcher, LinklikeMatcher, ListItemMatcher,
-    ListItemTask, MatchReplace, Matcher, ParagraphMatcher, SectionMatcher, Selector, TableMatcher,
+    BlockQuoteMatcher, CodeBlockMatcher, FootnoteMatcher, FrontMatterKey, FrontMatterKeyMatcher, FrontMatterMatcher,
+    HtmlMatcher, InlineCodeMatcher, InlineMathMatcher, LinklikeMatcher, ListItemMatcher, ListItemTask, MatchReplace,
+    Matcher, MathBlockMatcher, ParagraphMatcher, Position, SectionDepth, SectionMatcher, SectionSlugMatcher, Selector,
+    SpanMatcher, TableMatcher, TaskAction, ThematicBreakMatcher,
 };
 
+/// Markdown only has six levels of headings (`#` through `######`).
+const MAX_SECTION_DEPTH: u8 = 6;
+
 impl Selector {
     fn new_from_pairs(root: Pairs) -> Result<Self, InnerParseError> {
         // Get "all" the selector chains; there should be at most 1.
         let selector_chains = ByRule::new(Rule::selector_chain).find_all_in(root);
         let mut selectors: Vec<Self> = Vec::new();
         for chain in selector_chains {
-            // within the chain, get the selectors; for each one, get its inners (there should be exactly one) and get
-            // its selector.
-            let selector_inners = ByRule::new(Rule::selector).find_all_in(chain.into_inner());
-            for selector_pair in selector_inners {
-                let selector = Self::find_selector(selector_pair)?;
+            // within the chain, get the stages; within each stage, get the selectors (there may be several, as
+            // alternatives). For each selector, get its inners (there should be exactly one) and get its selector.
+            let stages = ByRule::new(Rule::selector_stage).find_all_in(chain.into_inner());
+            for stage in stages {
+                let stage_inners = stage.into_inner();
+                let selector = match stage_inners.peek() {
+                    Some(whole_stage) if matches!(whole_stage.as_rule(), Rule::select_position | Rule::task_action) => {
+                        Self::find_selector(whole_stage)?
+                    }
+                    _ => Self::find_alternatives(stage_inners)?,
+                };
                 selectors.push(selector);
             }
         }
@@ -39,6 +51,64 @@ impl Selector {
         Ok(parsed_selectors)
     }
 
+    fn parse_section_depth(pair: Option<Pair>) -> Result<Option<u8>, InnerParseError> {
+        let Some(pair) = pair else {
+            return Ok(None);
+        };
+        match pair.as_str().parse::<u8>() {
+            Ok(depth) if (1..=MAX_SECTION_DEPTH).contains(&depth) => Ok(Some(depth)),
+            _ => Err(InnerParseError::Other(
+                DetachedSpan::from(&pair),
+                format!("section depth must be between 1 and {MAX_SECTION_DEPTH}"),
+            )),
+        }
+    }
+
+    fn parse_position_index(pair: Option<Pair>) -> Result<Option<usize>, InnerParseError> {
+        let Some(pair) = pair else {
+            return Ok(None);
+        };
+        match pair.as_str().parse::<usize>() {
+            Ok(index) => Ok(Some(index)),
+            Err(_) => Err(InnerParseError::Other(
+                DetachedSpan::from(&pair),
+                "position is too large".to_string(),
+            )),
+        }
+    }
+
+    fn parse_front_matter_path(pair: Pair) -> Result<Vec<FrontMatterKey>, InnerParseError> {
+        let mut path = Vec::new();
+        for step in pair.into_inner() {
+            match step.as_rule() {
+                Rule::front_matter_key => path.push(FrontMatterKey::Field(step.as_str().to_string())),
+                Rule::front_matter_index => match step.as_str().parse::<usize>() {
+                    Ok(index) => path.push(FrontMatterKey::Index(index)),
+                    Err(_) => {
+                        return Err(InnerParseError::Other(
+                            DetachedSpan::from(&step),
+                            "front matter index is too large".to_string(),
+                        ))
+                    }
+                },
+                _ => {}
+            }
+        }
+        Ok(path)
+    }
+
+    /// Finds the selectors within the pairs, and returns either the one selector, or a union of all of them.
+    fn find_alternatives(pairs: Pairs) -> Result<Self, InnerParseError> {
+        let mut alternatives = Vec::new();
+        for selector_pair in ByRule::new(Rule::selector).find_all_in(pairs) {
+            alternatives.push(Self::find_selector(selector_pair)?);
+        }
+        Ok(match alternatives.len() {
+            1 => alternatives.into_iter().next().unwrap(),
+            _ => Self::Union(alternatives),
+        })
+    }
+
     fn find_selector(root: Pair) -> Result<Self, InnerParseError> {
         let span = DetachedSpan::from(&root);
         let to_parse_error = |es: String| InnerParseError::Other(span, es);
@@ -47,9 +117,77 @@ impl Selector {
 
         match as_rule {
             Rule::select_section => {
-                let SectionResults { title } = SectionTraverser::traverse(children);
+                let SectionResults {
+                    section_hashes,
+                    section_depth_min,
+                    section_depth_comma,
+                    section_depth_max,
+                    title,
+                    subsection,
+                } = SectionTraverser::traverse(children);
+                let depth = match section_hashes.take().map_err(to_parse_error)? {
+                    Some(hashes) => match hashes.as_str().len() {
+                        1 => None,
+                        n => Some(SectionDepth {
+                            min: n as u8,
+                            max: n as u8,
+                        }),
+                    },
+                    None => {
+                        let min = Self::parse_section_depth(section_depth_min.take().map_err(to_parse_error)?)?;
+                        let max = Self::parse_section_depth(section_depth_max.take().map_err(to_parse_error)?)?;
+                        let (min, max) = if section_depth_comma.is_present() {
+                            (min.unwrap_or(1), max.unwrap_or(MAX_SECTION_DEPTH))
+                        } else {
+                            let Some(exact) = min else {
+                                return Err(InnerParseError::Other(
+                                    span,
+                                    "section depth range must specify a depth".to_string(),
+                                ));
+                            };
+                            (exact, exact)
+                        };
+                        if min > max {
+                            return Err(InnerParseError::Other(
+                                span,
+                                format!("section depth range is empty: {min} is greater than {max}"),
+                            ));
+                        }
+                        Some(SectionDepth { min, max })
+                    }
+                };
                 let title = MatchReplace::try_from(title.take().map_err(to_parse_error)?)?;
-                Ok(Self::Section(SectionMatcher { title }))
+                let mut subsections = Vec::new();
+                for subsection_pair in subsection.take() {
+                    subsections.push(MatchReplace::try_from(Some(subsection_pair))?);
+                }
+                // the selector outputs the last section in the path, so that's the only one whose title can be replaced
+                let parent_titles = std::iter::once(&title).chain(subsections.iter());
+                if parent_titles.take(subsections.len()).any(|t| t.replacement.is_some()) {
+                    return Err(InnerParseError::Other(
+                        span,
+                        "only the last section in a section path can have a replacement".to_string(),
+                    ));
+                }
+                Ok(Self::Section(SectionMatcher {
+                    title,
+                    depth,
+                    subsections,
+                }))
+            }
+            Rule::select_section_slug => {
+                let slug = ByRule::new(Rule::section_slug).find_all_in(children);
+                let slug = slug.first().map(|pair| pair.as_str().to_string()).unwrap_or_default();
+                Ok(Self::SectionSlug(SectionSlugMatcher { slug }))
+            }
+            Rule::select_thematic_break => {
+                let res = ThematicBreakTraverser::traverse(children);
+                let text = MatchReplace::try_from(res.text.take().map_err(to_parse_error)?)?;
+                let between = match text.matcher {
+                    Matcher::Any { explicit: false } => None,
+                    _ => Some(text),
+                };
+                Ok(Self::ThematicBreak(ThematicBreakMatcher { between }))
             }
             Rule::select_list_item => {
                 let res = ListItemTraverser::traverse(children);
@@ -81,6 +219,21 @@ impl Selector {
                     Ok(Self::Link(link_matcher))
                 }
             }
+            Rule::select_footnote => {
+                let res = FootnoteTraverser::traverse(children);
+                let label_pair = res.label.take().map_err(to_parse_error)?;
+                let label_span = label_pair.as_ref().map(DetachedSpan::from);
+                let label = MatchReplace::try_from(label_pair)?;
+                if let (Some(label_span), Some(_)) = (label_span, &label.replacement) {
+                    // the selector outputs the footnote's definition, so there's nowhere for a replaced label to go
+                    return Err(InnerParseError::Other(
+                        label_span,
+                        "footnote label can't have a replacement".to_string(),
+                    ));
+                }
+                let text = MatchReplace::try_from(res.text.take().map_err(to_parse_error)?)?;
+                Ok(Self::Footnote(FootnoteMatcher { label, text }))
+            }
             Rule::select_block_quote => {
                 let res = BlockQuoteTraverser::traverse(children);
                 let text = MatchReplace::try_from(res.text.take().map_err(to_parse_error)?)?;
@@ -95,6 +248,33 @@ impl Selector {
                     contents: contents_matcher,
                 }))
             }
+            Rule::select_math_block => {
+                let res = MathBlockTraverser::traverse(children);
+                let text = MatchReplace::try_from(res.text.take().map_err(to_parse_error)?)?;
+                Ok(Self::MathBlock(MathBlockMatcher { text }))
+            }
+            Rule::select_inline_code => {
+                let res = InlineCodeTraverser::traverse(children);
+                let text = MatchReplace::try_from(res.text.take().map_err(to_parse_error)?)?;
+                Ok(Self::InlineCode(InlineCodeMatcher { text }))
+            }
+            Rule::select_inline_math => {
+                let res = InlineMathTraverser::traverse(children);
+                let text = MatchReplace::try_from(res.text.take().map_err(to_parse_error)?)?;
+                Ok(Self::InlineMath(InlineMathMatcher { text }))
+            }
+            Rule::select_span => {
+                let res = SpanTraverser::traverse(children);
+                let variant = if res.strong_start.is_present() {
+                    SpanVariant::Strong
+                } else if res.emphasis_start.is_present() {
+                    SpanVariant::Emphasis
+                } else {
+                    SpanVariant::Delete
+                };
+                let text = MatchReplace::try_from(res.text.take().map_err(to_parse_error)?)?;
+                Ok(Self::Span(SpanMatcher { variant, text }))
+            }
             Rule::select_front_matter => {
                 let res = FrontMatterTraverser::traverse(children);
                 let variant_pair = res.variant.take().map_err(to_parse_error)?;
@@ -113,6 +293,12 @@ impl Selector {
                         }
                     },
                 };
+                if let Some(path_pair) = res.front_matter_path.take().map_err(to_parse_error)? {
+                    return Ok(Self::FrontMatterKey(FrontMatterKeyMatcher {
+                        variant: variant_matcher,
+                        path: Self::parse_front_matter_path(path_pair)?,
+                    }));
+                }
                 let body_matcher = MatchReplace::try_from(res.text.take().map_err(to_parse_error)?)?;
                 Ok(Self::FrontMatter(FrontMatterMatcher {
                     variant: variant_matcher,
@@ -149,6 +335,39 @@ impl Selector {
                     rows: row_matcher,
                 }))
             }
+            Rule::select_position => {
+                let res = PositionTraverser::traverse(children);
+                if res.position_first.is_present() {
+                    return Ok(Self::Position(Position::First));
+                }
+                if res.position_last.is_present() {
+                    return Ok(Self::Position(Position::Last));
+                }
+                let nth_pair = res.position_nth_index.take().map_err(to_parse_error)?;
+                let nth_span = nth_pair.as_ref().map(DetachedSpan::from);
+                if let Some(nth) = Self::parse_position_index(nth_pair)? {
+                    if nth == 0 {
+                        return Err(InnerParseError::Other(
+                            nth_span.unwrap_or(span),
+                            ":nth positions start at 1".to_string(),
+                        ));
+                    }
+                    return Ok(Self::Position(Position::Nth(nth)));
+                }
+                let start = Self::parse_position_index(res.position_slice_start.take().map_err(to_parse_error)?)?;
+                let end = Self::parse_position_index(res.position_slice_end.take().map_err(to_parse_error)?)?;
+                Ok(Self::Position(Position::Slice { start, end }))
+            }
+            Rule::task_action => {
+                let action = match children.peek().map(|pair| pair.as_rule()) {
+                    Some(Rule::task_action_check) => TaskAction::Check,
+                    Some(Rule::task_action_uncheck) => TaskAction::Uncheck,
+                    _ => TaskAction::Toggle,
+                };
+                Ok(Self::TaskAction(action))
+            }
+            Rule::select_not => Ok(Self::Not(Box::new(Self::find_alternatives(children)?))),
+            Rule::select_has => Ok(Self::Has(Box::new(Self::find_alternatives(children)?))),
             _ => {
                 // We only expect to get here if we hit the Rule::selector rule. In that case, traversing the inners
                 // (there should only be one) will get us the actual, concrete selector for this selector union.
@@ -238,6 +457,8 @@ mod tests {
                 "| #",
                 Selector::Section(SectionMatcher {
                     title: without_replace(Matcher::Any { explicit: false }),
+                    depth: None,
+                    subsections: Vec::new(),
                 }),
             )
         }
@@ -248,6 +469,8 @@ mod tests {
                 "# |",
                 Selector::Section(SectionMatcher {
                     title: without_replace(Matcher::Any { explicit: false }),
+                    depth: None,
+                    subsections: Vec::new(),
                 }),
             )
         }
@@ -259,6 +482,8 @@ mod tests {
                 Selector::Chain(vec![
                     Selector::Section(SectionMatcher {
                         title: without_replace(Matcher::Any { explicit: false }),
+                        depth: None,
+                        subsections: Vec::new(),
                     }),
                     Selector::Link(LinklikeMatcher {
                         display_matcher: without_replace(Matcher::Any { explicit: false }),
@@ -275,6 +500,8 @@ mod tests {
                 Selector::Chain(vec![
                     Selector::Section(SectionMatcher {
                         title: without_replace(Matcher::Any { explicit: false }),
+                        depth: None,
+                        subsections: Vec::new(),
                     }),
                     Selector::Link(LinklikeMatcher {
                         display_matcher: without_replace(Matcher::Any { explicit: false }),
@@ -287,6 +514,8 @@ mod tests {
 
     mod section {
         use super::*;
+        use crate::select::Regex;
+        use indoc::indoc;
 
         #[test]
         fn section_no_matcher() {
@@ -294,6 +523,8 @@ mod tests {
                 "#",
                 Selector::Section(SectionMatcher {
                     title: without_replace(Matcher::Any { explicit: false }),
+                    depth: None,
+                    subsections: Vec::new(),
                 }),
             );
         }
@@ -304,227 +535,612 @@ mod tests {
                 "# foo",
                 Selector::Section(SectionMatcher {
                     title: matcher_text(false, "foo", false),
+                    depth: None,
+                    subsections: Vec::new(),
                 }),
             );
         }
-    }
-
-    mod list_item {
-        use super::*;
-        use indoc::indoc;
 
         #[test]
-        fn unordered_list_item_no_matcher() {
+        fn section_exact_depth() {
             find_selector(
-                "-",
-                Selector::ListItem(ListItemMatcher {
-                    ordered: false,
-                    task: ListItemTask::None,
-                    matcher: without_replace(Matcher::Any { explicit: false }),
+                "### foo",
+                Selector::Section(SectionMatcher {
+                    title: matcher_text(false, "foo", false),
+                    depth: Some(SectionDepth { min: 3, max: 3 }),
+                    subsections: Vec::new(),
                 }),
-            )
+            );
         }
 
         #[test]
-        fn unordered_list_item_with_matcher() {
+        fn section_depth_range() {
             find_selector(
-                "- hello",
-                Selector::ListItem(ListItemMatcher {
-                    ordered: false,
-                    task: ListItemTask::None,
-                    matcher: matcher_text(false, "hello", false),
+                "#{2,3} foo",
+                Selector::Section(SectionMatcher {
+                    title: matcher_text(false, "foo", false),
+                    depth: Some(SectionDepth { min: 2, max: 3 }),
+                    subsections: Vec::new(),
                 }),
-            )
-        }
-
-        #[test]
-        fn unordered_list_item_with_matcher_no_space() {
-            expect_parse_error(
-                "-hello",
-                indoc! {r#"
-                     --> 1:2
-                      |
-                    1 | -hello
-                      |  ^---
-                      |
-                      = expected end of input or space"#},
             );
         }
 
         #[test]
-        fn ordered_list_item_no_matcher() {
+        fn section_depth_range_single() {
             find_selector(
-                "1.",
-                Selector::ListItem(ListItemMatcher {
-                    ordered: true,
-                    task: ListItemTask::None,
-                    matcher: without_replace(Matcher::Any { explicit: false }),
+                "#{2}",
+                Selector::Section(SectionMatcher {
+                    title: without_replace(Matcher::Any { explicit: false }),
+                    depth: Some(SectionDepth { min: 2, max: 2 }),
+                    subsections: Vec::new(),
                 }),
-            )
+            );
         }
 
         #[test]
-        fn ordered_list_item_with_matcher() {
+        fn section_path() {
             find_selector(
-                "1. world",
-                Selector::ListItem(ListItemMatcher {
-                    ordered: true,
-                    task: ListItemTask::None,
-                    matcher: matcher_text(false, "world", false),
+                "## 'api' > 'errors' > /codes?/",
+                Selector::Section(SectionMatcher {
+                    title: quoted_text("api"),
+                    depth: Some(SectionDepth { min: 2, max: 2 }),
+                    subsections: vec![
+                        quoted_text("errors"),
+                        without_replace(Matcher::Regex(Regex {
+                            re: fancy_regex::Regex::new("codes?").unwrap(),
+                        })),
+                    ],
                 }),
-            )
+            );
         }
 
         #[test]
-        fn ordered_list_item_with_matcher_anchored() {
+        fn section_path_quoted() {
             find_selector(
-                "1. ^ world $",
-                Selector::ListItem(ListItemMatcher {
-                    ordered: true,
-                    task: ListItemTask::None,
-                    matcher: matcher_text(true, "world", true),
+                "# 'a > b' > c",
+                Selector::Section(SectionMatcher {
+                    title: without_replace(Matcher::Text {
+                        case_sensitive: true,
+                        anchor_start: false,
+                        text: "a > b".to_string(),
+                        anchor_end: false,
+                    }),
+                    depth: None,
+                    subsections: vec![matcher_text(false, "c", false)],
                 }),
-            )
+            );
         }
 
         #[test]
-        fn unordered_unchecked_task() {
+        fn unquoted_title_with_angle_bracket_is_not_a_path() {
             find_selector(
-                "- [ ]",
-                Selector::ListItem(ListItemMatcher {
-                    ordered: false,
-                    task: ListItemTask::Unselected,
-                    matcher: without_replace(Matcher::Any { explicit: false }),
+                "# Input > Output",
+                Selector::Section(SectionMatcher {
+                    title: matcher_text(false, "Input > Output", false),
+                    depth: None,
+                    subsections: Vec::new(),
                 }),
-            )
-        }
-
-        #[test]
-        fn unordered_checked_task() {
+            );
             find_selector(
-                "- [x]",
-                Selector::ListItem(ListItemMatcher {
-                    ordered: false,
-                    task: ListItemTask::Selected,
-                    matcher: without_replace(Matcher::Any { explicit: false }),
+                "# a>b",
+                Selector::Section(SectionMatcher {
+                    title: matcher_text(false, "a>b", false),
+                    depth: None,
+                    subsections: Vec::new(),
                 }),
-            )
+            );
         }
 
         #[test]
-        fn unordered_either_task() {
+        fn section_path_with_slashes() {
             find_selector(
-                "- [?]",
-                Selector::ListItem(ListItemMatcher {
-                    ordered: false,
-                    task: ListItemTask::Either,
-                    matcher: without_replace(Matcher::Any { explicit: false }),
+                "## api / errors / /codes?/",
+                Selector::Section(SectionMatcher {
+                    title: matcher_text(false, "api", false),
+                    depth: Some(SectionDepth { min: 2, max: 2 }),
+                    subsections: vec![
+                        matcher_text(false, "errors", false),
+                        without_replace(Matcher::Regex(Regex {
+                            re: fancy_regex::Regex::new("codes?").unwrap(),
+                        })),
+                    ],
                 }),
-            )
-        }
-
-        #[test]
-        fn task_cannot_have_extra_spaces() {
-            expect_parse_error(
-                "- [  ]",
-                indoc! {r#"
-                 --> 1:5
-                  |
-                1 | - [  ]
-                  |     ^---
-                  |
-                  = expected "]""#},
             );
-        }
-
-        #[test]
-        fn unordered_task_with_matcher() {
             find_selector(
-                "- [?] my task",
-                Selector::ListItem(ListItemMatcher {
-                    ordered: false,
-                    task: ListItemTask::Either,
-                    matcher: matcher_text(false, "my task", false),
+                "# 'a' / b > c",
+                Selector::Section(SectionMatcher {
+                    title: quoted_text("a"),
+                    depth: None,
+                    subsections: vec![matcher_text(false, "b > c", false)],
                 }),
-            )
+            );
         }
 
         #[test]
-        fn ordered_task() {
+        fn slash_without_spaces_is_not_a_path() {
             find_selector(
-                "1. [ ] my ordered task",
-                Selector::ListItem(ListItemMatcher {
-                    ordered: true,
-                    task: ListItemTask::Unselected,
-                    matcher: matcher_text(false, "my ordered task", false),
+                "# I/O",
+                Selector::Section(SectionMatcher {
+                    title: matcher_text(false, "I/O", false),
+                    depth: None,
+                    subsections: Vec::new(),
                 }),
-            )
-        }
-    }
-
-    mod link {
-        use super::*;
-
-        #[test]
-        fn link_no_matchers() {
+            );
             find_selector(
-                "[]()",
-                Selector::Link(LinklikeMatcher {
-                    display_matcher: without_replace(Matcher::Any { explicit: false }),
-                    url_matcher: without_replace(Matcher::Any { explicit: false }),
+                "# 'a / b'",
+                Selector::Section(SectionMatcher {
+                    title: quoted_text("a / b"),
+                    depth: None,
+                    subsections: Vec::new(),
                 }),
-            )
+            );
         }
 
         #[test]
-        fn link_with_display() {
+        fn section_path_after_any_or_anchor() {
             find_selector(
-                "[hello]()",
-                Selector::Link(LinklikeMatcher {
-                    display_matcher: matcher_text(false, "hello", false),
-                    url_matcher: without_replace(Matcher::Any { explicit: false }),
+                "# * > b",
+                Selector::Section(SectionMatcher {
+                    title: without_replace(Matcher::Any { explicit: true }),
+                    depth: None,
+                    subsections: vec![matcher_text(false, "b", false)],
                 }),
-            )
-        }
-
-        #[test]
-        fn link_with_url() {
+            );
             find_selector(
-                "[](example.com)",
-                Selector::Link(LinklikeMatcher {
-                    display_matcher: without_replace(Matcher::Any { explicit: false }),
-                    url_matcher: matcher_text(false, "example.com", false),
+                "# a$ > b",
+                Selector::Section(SectionMatcher {
+                    title: matcher_text(false, "a", true),
+                    depth: None,
+                    subsections: vec![matcher_text(false, "b", false)],
                 }),
-            )
+            );
         }
 
         #[test]
-        fn link_with_both() {
+        fn section_path_in_group() {
             find_selector(
-                "[click here](example.com)",
-                Selector::Link(LinklikeMatcher {
-                    display_matcher: matcher_text(false, "click here", false),
-                    url_matcher: matcher_text(false, "example.com", false),
+                "(# 'a' > b)",
+                Selector::Section(SectionMatcher {
+                    title: quoted_text("a"),
+                    depth: None,
+                    subsections: vec![matcher_text(false, "b", false)],
                 }),
-            )
+            );
+            find_selector(
+                "(# a / b, # c)",
+                Selector::Union(vec![
+                    Selector::Section(SectionMatcher {
+                        title: matcher_text(false, "a", false),
+                        depth: None,
+                        subsections: vec![matcher_text(false, "b", false)],
+                    }),
+                    Selector::Section(SectionMatcher {
+                        title: matcher_text(false, "c", false),
+                        depth: None,
+                        subsections: Vec::new(),
+                    }),
+                ]),
+            );
         }
 
         #[test]
-        fn image_no_matchers() {
+        fn section_path_replacement_only_at_end() {
             find_selector(
-                "![]()",
-                Selector::Image(LinklikeMatcher {
-                    display_matcher: without_replace(Matcher::Any { explicit: false }),
-                    url_matcher: without_replace(Matcher::Any { explicit: false }),
+                "# 'a' > !s/b/c/",
+                Selector::Section(SectionMatcher {
+                    title: quoted_text("a"),
+                    depth: None,
+                    subsections: vec![MatchReplace {
+                        matcher: Matcher::Regex(Regex {
+                            re: fancy_regex::Regex::new("b").unwrap(),
+                        }),
+                        replacement: Some("c".to_string()),
+                    }],
                 }),
-            )
+            );
+            expect_parse_error(
+                "# !s/a/c/ > b",
+                indoc! {r"
+                     --> 1:1
+                      |
+                    1 | # !s/a/c/ > b
+                      | ^-----------^
+                      |
+                      = only the last section in a section path can have a replacement"},
+            );
         }
 
         #[test]
-        fn image_with_alt() {
+        fn section_depth_range_open_ended() {
             find_selector(
-                "![alt text]()",
-                Selector::Image(LinklikeMatcher {
+                "#{2,}",
+                Selector::Section(SectionMatcher {
+                    title: without_replace(Matcher::Any { explicit: false }),
+                    depth: Some(SectionDepth { min: 2, max: 6 }),
+                    subsections: Vec::new(),
+                }),
+            );
+            find_selector(
+                "#{,3}",
+                Selector::Section(SectionMatcher {
+                    title: without_replace(Matcher::Any { explicit: false }),
+                    depth: Some(SectionDepth { min: 1, max: 3 }),
+                    subsections: Vec::new(),
+                }),
+            );
+        }
+
+        #[test]
+        fn section_depth_range_out_of_bounds() {
+            expect_parse_error(
+                "#{2,7}",
+                indoc! {r#"
+                     --> 1:5
+                      |
+                    1 | #{2,7}
+                      |     ^
+                      |
+                      = section depth must be between 1 and 6"#},
+            );
+        }
+
+        #[test]
+        fn section_depth_range_inverted() {
+            expect_parse_error(
+                "#{3,2}",
+                indoc! {r#"
+                     --> 1:1
+                      |
+                    1 | #{3,2}
+                      | ^----^
+                      |
+                      = section depth range is empty: 3 is greater than 2"#},
+            );
+        }
+
+        #[test]
+        fn section_depth_range_empty() {
+            expect_parse_error(
+                "#{}",
+                indoc! {r#"
+                     --> 1:1
+                      |
+                    1 | #{}
+                      | ^-^
+                      |
+                      = section depth range must specify a depth"#},
+            );
+        }
+
+        #[test]
+        fn section_too_many_hashes() {
+            expect_parse_error(
+                "####### foo",
+                indoc! {r#"
+                     --> 1:7
+                      |
+                    1 | ####### foo
+                      |       ^---
+                      |
+                      = expected end of input or space"#},
+            );
+        }
+    }
+
+    mod section_slug {
+        use super::*;
+        use indoc::indoc;
+
+        #[test]
+        fn slug() {
+            find_selector(
+                "#running-tests",
+                Selector::SectionSlug(SectionSlugMatcher {
+                    slug: "running-tests".to_string(),
+                }),
+            )
+        }
+
+        #[test]
+        fn slug_with_suffix_and_unicode() {
+            find_selector(
+                "#ünïcödé_title-1",
+                Selector::SectionSlug(SectionSlugMatcher {
+                    slug: "ünïcödé_title-1".to_string(),
+                }),
+            )
+        }
+
+        #[test]
+        fn slug_in_chain() {
+            find_selectors(
+                "#api | #errors",
+                Selector::Chain(vec![
+                    Selector::SectionSlug(SectionSlugMatcher {
+                        slug: "api".to_string(),
+                    }),
+                    Selector::SectionSlug(SectionSlugMatcher {
+                        slug: "errors".to_string(),
+                    }),
+                ]),
+            )
+        }
+
+        #[test]
+        fn slug_with_invalid_char() {
+            expect_parse_error(
+                "#running.tests",
+                indoc! {r#"
+                     --> 1:9
+                      |
+                    1 | #running.tests
+                      |         ^---
+                      |
+                      = expected end of input"#},
+            )
+        }
+    }
+
+    mod thematic_break {
+        use super::*;
+        use indoc::indoc;
+
+        #[test]
+        fn breaks() {
+            find_selector("---", Selector::ThematicBreak(ThematicBreakMatcher { between: None }))
+        }
+
+        #[test]
+        fn between_with_text() {
+            find_selector(
+                "--- hello",
+                Selector::ThematicBreak(ThematicBreakMatcher {
+                    between: Some(matcher_text(false, "hello", false)),
+                }),
+            )
+        }
+
+        #[test]
+        fn between_with_explicit_any() {
+            find_selector(
+                "--- *",
+                Selector::ThematicBreak(ThematicBreakMatcher {
+                    between: Some(without_replace(Matcher::Any { explicit: true })),
+                }),
+            )
+        }
+
+        #[test]
+        fn no_space_after_dashes() {
+            expect_parse_error(
+                "---hello",
+                indoc! {r#"
+                     --> 1:4
+                      |
+                    1 | ---hello
+                      |    ^---
+                      |
+                      = expected end of input or space"#},
+            );
+        }
+    }
+
+    mod list_item {
+        use super::*;
+        use indoc::indoc;
+
+        #[test]
+        fn unordered_list_item_no_matcher() {
+            find_selector(
+                "-",
+                Selector::ListItem(ListItemMatcher {
+                    ordered: false,
+                    task: ListItemTask::None,
+                    matcher: without_replace(Matcher::Any { explicit: false }),
+                }),
+            )
+        }
+
+        #[test]
+        fn unordered_list_item_with_matcher() {
+            find_selector(
+                "- hello",
+                Selector::ListItem(ListItemMatcher {
+                    ordered: false,
+                    task: ListItemTask::None,
+                    matcher: matcher_text(false, "hello", false),
+                }),
+            )
+        }
+
+        #[test]
+        fn unordered_list_item_with_matcher_no_space() {
+            expect_parse_error(
+                "-hello",
+                indoc! {r#"
+                     --> 1:2
+                      |
+                    1 | -hello
+                      |  ^---
+                      |
+                      = expected end of input or space"#},
+            );
+        }
+
+        #[test]
+        fn ordered_list_item_no_matcher() {
+            find_selector(
+                "1.",
+                Selector::ListItem(ListItemMatcher {
+                    ordered: true,
+                    task: ListItemTask::None,
+                    matcher: without_replace(Matcher::Any { explicit: false }),
+                }),
+            )
+        }
+
+        #[test]
+        fn ordered_list_item_with_matcher() {
+            find_selector(
+                "1. world",
+                Selector::ListItem(ListItemMatcher {
+                    ordered: true,
+                    task: ListItemTask::None,
+                    matcher: matcher_text(false, "world", false),
+                }),
+            )
+        }
+
+        #[test]
+        fn ordered_list_item_with_matcher_anchored() {
+            find_selector(
+                "1. ^ world $",
+                Selector::ListItem(ListItemMatcher {
+                    ordered: true,
+                    task: ListItemTask::None,
+                    matcher: matcher_text(true, "world", true),
+                }),
+            )
+        }
+
+        #[test]
+        fn unordered_unchecked_task() {
+            find_selector(
+                "- [ ]",
+                Selector::ListItem(ListItemMatcher {
+                    ordered: false,
+                    task: ListItemTask::Unselected,
+                    matcher: without_replace(Matcher::Any { explicit: false }),
+                }),
+            )
+        }
+
+        #[test]
+        fn unordered_checked_task() {
+            find_selector(
+                "- [x]",
+                Selector::ListItem(ListItemMatcher {
+                    ordered: false,
+                    task: ListItemTask::Selected,
+                    matcher: without_replace(Matcher::Any { explicit: false }),
+                }),
+            )
+        }
+
+        #[test]
+        fn unordered_either_task() {
+            find_selector(
+                "- [?]",
+                Selector::ListItem(ListItemMatcher {
+                    ordered: false,
+                    task: ListItemTask::Either,
+                    matcher: without_replace(Matcher::Any { explicit: false }),
+                }),
+            )
+        }
+
+        #[test]
+        fn task_cannot_have_extra_spaces() {
+            expect_parse_error(
+                "- [  ]",
+                indoc! {r#"
+                 --> 1:5
+                  |
+                1 | - [  ]
+                  |     ^---
+                  |
+                  = expected "]""#},
+            );
+        }
+
+        #[test]
+        fn unordered_task_with_matcher() {
+            find_selector(
+                "- [?] my task",
+                Selector::ListItem(ListItemMatcher {
+                    ordered: false,
+                    task: ListItemTask::Either,
+                    matcher: matcher_text(false, "my task", false),
+                }),
+            )
+        }
+
+        #[test]
+        fn ordered_task() {
+            find_selector(
+                "1. [ ] my ordered task",
+                Selector::ListItem(ListItemMatcher {
+                    ordered: true,
+                    task: ListItemTask::Unselected,
+                    matcher: matcher_text(false, "my ordered task", false),
+                }),
+            )
+        }
+    }
+
+    mod link {
+        use super::*;
+
+        #[test]
+        fn link_no_matchers() {
+            find_selector(
+                "[]()",
+                Selector::Link(LinklikeMatcher {
+                    display_matcher: without_replace(Matcher::Any { explicit: false }),
+                    url_matcher: without_replace(Matcher::Any { explicit: false }),
+                }),
+            )
+        }
+
+        #[test]
+        fn link_with_display() {
+            find_selector(
+                "[hello]()",
+                Selector::Link(LinklikeMatcher {
+                    display_matcher: matcher_text(false, "hello", false),
+                    url_matcher: without_replace(Matcher::Any { explicit: false }),
+                }),
+            )
+        }
+
+        #[test]
+        fn link_with_url() {
+            find_selector(
+                "[](example.com)",
+                Selector::Link(LinklikeMatcher {
+                    display_matcher: without_replace(Matcher::Any { explicit: false }),
+                    url_matcher: matcher_text(false, "example.com", false),
+                }),
+            )
+        }
+
+        #[test]
+        fn link_with_both() {
+            find_selector(
+                "[click here](example.com)",
+                Selector::Link(LinklikeMatcher {
+                    display_matcher: matcher_text(false, "click here", false),
+                    url_matcher: matcher_text(false, "example.com", false),
+                }),
+            )
+        }
+
+        #[test]
+        fn image_no_matchers() {
+            find_selector(
+                "![]()",
+                Selector::Image(LinklikeMatcher {
+                    display_matcher: without_replace(Matcher::Any { explicit: false }),
+                    url_matcher: without_replace(Matcher::Any { explicit: false }),
+                }),
+            )
+        }
+
+        #[test]
+        fn image_with_alt() {
+            find_selector(
+                "![alt text]()",
+                Selector::Image(LinklikeMatcher {
                     display_matcher: matcher_text(false, "alt text", false),
                     url_matcher: without_replace(Matcher::Any { explicit: false }),
                 }),
@@ -532,6 +1148,69 @@ mod tests {
         }
     }
 
+    mod footnote {
+        use super::*;
+        use indoc::indoc;
+
+        #[test]
+        fn footnote_no_matchers() {
+            find_selector(
+                "[^]",
+                Selector::Footnote(FootnoteMatcher {
+                    label: without_replace(Matcher::Any { explicit: false }),
+                    text: without_replace(Matcher::Any { explicit: false }),
+                }),
+            )
+        }
+
+        #[test]
+        fn footnote_with_label_and_text() {
+            find_selector(
+                "[^smith] study",
+                Selector::Footnote(FootnoteMatcher {
+                    label: matcher_text(false, "smith", false),
+                    text: matcher_text(false, "study", false),
+                }),
+            )
+        }
+
+        #[test]
+        fn footnote_with_only_text() {
+            find_selector(
+                "[^] study",
+                Selector::Footnote(FootnoteMatcher {
+                    label: without_replace(Matcher::Any { explicit: false }),
+                    text: matcher_text(false, "study", false),
+                }),
+            )
+        }
+
+        #[test]
+        fn anchored_link_is_not_footnote() {
+            find_selector(
+                "[^foo]()",
+                Selector::Link(LinklikeMatcher {
+                    display_matcher: matcher_text(true, "foo", false),
+                    url_matcher: without_replace(Matcher::Any { explicit: false }),
+                }),
+            )
+        }
+
+        #[test]
+        fn label_with_replacement() {
+            expect_parse_error(
+                "[^!s/a/b/]",
+                indoc! {r#"
+                     --> 1:3
+                      |
+                    1 | [^!s/a/b/]
+                      |   ^-----^
+                      |
+                      = footnote label can't have a replacement"#},
+            );
+        }
+    }
+
     mod block_quote {
         use super::*;
 
@@ -581,46 +1260,327 @@ mod tests {
         }
 
         #[test]
-        fn code_block_with_only_language() {
+        fn code_block_with_only_language() {
+            find_selector(
+                "```rust",
+                Selector::CodeBlock(CodeBlockMatcher {
+                    language: matcher_text(false, "rust", false),
+                    contents: without_replace(Matcher::Any { explicit: false }),
+                }),
+            )
+        }
+
+        #[test]
+        fn code_block_with_only_language_and_trailing_space() {
+            find_selector(
+                "```rust",
+                Selector::CodeBlock(CodeBlockMatcher {
+                    language: matcher_text(false, "rust", false),
+                    contents: without_replace(Matcher::Any { explicit: false }),
+                }),
+            )
+        }
+
+        #[test]
+        fn code_block_with_only_content() {
+            find_selector(
+                "``` fn main()",
+                Selector::CodeBlock(CodeBlockMatcher {
+                    language: without_replace(Matcher::Any { explicit: false }),
+                    contents: matcher_text(false, "fn main()", false),
+                }),
+            )
+        }
+
+        #[test]
+        fn code_block_with_both() {
+            find_selector(
+                "```rust fn main()",
+                Selector::CodeBlock(CodeBlockMatcher {
+                    language: matcher_text(false, "rust", false),
+                    contents: matcher_text(false, "fn main()", false),
+                }),
+            )
+        }
+    }
+
+    mod math_block {
+        use super::*;
+
+        #[test]
+        fn math_block_no_matcher() {
+            find_selector(
+                "$$",
+                Selector::MathBlock(MathBlockMatcher {
+                    text: without_replace(Matcher::Any { explicit: false }),
+                }),
+            )
+        }
+
+        #[test]
+        fn math_block_with_text() {
+            find_selector(
+                "$$ frac",
+                Selector::MathBlock(MathBlockMatcher {
+                    text: matcher_text(false, "frac", false),
+                }),
+            )
+        }
+
+        #[test]
+        fn math_block_with_anchored_text() {
+            find_selector(
+                "$$ ^frac$",
+                Selector::MathBlock(MathBlockMatcher {
+                    text: matcher_text(true, "frac", true),
+                }),
+            )
+        }
+    }
+
+    mod inline_code {
+        use super::*;
+        use indoc::indoc;
+
+        #[test]
+        fn inline_code_no_matcher() {
+            find_selector(
+                "``",
+                Selector::InlineCode(InlineCodeMatcher {
+                    text: without_replace(Matcher::Any { explicit: false }),
+                }),
+            )
+        }
+
+        #[test]
+        fn inline_code_with_text() {
+            find_selector(
+                "`'--verbose'`",
+                Selector::InlineCode(InlineCodeMatcher {
+                    text: without_replace(Matcher::Text {
+                        case_sensitive: true,
+                        anchor_start: false,
+                        text: "--verbose".to_string(),
+                        anchor_end: false,
+                    }),
+                }),
+            )
+        }
+
+        #[test]
+        fn inline_code_with_unquoted_text() {
+            find_selector(
+                "`foo bar`",
+                Selector::InlineCode(InlineCodeMatcher {
+                    text: matcher_text(false, "foo bar", false),
+                }),
+            )
+        }
+
+        #[test]
+        fn inline_code_missing_close() {
+            expect_parse_error(
+                "`foo",
+                indoc! {r#"
+                     --> 1:5
+                      |
+                    1 | `foo
+                      |     ^---
+                      |
+                      = expected "$""#},
+            );
+        }
+    }
+
+    mod inline_math {
+        use super::*;
+        use indoc::indoc;
+
+        #[test]
+        fn inline_math_no_matcher() {
+            find_selector(
+                "$ $",
+                Selector::InlineMath(InlineMathMatcher {
+                    text: without_replace(Matcher::Any { explicit: false }),
+                }),
+            )
+        }
+
+        #[test]
+        fn inline_math_with_text() {
+            find_selector(
+                "$mc$",
+                Selector::InlineMath(InlineMathMatcher {
+                    text: matcher_text(false, "mc", false),
+                }),
+            )
+        }
+
+        #[test]
+        fn inline_math_with_anchored_text() {
+            find_selector(
+                "$^x$$",
+                Selector::InlineMath(InlineMathMatcher {
+                    text: matcher_text(true, "x", true),
+                }),
+            )
+        }
+
+        #[test]
+        fn inline_math_missing_close() {
+            expect_parse_error(
+                "$x",
+                indoc! {r#"
+                     --> 1:3
+                      |
+                    1 | $x
+                      |   ^---
+                      |
+                      = expected "$""#},
+            );
+        }
+    }
+
+    mod span {
+        use super::*;
+        use indoc::indoc;
+
+        #[test]
+        fn emphasis_no_matcher() {
+            find_selector(
+                "* *",
+                Selector::Span(SpanMatcher {
+                    variant: SpanVariant::Emphasis,
+                    text: without_replace(Matcher::Any { explicit: false }),
+                }),
+            )
+        }
+
+        #[test]
+        fn emphasis_with_text() {
+            find_selector(
+                "*hello world*",
+                Selector::Span(SpanMatcher {
+                    variant: SpanVariant::Emphasis,
+                    text: matcher_text(false, "hello world", false),
+                }),
+            )
+        }
+
+        #[test]
+        fn strong_no_matcher() {
+            find_selector(
+                "****",
+                Selector::Span(SpanMatcher {
+                    variant: SpanVariant::Strong,
+                    text: without_replace(Matcher::Any { explicit: false }),
+                }),
+            )
+        }
+
+        #[test]
+        fn strong_with_text() {
+            find_selector(
+                "** ^hello$ **",
+                Selector::Span(SpanMatcher {
+                    variant: SpanVariant::Strong,
+                    text: matcher_text(true, "hello", true),
+                }),
+            )
+        }
+
+        #[test]
+        fn delete_with_text() {
+            find_selector(
+                "~~hello~~",
+                Selector::Span(SpanMatcher {
+                    variant: SpanVariant::Delete,
+                    text: matcher_text(false, "hello", false),
+                }),
+            )
+        }
+
+        #[test]
+        fn strong_missing_close() {
+            expect_parse_error(
+                "**hello*",
+                indoc! {r#"
+                     --> 1:9
+                      |
+                    1 | **hello*
+                      |         ^---
+                      |
+                      = expected "$""#},
+            );
+        }
+    }
+
+    mod front_matter {
+        use super::*;
+
+        #[test]
+        fn text_matcher() {
+            find_selector(
+                "+++yaml title",
+                Selector::FrontMatter(FrontMatterMatcher {
+                    variant: Some(FrontMatterVariant::Yaml),
+                    text: matcher_text(false, "title", false),
+                }),
+            )
+        }
+
+        #[test]
+        fn key_path() {
             find_selector(
-                "```rust",
-                Selector::CodeBlock(CodeBlockMatcher {
-                    language: matcher_text(false, "rust", false),
-                    contents: without_replace(Matcher::Any { explicit: false }),
+                "+++ .title",
+                Selector::FrontMatterKey(FrontMatterKeyMatcher {
+                    variant: None,
+                    path: vec![FrontMatterKey::Field("title".to_string())],
                 }),
             )
         }
 
         #[test]
-        fn code_block_with_only_language_and_trailing_space() {
+        fn key_path_with_variant_and_index() {
             find_selector(
-                "```rust",
-                Selector::CodeBlock(CodeBlockMatcher {
-                    language: matcher_text(false, "rust", false),
-                    contents: without_replace(Matcher::Any { explicit: false }),
+                "+++toml .post.tags[0]",
+                Selector::FrontMatterKey(FrontMatterKeyMatcher {
+                    variant: Some(FrontMatterVariant::Toml),
+                    path: vec![
+                        FrontMatterKey::Field("post".to_string()),
+                        FrontMatterKey::Field("tags".to_string()),
+                        FrontMatterKey::Index(0),
+                    ],
                 }),
             )
         }
 
         #[test]
-        fn code_block_with_only_content() {
+        fn empty_key_path() {
             find_selector(
-                "``` fn main()",
-                Selector::CodeBlock(CodeBlockMatcher {
-                    language: without_replace(Matcher::Any { explicit: false }),
-                    contents: matcher_text(false, "fn main()", false),
+                "+++ .",
+                Selector::FrontMatterKey(FrontMatterKeyMatcher {
+                    variant: None,
+                    path: vec![],
                 }),
             )
         }
 
         #[test]
-        fn code_block_with_both() {
-            find_selector(
-                "```rust fn main()",
-                Selector::CodeBlock(CodeBlockMatcher {
-                    language: matcher_text(false, "rust", false),
-                    contents: matcher_text(false, "fn main()", false),
-                }),
+        fn key_path_then_chain() {
+            find_selectors(
+                "+++ .tags | - alpha",
+                Selector::Chain(vec![
+                    Selector::FrontMatterKey(FrontMatterKeyMatcher {
+                        variant: None,
+                        path: vec![FrontMatterKey::Field("tags".to_string())],
+                    }),
+                    Selector::ListItem(ListItemMatcher {
+                        ordered: false,
+                        task: ListItemTask::None,
+                        matcher: matcher_text(false, "alpha", false),
+                    }),
+                ]),
             )
         }
     }
@@ -794,6 +1754,402 @@ mod tests {
         }
     }
 
+    mod union {
+        use super::*;
+        use indoc::indoc;
+
+        #[test]
+        fn comma_outside_of_group_is_text() {
+            find_selector(
+                "# usage, # examples",
+                Selector::Section(SectionMatcher {
+                    title: matcher_text(false, "usage, # examples", false),
+                    depth: None,
+                    subsections: Vec::new(),
+                }),
+            );
+            find_selector(
+                "P: hello, *world*",
+                Selector::Paragraph(ParagraphMatcher {
+                    text: matcher_text(false, "hello, *world*", false),
+                }),
+            );
+            find_selector(
+                "- a, - b",
+                Selector::ListItem(ListItemMatcher {
+                    ordered: false,
+                    task: ListItemTask::None,
+                    matcher: matcher_text(false, "a, - b", false),
+                }),
+            );
+        }
+
+        #[test]
+        fn parenthesized_with_pipe() {
+            find_selector(
+                "(- [ ] | > warning)",
+                Selector::Union(vec![
+                    Selector::ListItem(ListItemMatcher {
+                        ordered: false,
+                        task: ListItemTask::Unselected,
+                        matcher: without_replace(Matcher::Any { explicit: false }),
+                    }),
+                    Selector::BlockQuote(BlockQuoteMatcher {
+                        text: matcher_text(false, "warning", false),
+                    }),
+                ]),
+            )
+        }
+
+        #[test]
+        fn parenthesized_with_comma() {
+            find_selector(
+                "(P: alpha, P: bravo)",
+                Selector::Union(vec![
+                    Selector::Paragraph(ParagraphMatcher {
+                        text: matcher_text(false, "alpha", false),
+                    }),
+                    Selector::Paragraph(ParagraphMatcher {
+                        text: matcher_text(false, "bravo", false),
+                    }),
+                ]),
+            )
+        }
+
+        #[test]
+        fn single_parenthesized_selector_is_not_a_union() {
+            find_selector(
+                "(P: alpha)",
+                Selector::Paragraph(ParagraphMatcher {
+                    text: matcher_text(false, "alpha", false),
+                }),
+            )
+        }
+
+        #[test]
+        fn comma_within_text() {
+            find_selector(
+                "P: hello, world",
+                Selector::Paragraph(ParagraphMatcher {
+                    text: matcher_text(false, "hello, world", false),
+                }),
+            );
+            find_selector(
+                "(P: hello, world)",
+                Selector::Paragraph(ParagraphMatcher {
+                    text: matcher_text(false, "hello, world", false),
+                }),
+            );
+        }
+
+        #[test]
+        fn within_chain() {
+            find_selectors(
+                "(# usage, # examples) | P: alpha",
+                Selector::Chain(vec![
+                    Selector::Union(vec![
+                        Selector::Section(SectionMatcher {
+                            title: matcher_text(false, "usage", false),
+                            depth: None,
+                            subsections: Vec::new(),
+                        }),
+                        Selector::Section(SectionMatcher {
+                            title: matcher_text(false, "examples", false),
+                            depth: None,
+                            subsections: Vec::new(),
+                        }),
+                    ]),
+                    Selector::Paragraph(ParagraphMatcher {
+                        text: matcher_text(false, "alpha", false),
+                    }),
+                ]),
+            )
+        }
+
+        #[test]
+        fn unclosed_group() {
+            expect_parse_error(
+                "(P: alpha",
+                indoc! {r#"
+                     --> 1:10
+                      |
+                    1 | (P: alpha
+                      |          ^---
+                      |
+                      = expected "$""#},
+            )
+        }
+    }
+
+    mod not {
+        use super::*;
+        use indoc::indoc;
+
+        #[test]
+        fn single_selector() {
+            find_selector(
+                "not(# deprecated)",
+                Selector::Not(Box::new(Selector::Section(SectionMatcher {
+                    title: matcher_text(false, "deprecated", false),
+                    depth: None,
+                    subsections: Vec::new(),
+                }))),
+            )
+        }
+
+        #[test]
+        fn alternatives() {
+            find_selector(
+                "not(- [x], > note)",
+                Selector::Not(Box::new(Selector::Union(vec![
+                    Selector::ListItem(ListItemMatcher {
+                        ordered: false,
+                        task: ListItemTask::Selected,
+                        matcher: without_replace(Matcher::Any { explicit: false }),
+                    }),
+                    Selector::BlockQuote(BlockQuoteMatcher {
+                        text: matcher_text(false, "note", false),
+                    }),
+                ]))),
+            )
+        }
+
+        #[test]
+        fn within_chain() {
+            find_selectors(
+                "# * | not(# deprecated)",
+                Selector::Chain(vec![
+                    Selector::Section(SectionMatcher {
+                        title: without_replace(Matcher::Any { explicit: true }),
+                        depth: None,
+                        subsections: Vec::new(),
+                    }),
+                    Selector::Not(Box::new(Selector::Section(SectionMatcher {
+                        title: matcher_text(false, "deprecated", false),
+                        depth: None,
+                        subsections: Vec::new(),
+                    }))),
+                ]),
+            )
+        }
+
+        #[test]
+        fn nested() {
+            find_selector(
+                "not(not(P: alpha))",
+                Selector::Not(Box::new(Selector::Not(Box::new(Selector::Paragraph(
+                    ParagraphMatcher {
+                        text: matcher_text(false, "alpha", false),
+                    },
+                ))))),
+            )
+        }
+
+        #[test]
+        fn empty() {
+            expect_parse_error(
+                "not()",
+                indoc! {r"
+                     --> 1:5
+                      |
+                    1 | not()
+                      |     ^---
+                      |
+                      = expected selector"},
+            )
+        }
+    }
+
+    mod has {
+        use super::*;
+
+        #[test]
+        fn single_selector() {
+            find_selector(
+                "has(- [ ])",
+                Selector::Has(Box::new(Selector::ListItem(ListItemMatcher {
+                    ordered: false,
+                    task: ListItemTask::Unselected,
+                    matcher: without_replace(Matcher::Any { explicit: false }),
+                }))),
+            )
+        }
+
+        #[test]
+        fn alternatives_within_chain() {
+            find_selectors(
+                "- * | has([](jira), P: todo)",
+                Selector::Chain(vec![
+                    Selector::ListItem(ListItemMatcher {
+                        ordered: false,
+                        task: ListItemTask::None,
+                        matcher: without_replace(Matcher::Any { explicit: true }),
+                    }),
+                    Selector::Has(Box::new(Selector::Union(vec![
+                        Selector::Link(LinklikeMatcher {
+                            display_matcher: without_replace(Matcher::Any { explicit: false }),
+                            url_matcher: matcher_text(false, "jira", false),
+                        }),
+                        Selector::Paragraph(ParagraphMatcher {
+                            text: matcher_text(false, "todo", false),
+                        }),
+                    ]))),
+                ]),
+            )
+        }
+
+        #[test]
+        fn within_not() {
+            find_selector(
+                "not(has(- [ ]))",
+                Selector::Not(Box::new(Selector::Has(Box::new(Selector::ListItem(ListItemMatcher {
+                    ordered: false,
+                    task: ListItemTask::Unselected,
+                    matcher: without_replace(Matcher::Any { explicit: false }),
+                }))))),
+            )
+        }
+    }
+
+    mod position {
+        use super::*;
+        use indoc::indoc;
+
+        #[test]
+        fn first() {
+            find_selector(":first", Selector::Position(Position::First))
+        }
+
+        #[test]
+        fn last() {
+            find_selector(":last", Selector::Position(Position::Last))
+        }
+
+        #[test]
+        fn nth() {
+            find_selector(":nth(3)", Selector::Position(Position::Nth(3)))
+        }
+
+        #[test]
+        fn nth_zero() {
+            expect_parse_error(
+                ":nth(0)",
+                indoc! {r"
+                     --> 1:6
+                      |
+                    1 | :nth(0)
+                      |      ^
+                      |
+                      = :nth positions start at 1"},
+            )
+        }
+
+        #[test]
+        fn slice() {
+            find_selector(
+                "[1..4]",
+                Selector::Position(Position::Slice {
+                    start: Some(1),
+                    end: Some(4),
+                }),
+            )
+        }
+
+        #[test]
+        fn slice_open_ends() {
+            find_selector("[..]", Selector::Position(Position::Slice { start: None, end: None }));
+            find_selector(
+                "[2..]",
+                Selector::Position(Position::Slice {
+                    start: Some(2),
+                    end: None,
+                }),
+            );
+        }
+
+        #[test]
+        fn within_chain() {
+            find_selectors(
+                "# installation | ``` | :first",
+                Selector::Chain(vec![
+                    Selector::Section(SectionMatcher {
+                        title: matcher_text(false, "installation", false),
+                        depth: None,
+                        subsections: Vec::new(),
+                    }),
+                    Selector::CodeBlock(CodeBlockMatcher {
+                        language: without_replace(Matcher::Any { explicit: false }),
+                        contents: without_replace(Matcher::Any { explicit: false }),
+                    }),
+                    Selector::Position(Position::First),
+                ]),
+            )
+        }
+
+        #[test]
+        fn not_within_union() {
+            expect_parse_error(
+                "(:first, # foo)",
+                indoc! {r"
+                     --> 1:2
+                      |
+                    1 | (:first, # foo)
+                      |  ^---
+                      |
+                      = expected selector"},
+            )
+        }
+    }
+
+    mod task_action {
+        use super::*;
+        use indoc::indoc;
+
+        #[test]
+        fn check() {
+            find_selector("check", Selector::TaskAction(TaskAction::Check))
+        }
+
+        #[test]
+        fn uncheck() {
+            find_selector("uncheck", Selector::TaskAction(TaskAction::Uncheck))
+        }
+
+        #[test]
+        fn toggle() {
+            find_selector("toggle", Selector::TaskAction(TaskAction::Toggle))
+        }
+
+        #[test]
+        fn within_chain() {
+            find_selectors(
+                "- [ ] | check",
+                Selector::Chain(vec![
+                    Selector::ListItem(ListItemMatcher {
+                        ordered: false,
+                        task: ListItemTask::Unselected,
+                        matcher: without_replace(Matcher::Any { explicit: false }),
+                    }),
+                    Selector::TaskAction(TaskAction::Check),
+                ]),
+            )
+        }
+
+        #[test]
+        fn not_a_prefix() {
+            expect_parse_error(
+                "checked",
+                indoc! {r"
+                     --> 1:1
+                      |
+                    1 | checked
+                      | ^---
+                      |
+                      = expected valid query"},
+            )
+        }
+    }
+
     mod invalid {
         use super::*;
         use indoc::indoc;
@@ -859,6 +2215,15 @@ mod tests {
         })
     }
 
+    fn quoted_text(text: &str) -> MatchReplace {
+        without_replace(Matcher::Text {
+            case_sensitive: true,
+            anchor_start: false,
+            text: text.to_string(),
+            anchor_end: false,
+        })
+    }
+
     fn without_replace(matcher: Matcher) -> MatchReplace {
         MatchReplace {
             matcher,
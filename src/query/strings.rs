@@ -135,7 +135,7 @@ impl ParsedString {
                     Rule::anchor_end => {
                         me.anchor_end = true;
                     }
-                    Rule::unquoted_string => {
+                    Rule::unquoted_string | Rule::section_unquoted_string => {
                         me.mode = ParsedStringMode::CaseInsensitive;
                         me.text.push_str(pair.as_str().trim_end());
                     }
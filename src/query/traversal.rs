@@ -83,6 +83,20 @@ impl<T> OneOf<T> {
     }
 }
 
+/// Stores every pair it's given, in order.
+#[derive(Debug, Default)]
+pub(crate) struct ManyPairs<'a>(Vec<Pair<'a>>);
+
+impl<'a> ManyPairs<'a> {
+    pub(crate) fn store(&mut self, pair: Pair<'a>) {
+        self.0.push(pair)
+    }
+
+    pub(crate) fn take(self) -> Vec<Pair<'a>> {
+        self.0
+    }
+}
+
 #[derive(Debug)]
 pub(crate) struct FindAll<'a, M>(M, Vec<Pair<'a>>);
 
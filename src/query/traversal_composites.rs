@@ -2,7 +2,7 @@ use crate::query::pest::{Pair, Pairs, Rule};
 use crate::query::traversal::MatchStoreResult;
 use crate::query::traversal::PairMatchStore;
 use crate::query::traversal::PairMatcher;
-use crate::query::traversal::{ByRule, ByTag, OnePair, Present};
+use crate::query::traversal::{ByRule, ByTag, ManyPairs, OnePair, Present};
 use paste::paste;
 
 /// A macro for creating:
@@ -75,8 +75,25 @@ macro_rules! composite_finder {
 }
 
 composite_finder! { Section {
+    section_hashes OnePair<'a>: ByRule,
+    section_depth_min OnePair<'a>: ByRule,
+    section_depth_comma Present: ByRule,
+    section_depth_max OnePair<'a>: ByRule,
     title OnePair<'a>: ByTag,
+    subsection ManyPairs<'a>: ByTag,
 }}
+composite_finder! { Position {
+    position_first Present: ByRule,
+    position_last Present: ByRule,
+    position_nth_index OnePair<'a>: ByRule,
+    position_slice_start OnePair<'a>: ByRule,
+    position_slice_end OnePair<'a>: ByRule,
+}}
+
+composite_finder! { ThematicBreak {
+    text OnePair<'a>: ByTag,
+}}
+
 composite_finder! { ListItem {
     list_ordered Present: ByRule,
     task_checked Present: ByRule,
@@ -91,6 +108,11 @@ composite_finder! { Link {
     image_start Present: ByRule,
 }}
 
+composite_finder! { Footnote {
+    label OnePair<'a>: ByTag,
+    text OnePair<'a>: ByTag,
+}}
+
 composite_finder! { BlockQuote {
     text OnePair<'a>: ByTag,
 }}
@@ -100,9 +122,29 @@ composite_finder! { CodeBlock {
     text OnePair<'a>: ByTag,
 }}
 
+composite_finder! { MathBlock {
+    text OnePair<'a>: ByTag,
+}}
+
+composite_finder! { InlineCode {
+    text OnePair<'a>: ByTag,
+}}
+
+composite_finder! { InlineMath {
+    text OnePair<'a>: ByTag,
+}}
+
+composite_finder! { Span {
+    strong_start Present: ByRule,
+    emphasis_start Present: ByRule,
+    delete_start Present: ByRule,
+    text OnePair<'a>: ByTag,
+}}
+
 composite_finder! { FrontMatter {
     variant OnePair<'a>: ByTag,
     text OnePair<'a>: ByTag,
+    front_matter_path OnePair<'a>: ByRule,
 }}
 
 composite_finder! { Html {
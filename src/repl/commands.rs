@@ -1,7 +1,9 @@
 use crate::md_elem::MdDoc;
-use crate::run::OutputFormat;
+use crate::output::{PlainWriter, PlainWriterOptions};
+use crate::repl::ReplState;
+use crate::run::{write_results, OutputFormat};
 use crate::select::Selector;
-use crate::output::MdWriterOptions;
+use std::collections::HashMap;
 use std::io::{self, Write};
 
 /// Built-in REPL commands
@@ -9,37 +11,46 @@ use std::io::{self, Write};
 pub enum ReplCommand {
     /// Execute a selector query
     Query(String),
-    
+
     /// Load a document from file
     Load(String),
-    
+
     /// Reload current document
     Reload,
-    
+
     /// Change output format
     Format(OutputFormat),
-    
+
     /// Set a variable
     Set(String, String),
-    
+
     /// Get a variable
     Get(String),
-    
+
+    /// Set a variable to the plain text of a query's results
+    Let(String, String),
+
     /// List all variables
     Variables,
-    
+
     /// Show help
     Help,
-    
+
     /// Show document info
     Info,
-    
+
     /// Clear document
     Clear,
-    
+
+    /// Make the last query's results the working document
+    Narrow,
+
+    /// Go back to the working document from before the last narrowing
+    Pop,
+
     /// Exit REPL
     Exit,
-    
+
     /// Unknown command
     Unknown(String),
 }
@@ -48,18 +59,18 @@ impl ReplCommand {
     /// Parses a command string into a ReplCommand
     pub fn parse(input: &str) -> Self {
         let input = input.trim();
-        
+
         if input.is_empty() {
             return ReplCommand::Unknown(input.to_string());
         }
-        
+
         // Check for built-in commands
         if let Some(stripped) = input.strip_prefix('.') {
             let parts: Vec<&str> = stripped.split_whitespace().collect();
             if parts.is_empty() {
                 return ReplCommand::Unknown(input.to_string());
             }
-            
+
             match parts[0] {
                 "load" => {
                     if parts.len() == 2 {
@@ -97,10 +108,18 @@ impl ReplCommand {
                         ReplCommand::Unknown(input.to_string())
                     }
                 }
+                "let" => match stripped["let".len()..].split_once('=') {
+                    Some((name, query)) if is_variable_name(name.trim()) && !query.trim().is_empty() => {
+                        ReplCommand::Let(name.trim().to_string(), query.trim().to_string())
+                    }
+                    _ => ReplCommand::Unknown(input.to_string()),
+                },
                 "vars" | "variables" => ReplCommand::Variables,
                 "help" => ReplCommand::Help,
                 "info" => ReplCommand::Info,
                 "clear" => ReplCommand::Clear,
+                "narrow" | "push" => ReplCommand::Narrow,
+                "pop" | "up" => ReplCommand::Pop,
                 "exit" | "quit" => ReplCommand::Exit,
                 _ => ReplCommand::Unknown(input.to_string()),
             }
@@ -112,124 +131,230 @@ impl ReplCommand {
 }
 
 /// Executes a REPL command
-pub fn execute_command<W: Write>(
+///
+/// Results go to `output`, and status messages and errors to `status`, so that a script's output is just its results.
+/// Returns whether the command succeeded: a query or `.let` that matches nothing doesn't, as with mdq's exit code.
+pub fn execute_command<W: Write, S: Write>(
     command: &ReplCommand,
-    document: Option<&MdDoc>,
-    _options: &mut MdWriterOptions,
-    variables: &mut std::collections::HashMap<String, String>,
+    state: &mut ReplState,
     output: &mut W,
+    status: &mut S,
 ) -> io::Result<bool> {
     match command {
-        ReplCommand::Query(selector_str) => {
-            execute_query(selector_str, document, _options, output)
-        }
+        ReplCommand::Query(selector_str) => Ok(execute_query(selector_str, state, output, status)?.is_some()),
         ReplCommand::Load(path) => {
-            writeln!(output, "Loading document from: {}", path)?;
-            Ok(true) // Signal that document should be loaded
+            writeln!(status, "Loading document from: {}", path)?;
+            Ok(true)
         }
         ReplCommand::Reload => {
-            writeln!(output, "Reloading document...")?;
-            Ok(true) // Signal that document should be reloaded
+            writeln!(status, "Reloading document...")?;
+            Ok(true)
         }
         ReplCommand::Format(format) => {
-            writeln!(output, "Setting output format to: {:?}", format)?;
-            Ok(false)
+            writeln!(status, "Setting output format to: {:?}", format)?;
+            Ok(true)
         }
         ReplCommand::Set(name, value) => {
-            variables.insert(name.clone(), value.clone());
-            writeln!(output, "Set variable '{}' = '{}'", name, value)?;
-            Ok(false)
+            state.set_variable(name.clone(), value.clone());
+            writeln!(status, "Set variable '{}' = '{}'", name, value)?;
+            Ok(true)
         }
+        ReplCommand::Let(name, selector_str) => match find_results(selector_str, state, status) {
+            Ok(Some(results)) => {
+                let mut value = Vec::new();
+                PlainWriter::with_options(PlainWriterOptions { include_breaks: false })
+                    .write(results.roots.iter(), &mut value);
+                let value = String::from_utf8_lossy(&value).trim_end().to_string();
+                writeln!(status, "{} = {}", name, value)?;
+                state.set_variable(name.clone(), value);
+                Ok(true)
+            }
+            Ok(None) => {
+                writeln!(status, "No elements matched the selector")?;
+                Ok(false)
+            }
+            Err(e) => {
+                writeln!(status, "{}", e)?;
+                Ok(false)
+            }
+        },
         ReplCommand::Get(name) => {
-            if let Some(value) = variables.get(name) {
+            if let Some(value) = state.get_variable(name) {
                 writeln!(output, "{} = {}", name, value)?;
+                Ok(true)
             } else {
-                writeln!(output, "Variable '{}' not found", name)?;
+                writeln!(status, "Variable '{}' not found", name)?;
+                Ok(false)
             }
-            Ok(false)
         }
         ReplCommand::Variables => {
+            let mut variables: Vec<_> = state.variables().iter().collect();
             if variables.is_empty() {
                 writeln!(output, "No variables set")?;
             } else {
+                variables.sort();
                 writeln!(output, "Variables:")?;
                 for (name, value) in variables {
                     writeln!(output, "  {} = {}", name, value)?;
                 }
             }
-            Ok(false)
+            Ok(true)
         }
         ReplCommand::Help => {
             show_help(output)?;
-            Ok(false)
+            Ok(true)
         }
         ReplCommand::Info => {
-            if let Some(doc) = document {
+            if let Some(doc) = state.document() {
                 writeln!(output, "Document loaded with {} root elements", doc.roots.len())?;
             } else {
                 writeln!(output, "No document loaded")?;
             }
-            Ok(false)
+            Ok(true)
         }
         ReplCommand::Clear => {
-            writeln!(output, "Document cleared")?;
-            Ok(false)
+            writeln!(status, "Document cleared")?;
+            Ok(true)
+        }
+        ReplCommand::Narrow | ReplCommand::Pop => {
+            // The engine handles these, since they change its state
+            Ok(true)
         }
         ReplCommand::Exit => {
-            writeln!(output, "Exiting REPL...")?;
-            Ok(false)
+            writeln!(status, "Exiting REPL...")?;
+            Ok(true)
         }
         ReplCommand::Unknown(cmd) => {
-            writeln!(output, "Unknown command: {}", cmd)?;
-            writeln!(output, "Use .help for available commands")?;
+            writeln!(status, "Unknown command: {}", cmd)?;
+            writeln!(status, "Use .help for available commands")?;
             Ok(false)
         }
     }
 }
 
-/// Executes a selector query
-fn execute_query<W: Write>(
+/// Executes a selector query, and writes its results in the state's output format
+///
+/// Returns the results as a document (which `.narrow` can make the working document), if there were any.
+pub fn execute_query<W: Write, S: Write>(
     selector_str: &str,
-    document: Option<&MdDoc>,
-    _options: &MdWriterOptions,
+    state: &ReplState,
     output: &mut W,
-) -> io::Result<bool> {
-    if document.is_none() {
-        writeln!(output, "Error: No document loaded. Use .load <file> first.")?;
-        return Ok(false);
-    }
-    
-    let doc = document.unwrap();
-    
-    // Parse the selector
-    let selector = match Selector::try_parse(selector_str) {
-        Ok(s) => s,
-        Err(e) => {
-            writeln!(output, "Error parsing selector: {}", e)?;
-            return Ok(false);
+    status: &mut S,
+) -> io::Result<Option<MdDoc>> {
+    let results = match find_results(selector_str, state, status) {
+        Ok(Some(results)) => results,
+        Ok(None) => {
+            writeln!(status, "No elements matched the selector")?;
+            return Ok(None);
         }
-    };
-    
-    // Execute the selector
-    let (pipeline_nodes, _ctx) = match selector.find_nodes(doc.clone()) {
-        Ok(result) => result,
         Err(e) => {
-            writeln!(output, "Error executing selector: {}", e)?;
-            return Ok(false);
+            writeln!(status, "{}", e)?;
+            return Ok(None);
         }
     };
-    
+
+    let options = state.options();
+    write_results(options, &results.roots, &[], &results.ctx, output);
+    if let OutputFormat::Json = options.output {
+        // The JSON doesn't end with a newline, so add one before the next prompt.
+        writeln!(output)?;
+    }
+
+    Ok(Some(results))
+}
+
+/// Runs a selector query against the working document, after substituting the variables into it
+///
+/// Returns the results as a document, or `None` if nothing matched. Any warnings go to `status`.
+fn find_results<S: Write>(selector_str: &str, state: &ReplState, status: &mut S) -> Result<Option<MdDoc>, String> {
+    let Some(doc) = state.document() else {
+        return Err("Error: No document loaded. Use .load <file> first.".to_string());
+    };
+
+    let selector_str = interpolate(selector_str, state.variables())?;
+
+    // Parse the selector
+    let selector = Selector::try_parse(&selector_str).map_err(|e| format!("Error parsing selector: {}", e))?;
+
+    for warning in selector.warnings(doc) {
+        writeln!(status, "warning: {warning}").map_err(|e| e.to_string())?;
+    }
+
+    // Execute the selector
+    let (pipeline_nodes, ctx) = selector
+        .find_nodes(doc.clone())
+        .map_err(|e| format!("Error executing selector: {}", e))?;
+
     if pipeline_nodes.is_empty() {
-        writeln!(output, "No elements matched the selector")?;
-        return Ok(false);
+        return Ok(None);
+    }
+    Ok(Some(MdDoc {
+        roots: pipeline_nodes,
+        ctx,
+    }))
+}
+
+/// Substitutes variables into a selector string
+///
+/// Both `$name` and `${name}` are replaced by the variable's value. A `$` that isn't followed by the name of a variable
+/// (as in the `$$` of math blocks, or inline math like `$x$`) is left as-is, and `\$` is always a literal `$`.
+pub fn interpolate(text: &str, variables: &HashMap<String, String>) -> Result<String, String> {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(idx) = rest.find(['$', '\\']) {
+        result.push_str(&rest[..idx]);
+        let after = &rest[idx + 1..];
+        if rest[idx..].starts_with('\\') {
+            // Only "\$" is an escape here; other backslashes are for the selector
+            match after.strip_prefix('$') {
+                Some(escaped) => {
+                    result.push('$');
+                    rest = escaped;
+                }
+                None => {
+                    result.push('\\');
+                    rest = after;
+                }
+            }
+            continue;
+        }
+        let (name, remaining) = match after.strip_prefix('{') {
+            Some(braced) => match braced.split_once('}') {
+                Some((name, remaining)) => (name, remaining),
+                None => return Err(format!("Error: unclosed \"${{\" in: {}", text)),
+            },
+            None => {
+                let len = after
+                    .find(|ch: char| !(ch.is_ascii_alphanumeric() || ch == '_'))
+                    .unwrap_or(after.len());
+                (&after[..len], &after[len..])
+            }
+        };
+        if !is_variable_name(name) {
+            result.push('$');
+            rest = after;
+            continue;
+        }
+        match variables.get(name) {
+            Some(value) => {
+                result.push_str(value);
+                rest = remaining;
+            }
+            None => {
+                result.push('$');
+                rest = after;
+            }
+        }
     }
-    
-    // For now, just show the count of matching elements
-    // TODO: Implement proper output formatting based on the current format setting
-    writeln!(output, "Found {} matching elements", pipeline_nodes.len())?;
-    writeln!(output, "Output formatting not yet implemented in REPL mode")?;
-    
-    Ok(false)
+    result.push_str(rest);
+    Ok(result)
+}
+
+/// Whether this is a valid variable name: letters, digits and underscores, not starting with a digit
+fn is_variable_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|ch| ch.is_ascii_alphabetic() || ch == '_')
+        && chars.all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
 }
 
 /// Shows help information
@@ -241,19 +366,212 @@ fn show_help<W: Write>(output: &mut W) -> io::Result<()> {
     writeln!(output, "  .load <file>   Load a document from file")?;
     writeln!(output, "  .reload        Reload the current document")?;
     writeln!(output, "  .format <fmt>  Change output format (md|json|plain)")?;
-    writeln!(output, "  .set <n> <v>   Set a variable")?;
+    writeln!(
+        output,
+        "  .set <n> <v>   Set a variable, which queries can use as $n or ${{n}} (\\$ for a literal $)"
+    )?;
+    writeln!(
+        output,
+        "  .let <n> = <s> Set a variable to the plain text of a selector's results"
+    )?;
     writeln!(output, "  .get <n>       Get a variable value")?;
     writeln!(output, "  .vars          List all variables")?;
     writeln!(output, "  .info          Show document information")?;
     writeln!(output, "  .clear         Clear current document")?;
+    writeln!(
+        output,
+        "  .narrow        Query only the last results from now on (or .push)"
+    )?;
+    writeln!(output, "  .pop           Undo the last .narrow (or .up)")?;
     writeln!(output, "  .help          Show this help")?;
     writeln!(output, "  .exit          Exit REPL")?;
     writeln!(output)?;
     writeln!(output, "Selector examples:")?;
-    writeln!(output, "  # Section      - Select sections with title containing 'Section'")?;
+    writeln!(
+        output,
+        "  # Section      - Select sections with title containing 'Section'"
+    )?;
     writeln!(output, "  - List item    - Select list items containing 'List item'")?;
     writeln!(output, "  [text](url)    - Select links with display text 'text'")?;
     writeln!(output, "  > Quote        - Select blockquotes containing 'Quote'")?;
     writeln!(output, "  ```rust        - Select code blocks with language 'rust'")?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::md_elem::ParseOptions;
+    use crate::output::ReferencePlacement;
+    use crate::run::RunOptions;
+
+    #[test]
+    fn query_as_markdown() {
+        let options = RunOptions::default();
+
+        let output = run_query("# Second", &options);
+
+        assert_eq!(
+            output,
+            "# Second\n\nSee [the docs][1].\n\n[1]: https://example.com/docs\n"
+        );
+    }
+
+    #[test]
+    fn query_with_link_placement() {
+        let options = RunOptions {
+            link_pos: ReferencePlacement::Doc,
+            add_breaks: Some(false),
+            ..RunOptions::default()
+        };
+
+        let output = run_query("#", &options);
+
+        assert_eq!(
+            output,
+            "# First\n\nHello, world.\n\n# Second\n\nSee [the docs][1].\n\n[1]: https://example.com/docs\n"
+        );
+    }
+
+    #[test]
+    fn query_as_plain() {
+        let options = RunOptions {
+            output: OutputFormat::Plain,
+            ..RunOptions::default()
+        };
+
+        let output = run_query("P: hello", &options);
+
+        assert_eq!(output, "Hello, world.\n");
+    }
+
+    #[test]
+    fn query_as_json() {
+        let options = RunOptions {
+            output: OutputFormat::Json,
+            ..RunOptions::default()
+        };
+
+        let output = run_query("P: hello", &options);
+
+        assert_eq!(
+            output,
+            r#"{"items":[{"paragraph":"Hello, world."}]}"#.to_string() + "\n"
+        );
+    }
+
+    #[test]
+    fn query_without_matches() {
+        let output = run_query("# Third", &RunOptions::default());
+
+        assert_eq!(output, "No elements matched the selector\n");
+    }
+
+    #[test]
+    fn query_with_variables() {
+        let mut state = new_state(RunOptions::default());
+        run(&mut state, ".set section Second");
+
+        // without a "proto" variable, "${proto}" stays in the selector as-is
+        assert!(run(&mut state, "# $section | [](^${proto}://)").contains("1 | # Second | [](^${proto}://)"));
+        run(&mut state, ".set proto https");
+        assert_eq!(
+            run(&mut state, "# $section | [](^${proto}://)"),
+            "[the docs][1]\n\n[1]: https://example.com/docs\n"
+        );
+    }
+
+    #[test]
+    fn inline_math_query() {
+        let doc = MdDoc::parse(
+            "Energy is $E = mc^2$, and $x$ is $y$.",
+            &ParseOptions {
+                math: true,
+                ..ParseOptions::default()
+            },
+        )
+        .unwrap();
+        let mut state = ReplState::new(RunOptions::default());
+        state.set_document(doc);
+
+        assert_eq!(run(&mut state, "$x$"), "$x$");
+        // once there's an "x" variable, "$x" is that variable, so the inline math needs "\$x"
+        run(&mut state, ".set x E");
+        assert_eq!(run(&mut state, "$${x}$"), "$E = mc^2$");
+        assert_eq!(run(&mut state, r"\$x$"), "$x$");
+    }
+
+    #[test]
+    fn let_from_query() {
+        let mut state = new_state(RunOptions::default());
+
+        assert_eq!(
+            run(&mut state, ".let title = # First | P: *"),
+            "title = Hello, world.\n"
+        );
+        assert_eq!(run(&mut state, ".get title"), "title = Hello, world.\n");
+        assert_eq!(
+            run(&mut state, ".let title = # Third"),
+            "No elements matched the selector\n"
+        );
+        assert_eq!(run(&mut state, ".get title"), "title = Hello, world.\n");
+    }
+
+    #[test]
+    fn parse_let() {
+        assert_eq!(
+            ReplCommand::parse(".let v = # Version | P: *"),
+            ReplCommand::Let("v".to_string(), "# Version | P: *".to_string())
+        );
+        assert!(matches!(
+            ReplCommand::parse(".let 1v = # Version"),
+            ReplCommand::Unknown(_)
+        ));
+        assert!(matches!(ReplCommand::parse(".let v ="), ReplCommand::Unknown(_)));
+    }
+
+    #[test]
+    fn interpolation() {
+        let variables = HashMap::from([("name".to_string(), "API".to_string())]);
+
+        assert_eq!(interpolate("# $name | - [ ]", &variables).unwrap(), "# API | - [ ]");
+        assert_eq!(interpolate("# ${name}s", &variables).unwrap(), "# APIs");
+        assert_eq!(interpolate("$$ math", &variables).unwrap(), "$$ math");
+        assert_eq!(interpolate("# end$", &variables).unwrap(), "# end$");
+        assert_eq!(interpolate(r"P: \$name$", &variables).unwrap(), "P: $name$");
+        assert_eq!(interpolate("$x$", &variables).unwrap(), "$x$");
+        assert_eq!(
+            interpolate("# $other ${other}", &variables).unwrap(),
+            "# $other ${other}"
+        );
+        assert_eq!(interpolate(r#"# "a\"b""#, &variables).unwrap(), r#"# "a\"b""#);
+        assert_eq!(
+            interpolate("# ${name", &variables).unwrap_err(),
+            "Error: unclosed \"${\" in: # ${name"
+        );
+    }
+
+    fn run_query(selector: &str, options: &RunOptions) -> String {
+        run(&mut new_state(options.clone()), selector)
+    }
+
+    fn new_state(options: RunOptions) -> ReplState {
+        let doc = MdDoc::parse(
+            "# First\n\nHello, world.\n\n# Second\n\nSee [the docs](https://example.com/docs).",
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let mut state = ReplState::new(options);
+        state.set_document(doc);
+        state
+    }
+
+    fn run(state: &mut ReplState, input: &str) -> String {
+        let command = ReplCommand::parse(input);
+        let mut output = Vec::new();
+        let mut status = Vec::new();
+        execute_command(&command, state, &mut output, &mut status).unwrap();
+        output.append(&mut status);
+        String::from_utf8(output).unwrap()
+    }
+}
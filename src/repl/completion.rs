@@ -0,0 +1,173 @@
+use crate::md_elem::{MdDoc, MdElem};
+use crate::output::{inlines_to_plain_string, InlineToStringOpts};
+
+/// The dot-commands, for completing a line that starts with "."
+const COMMANDS: &[&str] = &[
+    ".load", ".reload", ".format", ".set", ".get", ".let", ".vars", ".help", ".info", ".clear", ".narrow", ".pop",
+    ".exit",
+];
+
+/// The formats that `.format` takes
+const FORMATS: &[&str] = &["md", "json", "plain"];
+
+/// How selectors start, for completing the start of a selector (or of one of its `|` stages)
+const SELECTOR_PREFIXES: &[&str] = &[
+    "# ", "- ", "- [ ] ", "- [x] ", "- [?] ", "1. ", "> ", "[](", "![](", "```", "</> ", "P: ", ":-: ", "+++",
+];
+
+/// Completes the text before the cursor.
+///
+/// Returns the byte offset where the completed word starts, and what could replace it. Section selectors (as in
+/// `# Inst`) complete to the titles of the loaded document's sections.
+pub fn complete(line: &str, titles: &[String]) -> (usize, Vec<String>) {
+    let start = line.len() - line.trim_start().len();
+    let rest = &line[start..];
+    if rest.starts_with('.') {
+        return complete_command(line, start);
+    }
+    // Only the last stage of a selector chain matters
+    let stage_start = match line.rfind('|') {
+        Some(pipe) => pipe + 1,
+        None => start,
+    };
+    let stage_start = stage_start + (line[stage_start..].len() - line[stage_start..].trim_start().len());
+    let stage = &line[stage_start..];
+
+    let hashes = stage.len() - stage.trim_start_matches('#').len();
+    if hashes > 0 && stage[hashes..].starts_with(' ') {
+        let title_start = stage_start + hashes + (stage[hashes..].len() - stage[hashes..].trim_start().len());
+        let typed = line[title_start..].trim_start_matches(['"', '\'']).to_lowercase();
+        let candidates = titles
+            .iter()
+            .filter(|title| title.to_lowercase().starts_with(&typed))
+            .map(|title| title_selector(title))
+            .collect();
+        return (title_start, candidates);
+    }
+
+    let candidates = SELECTOR_PREFIXES
+        .iter()
+        .filter(|prefix| prefix.starts_with(stage))
+        .map(|prefix| prefix.to_string())
+        .collect();
+    (stage_start, candidates)
+}
+
+/// The titles of all the sections in the document, in document order, without duplicates
+pub fn section_titles(doc: &MdDoc) -> Vec<String> {
+    let mut titles = Vec::new();
+    add_section_titles(&doc.roots, &mut titles);
+    titles
+}
+
+fn add_section_titles(elems: &[MdElem], titles: &mut Vec<String>) {
+    for elem in elems {
+        match elem {
+            MdElem::Section(section) => {
+                let title = inlines_to_plain_string(&section.title, InlineToStringOpts::default());
+                if !titles.contains(&title) {
+                    titles.push(title);
+                }
+                add_section_titles(&section.body, titles);
+            }
+            MdElem::Doc(elems) => add_section_titles(elems, titles),
+            _ => {}
+        }
+    }
+}
+
+fn complete_command(line: &str, start: usize) -> (usize, Vec<String>) {
+    let rest = &line[start..];
+    match rest.split_once(char::is_whitespace) {
+        None => (
+            start,
+            COMMANDS
+                .iter()
+                .filter(|command| command.starts_with(rest))
+                .map(|command| command.to_string())
+                .collect(),
+        ),
+        Some((".format", format)) => {
+            let format = format.trim_start();
+            (
+                line.len() - format.len(),
+                FORMATS
+                    .iter()
+                    .filter(|name| name.starts_with(format))
+                    .map(|name| name.to_string())
+                    .collect(),
+            )
+        }
+        Some(_) => (line.len(), Vec::new()),
+    }
+}
+
+/// The title as selector text: unquoted if it can be, or else quoted
+fn title_selector(title: &str) -> String {
+    let mut chars = title.chars();
+    let plain = chars.next().is_some_and(char::is_alphabetic) && chars.all(|ch| ch.is_alphanumeric() || ch == ' ');
+    if plain {
+        return title.to_string();
+    }
+    let mut quoted = String::with_capacity(title.len() + 2);
+    quoted.push('"');
+    for ch in title.chars() {
+        if ch == '"' || ch == '\\' {
+            quoted.push('\\');
+        }
+        quoted.push(ch);
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::md_elem::ParseOptions;
+
+    #[test]
+    fn commands() {
+        assert_eq!(complete(".re", &[]), (0, vec![".reload".to_string()]));
+        assert_eq!(complete("  .f", &[]), (2, vec![".format".to_string()]));
+    }
+
+    #[test]
+    fn formats() {
+        assert_eq!(complete(".format j", &[]), (8, vec!["json".to_string()]));
+        assert_eq!(complete(".load READ", &[]), (10, vec![]));
+    }
+
+    #[test]
+    fn selector_prefixes() {
+        let (start, candidates) = complete("- [", &[]);
+        assert_eq!(start, 0);
+        assert_eq!(candidates, ["- [ ] ", "- [x] ", "- [?] "]);
+
+        assert_eq!(complete("# Usage | :", &[]), (10, vec![":-: ".to_string()]));
+    }
+
+    #[test]
+    fn section_titles_complete() {
+        let titles = ["Installation", "Usage", "1.0 release", "Install \"extras\""].map(str::to_string);
+
+        assert_eq!(
+            complete("## inst", &titles),
+            (
+                3,
+                vec!["Installation".to_string(), r#""Install \"extras\"""#.to_string()]
+            )
+        );
+        assert_eq!(
+            complete("# Usage | # 1", &titles),
+            (12, vec![r#""1.0 release""#.to_string()])
+        );
+    }
+
+    #[test]
+    fn titles_from_document() {
+        let doc = MdDoc::parse("# One\n\n## *Two*\n\n# One\n\n# Three", &ParseOptions::default()).unwrap();
+
+        assert_eq!(section_titles(&doc), ["One", "Two", "Three"]);
+    }
+}
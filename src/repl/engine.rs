@@ -1,40 +1,68 @@
-use crate::repl::{ReplSession, ReplState};
-use crate::repl::commands::{ReplCommand, execute_command};
+use crate::md_elem::ParseOptions;
+use crate::repl::commands::{execute_command, execute_query, ReplCommand};
+use crate::repl::completion::section_titles;
 use crate::repl::input::ReplInput;
+use crate::repl::{ReplSession, ReplState};
 use crate::run::RunOptions;
-use crate::output::MdWriterOptions;
-use std::io::{self, Write};
+use std::io::{self, BufRead, Write};
 
 /// The main REPL engine that coordinates the interactive session
 pub struct ReplEngine {
     /// Input handler for reading commands
     input: ReplInput,
-    
+
     /// Current run options
     options: RunOptions,
 }
 
+/// What happened when the engine executed a command
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Outcome {
+    Succeeded,
+    Failed,
+    Exit,
+}
+
+impl From<bool> for Outcome {
+    fn from(succeeded: bool) -> Self {
+        if succeeded {
+            Outcome::Succeeded
+        } else {
+            Outcome::Failed
+        }
+    }
+}
+
 impl ReplEngine {
     /// Creates a new REPL engine
     pub fn new(options: RunOptions) -> io::Result<Self> {
         Ok(Self {
-            input: ReplInput::default(),
+            input: ReplInput::new(1000, ReplInput::default_history_file())?,
+            options,
+        })
+    }
+
+    /// Creates a REPL engine for running scripts, which doesn't read or write the history file
+    pub fn for_script(options: RunOptions) -> io::Result<Self> {
+        Ok(Self {
+            input: ReplInput::new(1000, None)?,
             options,
         })
     }
 
     /// Runs the main REPL loop
     pub fn run(&mut self, session: &mut ReplSession) -> io::Result<()> {
-        let mut state = ReplState::new(self.options.clone());
-        let mut variables = std::collections::HashMap::new();
-        
+        let mut output = io::stdout();
+        let mut status = io::stdout();
+        let (mut state, _) = self.initial_state(session)?;
+
         // Show welcome message
         self.show_welcome()?;
-        
+
         // Main REPL loop
         loop {
             // Read command
-            let input = match self.input.read_line("mdq> ") {
+            let input = match self.input.read_line(&state.prompt()) {
                 Ok(Some(input)) => input,
                 Ok(None) => break, // EOF (Ctrl+D)
                 Err(e) => {
@@ -42,141 +70,199 @@ impl ReplEngine {
                     continue;
                 }
             };
-            
+
+            if input.is_empty() {
+                continue;
+            }
+
             // Parse and execute command
             let command = ReplCommand::parse(&input);
-            let should_continue = self.execute_command(&command, session, &mut state, &mut variables)?;
-            
-            if !should_continue {
+            if self.execute_command(&command, session, &mut state, &mut output, &mut status)? == Outcome::Exit {
                 break;
             }
         }
-        
+
+        if let Err(e) = self.input.save_history() {
+            writeln!(io::stderr(), "Error saving history: {}", e)?;
+        }
         writeln!(io::stdout(), "Goodbye!")?;
         Ok(())
     }
 
+    /// Runs REPL commands from a script, one per line, without prompts or banners
+    ///
+    /// Blank lines and lines starting with `//` are skipped. Results go to `output`, and status messages and errors
+    /// to `status`. Returns whether every command succeeded.
+    pub fn run_script(
+        &mut self,
+        session: &mut ReplSession,
+        script: impl BufRead,
+        output: &mut impl Write,
+        status: &mut impl Write,
+    ) -> io::Result<bool> {
+        let (mut state, mut succeeded) = self.initial_state(session)?;
+
+        for line in script.lines() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with("//") {
+                continue;
+            }
+
+            let command = ReplCommand::parse(line);
+            match self.execute_command(&command, session, &mut state, output, status)? {
+                Outcome::Succeeded => {}
+                Outcome::Failed => succeeded = false,
+                Outcome::Exit => break,
+            }
+        }
+
+        Ok(succeeded)
+    }
+
+    /// The state to start with. If a document given on the command line failed to parse, this also returns `false`.
+    fn initial_state(&mut self, session: &ReplSession) -> io::Result<(ReplState, bool)> {
+        let mut state = ReplState::new(self.options.clone());
+
+        // A document given on the command line has been read, but not yet parsed
+        if session.has_document() {
+            match session.parse_document(&ParseOptions::from(&self.options)) {
+                Ok(doc) => self.set_document(&mut state, doc),
+                Err(e) => {
+                    writeln!(io::stderr(), "Error parsing document: {}", e)?;
+                    return Ok((state, false));
+                }
+            }
+        }
+        Ok((state, true))
+    }
+
     /// Executes a REPL command
     fn execute_command(
-        &self,
+        &mut self,
         command: &ReplCommand,
         session: &mut ReplSession,
         state: &mut ReplState,
-        variables: &mut std::collections::HashMap<String, String>,
-    ) -> io::Result<bool> {
-        let mut output = io::stdout();
-        
+        output: &mut impl Write,
+        status: &mut impl Write,
+    ) -> io::Result<Outcome> {
         match command {
-            ReplCommand::Query(_) => {
-                // Execute query against current document
-                let document = state.document();
-                let mut options = self.build_writer_options(state);
-                
-                let should_continue = execute_command(
-                    command,
-                    document,
-                    &mut options,
-                    variables,
-                    &mut output,
-                )?;
-                
-                // Update state with new options - we don't need to update output format here
-                // since it's handled by the state management
-                
-                Ok(should_continue)
+            ReplCommand::Query(selector_str) => {
+                // Execute query against the working document, using the state's options (which .format changes)
+                let results = execute_query(selector_str, state, output, status)?;
+                let succeeded = results.is_some();
+                if let Some(results) = results {
+                    state.set_last_result(selector_str.clone(), results);
+                }
+
+                Ok(succeeded.into())
+            }
+            ReplCommand::Narrow => {
+                if state.narrow() {
+                    self.narrowed(state, status)?;
+                    Ok(Outcome::Succeeded)
+                } else {
+                    writeln!(status, "Nothing to narrow to: run a query that matches something first")?;
+                    Ok(Outcome::Failed)
+                }
+            }
+            ReplCommand::Pop => {
+                if state.pop_narrowing() {
+                    self.narrowed(state, status)?;
+                    Ok(Outcome::Succeeded)
+                } else {
+                    writeln!(status, "Already working with the whole document")?;
+                    Ok(Outcome::Failed)
+                }
             }
             ReplCommand::Load(path) => {
                 // Load document from file
                 match session.load_document_from_file(path.clone()) {
                     Ok(()) => {
                         // Parse the document
-                        match session.parse_document(self.options.allow_unknown_markdown) {
+                        match session.parse_document(&ParseOptions::from(&self.options)) {
                             Ok(doc) => {
-                                state.set_document(doc);
-                                writeln!(output, "Document loaded successfully: {}", path)?;
-                                writeln!(output, "{}", session.document_info())?;
+                                self.set_document(state, doc);
+                                writeln!(status, "Document loaded successfully: {}", path)?;
+                                writeln!(status, "{}", session.document_info())?;
+                                Ok(Outcome::Succeeded)
                             }
                             Err(e) => {
-                                writeln!(output, "Error parsing document: {}", e)?;
+                                writeln!(status, "Error parsing document: {}", e)?;
+                                Ok(Outcome::Failed)
                             }
                         }
                     }
                     Err(e) => {
-                        writeln!(output, "Error loading document: {}", e)?;
+                        writeln!(status, "Error loading document: {}", e)?;
+                        Ok(Outcome::Failed)
                     }
                 }
-                Ok(true)
             }
             ReplCommand::Reload => {
                 // Reload current document
                 match session.reload() {
-                    Ok(()) => {
-                        match session.parse_document(self.options.allow_unknown_markdown) {
-                            Ok(doc) => {
-                                state.set_document(doc);
-                                writeln!(output, "Document reloaded successfully")?;
-                                writeln!(output, "{}", session.document_info())?;
-                            }
-                            Err(e) => {
-                                writeln!(output, "Error parsing reloaded document: {}", e)?;
-                            }
+                    Ok(()) => match session.parse_document(&ParseOptions::from(&self.options)) {
+                        Ok(doc) => {
+                            self.set_document(state, doc);
+                            writeln!(status, "Document reloaded successfully")?;
+                            writeln!(status, "{}", session.document_info())?;
+                            Ok(Outcome::Succeeded)
                         }
-                    }
+                        Err(e) => {
+                            writeln!(status, "Error parsing reloaded document: {}", e)?;
+                            Ok(Outcome::Failed)
+                        }
+                    },
                     Err(e) => {
-                        writeln!(output, "Error reloading document: {}", e)?;
+                        writeln!(status, "Error reloading document: {}", e)?;
+                        Ok(Outcome::Failed)
                     }
                 }
-                Ok(true)
             }
             ReplCommand::Format(format) => {
                 // Change output format
                 state.set_output_format(*format);
-                writeln!(output, "Output format set to: {:?}", format)?;
-                Ok(true)
+                writeln!(status, "Output format set to: {:?}", format)?;
+                Ok(Outcome::Succeeded)
             }
             ReplCommand::Clear => {
                 // Clear current document
                 session.clear_document();
                 state.clear_document();
-                writeln!(output, "Document cleared")?;
-                Ok(true)
+                self.input.set_section_titles(Vec::new());
+                writeln!(status, "Document cleared")?;
+                Ok(Outcome::Succeeded)
             }
             ReplCommand::Exit => {
                 // Exit REPL
-                Ok(false)
+                Ok(Outcome::Exit)
             }
             _ => {
                 // Handle other commands
-                let document = state.document();
-                let mut options = self.build_writer_options(state);
-                
-                let should_continue = execute_command(
-                    command,
-                    document,
-                    &mut options,
-                    variables,
-                    &mut output,
-                )?;
-                
-                Ok(should_continue)
+                Ok(execute_command(command, state, output, status)?.into())
             }
         }
     }
 
-    /// Builds writer options from current state
-    fn build_writer_options(&self, state: &ReplState) -> MdWriterOptions {
-        let run_options = state.options();
-        
-        MdWriterOptions {
-            link_reference_placement: run_options.link_pos,
-            footnote_reference_placement: run_options.footnote_pos.unwrap_or(run_options.link_pos),
-            inline_options: crate::output::InlineElemOptions {
-                link_format: run_options.link_format,
-                renumber_footnotes: run_options.renumber_footnotes,
-            },
-            include_thematic_breaks: run_options.add_breaks.unwrap_or(true),
-            text_width: run_options.wrap_width,
+    /// Shows what the working document is now, and completes section selectors to its titles
+    fn narrowed(&mut self, state: &ReplState, status: &mut impl Write) -> io::Result<()> {
+        if let Some(doc) = state.document() {
+            self.input.set_section_titles(section_titles(doc));
+            let path = state.narrowing_path();
+            if path.is_empty() {
+                writeln!(status, "Working with the whole document")?;
+            } else {
+                writeln!(status, "Working within: {}", path.join(" > "))?;
+            }
         }
+        Ok(())
+    }
+
+    /// Sets the state's document, and completes section selectors to its titles
+    fn set_document(&mut self, state: &mut ReplState, doc: crate::md_elem::MdDoc) {
+        self.input.set_section_titles(section_titles(&doc));
+        state.set_document(doc);
     }
 
     /// Shows the welcome message
@@ -199,14 +285,65 @@ mod tests {
     fn test_command_parsing() {
         let command = ReplCommand::parse("# Section");
         assert!(matches!(command, ReplCommand::Query(_)));
-        
+
         let command = ReplCommand::parse(".help");
         assert!(matches!(command, ReplCommand::Help));
-        
+
         let command = ReplCommand::parse(".load test.md");
         assert!(matches!(command, ReplCommand::Load(_)));
-        
+
         let command = ReplCommand::parse(".format json");
         assert!(matches!(command, ReplCommand::Format(OutputFormat::Json)));
+
+        let command = ReplCommand::parse(".push");
+        assert!(matches!(command, ReplCommand::Narrow));
+
+        let command = ReplCommand::parse(".up");
+        assert!(matches!(command, ReplCommand::Pop));
+    }
+
+    #[test]
+    fn script() {
+        let (succeeded, output, status) =
+            run_script(".format plain\n\n// the first section\n# First | P: *\n.set s Second\n# $s\n");
+
+        assert!(succeeded);
+        assert_eq!(output, "Hello, world.\nSecond\nMore.\n");
+        assert_eq!(status, "Output format set to: Plain\nSet variable 's' = 'Second'\n");
+    }
+
+    #[test]
+    fn script_fails_if_any_command_fails() {
+        let (succeeded, output, status) = run_script("# Third\nP: hello\n");
+
+        assert!(!succeeded);
+        assert_eq!(output, "Hello, world.\n");
+        assert_eq!(status, "No elements matched the selector\n");
+    }
+
+    #[test]
+    fn script_stops_at_exit() {
+        let (succeeded, output, _) = run_script("P: hello\n.exit\n.bogus\n");
+
+        assert!(succeeded);
+        assert_eq!(output, "Hello, world.\n");
+    }
+
+    fn run_script(script: &str) -> (bool, String, String) {
+        let mut engine = ReplEngine::for_script(RunOptions::default()).unwrap();
+        let mut session = ReplSession::new();
+        session
+            .load_document("# First\n\nHello, world.\n\n# Second\n\nMore.".to_string())
+            .unwrap();
+        let mut output = Vec::new();
+        let mut status = Vec::new();
+        let succeeded = engine
+            .run_script(&mut session, script.as_bytes(), &mut output, &mut status)
+            .unwrap();
+        (
+            succeeded,
+            String::from_utf8(output).unwrap(),
+            String::from_utf8(status).unwrap(),
+        )
     }
 }
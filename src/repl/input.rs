@@ -1,116 +1,199 @@
-use std::io::{self, Write, BufRead, BufReader};
-use std::collections::VecDeque;
+use crate::repl::completion;
+use rustyline::completion::{Completer, FilenameCompleter, Pair};
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{CompletionType, Config, Context, Editor, Helper};
+use std::io;
+use std::path::PathBuf;
 
-/// Manages REPL input including history and line editing
+/// Manages REPL input: line editing, history (with arrow keys and Ctrl+R reverse search) and tab completion
 pub struct ReplInput {
-    /// Command history
-    history: VecDeque<String>,
-    
-    /// Maximum history size
-    max_history: usize,
-    
-    /// Current position in history (for navigation)
-    history_pos: Option<usize>,
+    /// The line editor, which also keeps the history
+    editor: Editor<ReplHelper, DefaultHistory>,
+
+    /// Where history is saved across sessions, if anywhere
+    history_file: Option<PathBuf>,
 }
 
 impl ReplInput {
-    /// Creates a new REPL input handler
-    pub fn new(max_history: usize) -> Self {
-        Self {
-            history: VecDeque::new(),
-            max_history,
-            history_pos: None,
+    /// Creates a new REPL input handler, and loads the history file if there is one
+    pub fn new(max_history: usize, history_file: Option<PathBuf>) -> io::Result<Self> {
+        let config = Config::builder()
+            .max_history_size(max_history)
+            .map_err(readline_error)?
+            .history_ignore_dups(true)
+            .map_err(readline_error)?
+            .completion_type(CompletionType::List)
+            .build();
+        let mut editor = Editor::with_config(config).map_err(readline_error)?;
+        editor.set_helper(Some(ReplHelper::default()));
+
+        if let Some(path) = &history_file {
+            match editor.load_history(path) {
+                Ok(()) => {}
+                // No history yet
+                Err(ReadlineError::Io(e)) if e.kind() == io::ErrorKind::NotFound => {}
+                Err(e) => return Err(readline_error(e)),
+            }
         }
+
+        Ok(Self { editor, history_file })
     }
 
-    /// Reads a line of input from stdin
+    /// Reads a line of input, with line editing if stdin is a terminal
+    ///
+    /// Returns `None` at EOF (Ctrl+D). Ctrl+C discards the line, and returns an empty one.
     pub fn read_line(&mut self, prompt: &str) -> io::Result<Option<String>> {
-        let stdout = io::stdout();
-        let mut stdout = stdout.lock();
-        
-        // Print prompt
-        write!(stdout, "{}", prompt)?;
-        stdout.flush()?;
-        drop(stdout);
-        
-        // Read input
-        let stdin = io::stdin();
-        let mut reader = BufReader::new(stdin);
-        let mut line = String::new();
-        
-        match reader.read_line(&mut line) {
-            Ok(0) => Ok(None), // EOF (Ctrl+D)
-            Ok(_) => {
+        match self.editor.readline(prompt) {
+            Ok(line) => {
                 let line = line.trim().to_string();
-                if !line.is_empty() {
-                    self.add_to_history(line.clone());
-                }
+                self.add_to_history(line.clone())?;
                 Ok(Some(line))
             }
-            Err(e) => Err(e),
+            Err(ReadlineError::Eof) => Ok(None),
+            Err(ReadlineError::Interrupted) => Ok(Some(String::new())),
+            Err(e) => Err(readline_error(e)),
         }
     }
 
     /// Adds a command to history
-    pub fn add_to_history(&mut self, command: String) {
-        // Don't add empty commands or duplicates
-        if command.is_empty() || self.history.back() == Some(&command) {
-            return;
+    pub fn add_to_history(&mut self, command: String) -> io::Result<()> {
+        // Don't add empty commands; the editor skips duplicates
+        if command.is_empty() {
+            return Ok(());
+        }
+        self.editor.add_history_entry(command).map_err(readline_error)?;
+        Ok(())
+    }
+
+    /// Saves the history to the history file, if there is one
+    pub fn save_history(&mut self) -> io::Result<()> {
+        match &self.history_file {
+            Some(path) => self.editor.save_history(path).map_err(readline_error),
+            None => Ok(()),
         }
-        
-        self.history.push_back(command);
-        
-        // Maintain history size limit
-        if self.history.len() > self.max_history {
-            self.history.pop_front();
+    }
+
+    /// Sets the section titles that `# ` selectors complete to
+    pub fn set_section_titles(&mut self, titles: Vec<String>) {
+        if let Some(helper) = self.editor.helper_mut() {
+            helper.titles = titles;
         }
-        
-        // Reset history position
-        self.history_pos = None;
+    }
+
+    /// The default history file: `$MDQ_HISTORY` if it's set, or else `.mdq_history` in the home directory
+    pub fn default_history_file() -> Option<PathBuf> {
+        if let Some(path) = std::env::var_os("MDQ_HISTORY") {
+            return Some(PathBuf::from(path));
+        }
+        let home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"))?;
+        Some(PathBuf::from(home).join(".mdq_history"))
     }
 }
 
-impl Default for ReplInput {
-    fn default() -> Self {
-        Self::new(1000)
+fn readline_error(e: ReadlineError) -> io::Error {
+    match e {
+        ReadlineError::Io(e) => e,
+        e => io::Error::other(e),
     }
 }
 
+/// Tab completion for the line editor
+#[derive(Default)]
+struct ReplHelper {
+    /// The loaded document's section titles
+    titles: Vec<String>,
+
+    files: FilenameCompleter,
+}
+
+impl Completer for ReplHelper {
+    type Candidate = Pair;
+
+    fn complete(&self, line: &str, pos: usize, ctx: &Context<'_>) -> rustyline::Result<(usize, Vec<Pair>)> {
+        if line.trim_start().starts_with(".load ") {
+            return self.files.complete(line, pos, ctx);
+        }
+        let (start, candidates) = completion::complete(&line[..pos], &self.titles);
+        let candidates = candidates
+            .into_iter()
+            .map(|candidate| Pair {
+                display: candidate.clone(),
+                replacement: candidate,
+            })
+            .collect();
+        Ok((start, candidates))
+    }
+}
+
+impl Hinter for ReplHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ReplHelper {}
+
+impl Validator for ReplHelper {}
+
+impl Helper for ReplHelper {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rustyline::history::{History, SearchDirection};
 
     #[test]
     fn test_history_management() {
-        let mut input = ReplInput::new(3);
-        
+        let mut input = ReplInput::new(3, None).unwrap();
+
         // Add commands
-        input.add_to_history("cmd1".to_string());
-        input.add_to_history("cmd2".to_string());
-        input.add_to_history("cmd3".to_string());
-        
+        input.add_to_history("cmd1".to_string()).unwrap();
+        input.add_to_history("cmd2".to_string()).unwrap();
+        input.add_to_history("cmd3".to_string()).unwrap();
+
         // Test history size limit
-        assert_eq!(input.history.len(), 3);
-        input.add_to_history("cmd4".to_string());
-        assert_eq!(input.history.len(), 3);
-        assert_eq!(input.history.front(), Some(&"cmd2".to_string()));
+        assert_eq!(input.editor.history().len(), 3);
+        input.add_to_history("cmd4".to_string()).unwrap();
+        assert_eq!(input.editor.history().len(), 3);
+        assert_eq!(history_entry(&input, 0), "cmd2");
     }
 
     #[test]
     fn test_duplicate_prevention() {
-        let mut input = ReplInput::new(5);
-        
-        input.add_to_history("cmd1".to_string());
-        input.add_to_history("cmd1".to_string()); // Duplicate
-        
-        assert_eq!(input.history.len(), 1);
+        let mut input = ReplInput::new(5, None).unwrap();
+
+        input.add_to_history("cmd1".to_string()).unwrap();
+        input.add_to_history("cmd1".to_string()).unwrap(); // Duplicate
+
+        assert_eq!(input.editor.history().len(), 1);
     }
 
     #[test]
     fn test_empty_command_handling() {
-        let mut input = ReplInput::new(5);
-        
-        input.add_to_history("".to_string());
-        assert_eq!(input.history.len(), 0);
+        let mut input = ReplInput::new(5, None).unwrap();
+
+        input.add_to_history("".to_string()).unwrap();
+        assert_eq!(input.editor.history().len(), 0);
+    }
+
+    #[test]
+    fn test_history_file() {
+        let path = std::env::temp_dir().join(format!("mdq_history_test_{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let mut input = ReplInput::new(5, Some(path.clone())).unwrap();
+        input.add_to_history("# Usage".to_string()).unwrap();
+        input.save_history().unwrap();
+
+        let reloaded = ReplInput::new(5, Some(path.clone())).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(history_entry(&reloaded, 0), "# Usage");
+    }
+
+    fn history_entry(input: &ReplInput, index: usize) -> String {
+        let result = input.editor.history().get(index, SearchDirection::Forward).unwrap();
+        result.expect("no history entry").entry.into_owned()
     }
 }
@@ -4,6 +4,7 @@
 //! Markdown documents without repeatedly invoking the command line.
 
 mod commands;
+mod completion;
 mod engine;
 mod input;
 mod session;
@@ -14,7 +15,7 @@ pub use session::ReplSession;
 pub use state::ReplState;
 
 use crate::run::{Error, RunOptions};
-use std::io;
+use std::io::{self, BufRead, Write};
 
 /// Main REPL engine that manages the interactive session
 pub struct Repl {
@@ -27,7 +28,15 @@ impl Repl {
     pub fn new(options: RunOptions) -> io::Result<Self> {
         let engine = ReplEngine::new(options)?;
         let session = ReplSession::new();
-        
+
+        Ok(Self { engine, session })
+    }
+
+    /// Creates a REPL instance for running scripts, which doesn't read or write the history file
+    pub fn for_script(options: RunOptions) -> io::Result<Self> {
+        let engine = ReplEngine::for_script(options)?;
+        let session = ReplSession::new();
+
         Ok(Self { engine, session })
     }
 
@@ -36,6 +45,14 @@ impl Repl {
         self.engine.run(&mut self.session)
     }
 
+    /// Runs REPL commands from a script (one per line) without prompts or banners, writing their results to `output`
+    ///
+    /// Status messages and errors go to stderr. Returns whether every command succeeded.
+    pub fn run_script(&mut self, script: impl BufRead, output: &mut impl Write) -> io::Result<bool> {
+        self.engine
+            .run_script(&mut self.session, script, output, &mut io::stderr())
+    }
+
     /// Loads a document into the REPL session
     pub fn load_document(&mut self, content: String) -> Result<(), Error> {
         self.session.load_document(content)
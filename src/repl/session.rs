@@ -1,4 +1,4 @@
-use crate::md_elem::{MdDoc, ParseOptions, InvalidMd};
+use crate::md_elem::{InvalidMd, MdDoc, ParseOptions};
 use crate::run::Error;
 
 /// Manages the REPL session including document loading and parsing
@@ -6,7 +6,7 @@ use crate::run::Error;
 pub struct ReplSession {
     /// Current document content as string
     content: Option<String>,
-    
+
     /// Current document path (if loaded from file)
     path: Option<String>,
 }
@@ -31,7 +31,7 @@ impl ReplSession {
     pub fn load_document_from_file(&mut self, path: String) -> Result<(), Error> {
         let content = std::fs::read_to_string(&path)
             .map_err(|e| Error::FileReadError(crate::run::Input::FilePath(path.clone()), e))?;
-        
+
         self.content = Some(content);
         self.path = Some(path);
         Ok(())
@@ -48,16 +48,13 @@ impl ReplSession {
     }
 
     /// Parses the current document content
-    pub fn parse_document(&self, allow_unknown_markdown: bool) -> Result<MdDoc, InvalidMd> {
-        let content = self.content.as_ref()
+    pub fn parse_document(&self, options: &ParseOptions) -> Result<MdDoc, InvalidMd> {
+        let content = self
+            .content
+            .as_ref()
             .ok_or_else(|| InvalidMd::ParseError("No document loaded".to_string()))?;
-        
-        let options = ParseOptions {
-            allow_unknown_markdown,
-            ..ParseOptions::default()
-        };
-        
-        MdDoc::parse(content, &options)
+
+        MdDoc::parse(content, options)
     }
 
     /// Reloads the current document from file (if it was loaded from a file)
@@ -1,5 +1,5 @@
 use crate::md_elem::MdDoc;
-use crate::run::{RunOptions, OutputFormat};
+use crate::run::{OutputFormat, RunOptions};
 use std::collections::HashMap;
 
 /// Represents the current state of a REPL session
@@ -7,49 +7,98 @@ use std::collections::HashMap;
 pub struct ReplState {
     /// Current document being worked with
     document: Option<MdDoc>,
-    
+
     /// Current run options (output format, link placement, etc.)
     options: RunOptions,
-    
+
     /// Variables stored during the session
     variables: HashMap<String, String>,
-    
+
     /// Command history
     history: Vec<String>,
-    
+
     /// Current output format
     current_format: OutputFormat,
+
+    /// The result of the last query that matched something, and that query's selector
+    last_result: Option<(String, MdDoc)>,
+
+    /// The results that `.narrow` made the working document, each with the selector that found it
+    narrowed: Vec<(String, MdDoc)>,
 }
 
 impl ReplState {
     /// Creates a new REPL state with default options
     pub fn new(options: RunOptions) -> Self {
         let current_format = options.output;
-        
+
         Self {
             document: None,
             options,
             variables: HashMap::new(),
             history: Vec::new(),
             current_format,
+            last_result: None,
+            narrowed: Vec::new(),
         }
     }
 
-    /// Sets the current document
+    /// Sets the current document, and goes back to working with all of it
     pub fn set_document(&mut self, doc: MdDoc) {
         self.document = Some(doc);
+        self.last_result = None;
+        self.narrowed.clear();
     }
 
-    /// Gets a reference to the current document
+    /// Gets a reference to the working document: the last result that `.narrow` narrowed to, or else the whole
+    /// document
     pub fn document(&self) -> Option<&MdDoc> {
-        self.document.as_ref()
+        match self.narrowed.last() {
+            Some((_, narrowed)) => Some(narrowed),
+            None => self.document.as_ref(),
+        }
     }
 
-    /// Gets a mutable reference to the current document
+    /// Gets a mutable reference to the current (whole) document
     pub fn document_mut(&mut self) -> Option<&mut MdDoc> {
         self.document.as_mut()
     }
 
+    /// Remembers a query's results, for `.narrow`
+    pub fn set_last_result(&mut self, selector: String, results: MdDoc) {
+        self.last_result = Some((selector, results));
+    }
+
+    /// Makes the last query's results the working document. Returns whether there were results to narrow to.
+    pub fn narrow(&mut self) -> bool {
+        match self.last_result.take() {
+            Some(result) => {
+                self.narrowed.push(result);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Goes back to the working document from before the last `.narrow`. Returns whether it was narrowed.
+    pub fn pop_narrowing(&mut self) -> bool {
+        self.last_result = None;
+        self.narrowed.pop().is_some()
+    }
+
+    /// The selectors that the working document was narrowed by, in order
+    pub fn narrowing_path(&self) -> Vec<&str> {
+        self.narrowed.iter().map(|(selector, _)| selector.as_str()).collect()
+    }
+
+    /// The prompt, which shows how the working document was narrowed (as in `mdq [# API > - [ ]]> `)
+    pub fn prompt(&self) -> String {
+        if self.narrowed.is_empty() {
+            return "mdq> ".to_string();
+        }
+        format!("mdq [{}]> ", self.narrowing_path().join(" > "))
+    }
+
     /// Gets the current options
     pub fn options(&self) -> &RunOptions {
         &self.options
@@ -70,6 +119,11 @@ impl ReplState {
         self.variables.get(name)
     }
 
+    /// Gets all the variables, which queries substitute as `$name` or `${name}`
+    pub fn variables(&self) -> &HashMap<String, String> {
+        &self.variables
+    }
+
     /// Adds a command to history
     pub fn add_to_history(&mut self, command: String) {
         self.history.push(command);
@@ -103,6 +157,8 @@ impl ReplState {
     /// Clears the document
     pub fn clear_document(&mut self) {
         self.document = None;
+        self.last_result = None;
+        self.narrowed.clear();
     }
 
     /// Checks if a document is loaded
@@ -110,3 +166,59 @@ impl ReplState {
         self.document.is_some()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::md_elem::ParseOptions;
+
+    #[test]
+    fn narrow_and_pop() {
+        let mut state = ReplState::new(RunOptions::default());
+        state.set_document(parse("# API\n\n- [ ] one\n- [x] two"));
+        assert_eq!(state.prompt(), "mdq> ");
+
+        state.set_last_result("# API".to_string(), parse("- [ ] one\n- [x] two"));
+        assert!(state.narrow());
+        state.set_last_result("- [ ]".to_string(), parse("- [ ] one"));
+        assert!(state.narrow());
+        assert_eq!(state.prompt(), "mdq [# API > - [ ]]> ");
+        assert_eq!(state.document().unwrap().roots.len(), 1);
+
+        assert!(state.pop_narrowing());
+        assert_eq!(state.prompt(), "mdq [# API]> ");
+        assert!(state.pop_narrowing());
+        assert!(!state.pop_narrowing());
+        assert_eq!(state.prompt(), "mdq> ");
+    }
+
+    #[test]
+    fn narrow_needs_results() {
+        let mut state = ReplState::new(RunOptions::default());
+        state.set_document(parse("hello"));
+
+        assert!(!state.narrow());
+
+        state.set_last_result("P: hello".to_string(), parse("hello"));
+        assert!(state.narrow());
+        // The same results can't be narrowed to twice
+        assert!(!state.narrow());
+    }
+
+    #[test]
+    fn new_document_resets_narrowing() {
+        let mut state = ReplState::new(RunOptions::default());
+        state.set_document(parse("hello"));
+        state.set_last_result("P: hello".to_string(), parse("hello"));
+        state.narrow();
+
+        state.set_document(parse("world"));
+
+        assert_eq!(state.prompt(), "mdq> ");
+        assert!(state.narrowing_path().is_empty());
+    }
+
+    fn parse(md: &str) -> MdDoc {
+        MdDoc::parse(md, &ParseOptions::default()).unwrap()
+    }
+}
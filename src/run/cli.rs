@@ -1,5 +1,7 @@
+use crate::md_elem::ParseOptions;
 use crate::output;
 use crate::output::{LinkTransform, ReferencePlacement};
+use crate::run::rewrite::Placement;
 use clap::error::ErrorKind;
 use clap::{CommandFactory, Parser, ValueEnum};
 use derive_builder::Builder;
@@ -99,6 +101,20 @@ macro_rules! create_options_structs {
                         None
                     }
                 };
+                let mut markdown_file_paths = value.markdown_file_paths;
+                // The REPL reads its selectors interactively, and the insertion options take theirs as values. Either
+                // way, the first positional arg is actually a file.
+                let selectors_elsewhere = value.repl
+                    || value.repl_script.is_some()
+                    || value.append_to.is_some()
+                    || value.prepend_to.is_some()
+                    || value.insert_before.is_some()
+                    || value.insert_after.is_some();
+                if selectors_elsewhere {
+                    if let Some(path) = value.selectors.take() {
+                        markdown_file_paths.insert(0, path);
+                    }
+                }
                 let selectors = match value.selectors.take() {
                     Some(s) => s,
                     None => match &value.list_selector {
@@ -113,7 +129,7 @@ macro_rules! create_options_structs {
                 };
                 Self {
                     $($name: value.$name,)*
-                    markdown_file_paths: value.markdown_file_paths,
+                    markdown_file_paths,
                     add_breaks,
                     selectors,
                 }
@@ -167,10 +183,129 @@ create_options_structs! {
     /// Enter interactive REPL mode
     ///
     /// When enabled, mdq will start an interactive session where you can
-    /// execute multiple queries without restarting the program.
+    /// execute multiple queries without restarting the program. Its history is saved to
+    /// $MDQ_HISTORY, or else ~/.mdq_history.
     clap(long, short)
     pub repl: bool,
 
+    /// Run a script of REPL commands, one per line, without prompts or banners.
+    ///
+    /// Blank lines and lines starting with "//" are skipped. Results go to standard output, and status messages
+    /// and errors to standard error. Scripts don't use or add to the REPL's history.
+    /// The exit code is for the whole script: it's a failure if any command failed, or if any query matched nothing.
+    /// With --repl, a script can also come from standard input, if it isn't a terminal.
+    clap(long, value_name = "FILE")
+    pub repl_script: Option<String>,
+
+    /// Only use the input files whose front matter matches this predicate.
+    ///
+    /// The predicate compares a front matter key to a value, as in `draft == false`, `author.name != "Jane Doe"` or
+    /// `tags contains rust`. Files without front matter, or without that key, are treated as not having the value.
+    clap(long = "where", value_name = "PREDICATE")
+    pub where_predicate: Option<String>,
+
+    /// Read directories recursively, and use the files within them as inputs, sorted by path.
+    ///
+    /// If there are no input paths, this reads the current directory. Symbolic links to directories are not followed.
+    ///
+    /// The short flag is -R, as in grep; -r is --repl. For example: mdq -R -- '- [ ]' docs/
+    clap(long, short = 'R')
+    pub recursive: bool,
+
+    /// With --recursive, only use the files that match this glob. Defaults to "*.md" and "*.markdown".
+    ///
+    /// In a glob, "*" matches within a file or directory name, "**" matches across directories, and "?" matches one
+    /// character. A glob without a "/" matches each file's name; otherwise, it matches the file's path within the
+    /// directory being read. This can be given more than once.
+    clap(long, value_name = "GLOB")
+    pub include: Vec<String>,
+
+    /// With --recursive, skip the files and directories that match this glob, as in "node_modules/**".
+    ///
+    /// Globs work the same as for --include. This can be given more than once.
+    clap(long, value_name = "GLOB")
+    pub exclude: Vec<String>,
+
+    /// Process each input file on its own, and print the file's name before its results.
+    ///
+    /// In JSON output, this puts each file's results in an object with a "file" field.
+    clap(long, short = 'H')
+    pub with_filename: bool,
+
+    /// Process each input file on its own, and only print the names of files that have results.
+    ///
+    /// This ignores --output. Unlike grep's, this has no -l short flag, since that's --link-format.
+    clap(long)
+    pub files_with_matches: bool,
+
+    /// Process each input file on its own, and only print each file's name and number of results.
+    ///
+    /// This ignores --output.
+    clap(long, short)
+    pub count: bool,
+
+    /// Apply the selector's `!s/regex/replacement/` replacements to the input files, and write them back.
+    ///
+    /// Only the replaced text changes; the rest of each file keeps its original formatting. Nothing is written to
+    /// stdout. The replaced text has to appear in the file as-is: text with escapes, for example, can't be replaced.
+    clap(long, short = 'i')
+    pub in_place: bool,
+
+    /// With --in-place, save a copy of each file that changes, at its path plus this suffix (as in ".bak").
+    clap(long, value_name = "SUFFIX", requires = "in_place")
+    pub backup_suffix: Option<String>,
+
+    /// Output the whole document, with the elements that the selector selects removed from it.
+    ///
+    /// Removing every item of a list removes the list, and removing everything within a paragraph removes the
+    /// paragraph. Selected elements have to be in the document as-is; a slice of a table, for example, can't be
+    /// removed.
+    clap(long, conflicts_with_all = ["in_place", "files_with_matches", "count"])
+    pub delete: bool,
+
+    /// Output the whole document, with --content-file's Markdown added to the end of each section this selects.
+    ///
+    /// This takes the place of the selectors argument, so the first argument after the options is a Markdown file.
+    clap(long, value_name = "SELECTORS", group = "insertion", requires = "content_file", allow_hyphen_values = true)
+    pub append_to: Option<String>,
+
+    /// Like --append-to, but adds the content to the start of each section's body, just after its title.
+    clap(long, value_name = "SELECTORS", group = "insertion", requires = "content_file", allow_hyphen_values = true)
+    pub prepend_to: Option<String>,
+
+    /// Like --append-to, but adds the content just before each element this selects, of any kind.
+    ///
+    /// Next to a list item, the content has to be a list; its items become items of the selected item's list.
+    clap(long, value_name = "SELECTORS", group = "insertion", requires = "content_file", allow_hyphen_values = true)
+    pub insert_before: Option<String>,
+
+    /// Like --insert-before, but adds the content just after each element this selects.
+    clap(long, value_name = "SELECTORS", group = "insertion", requires = "content_file", allow_hyphen_values = true)
+    pub insert_after: Option<String>,
+
+    /// The Markdown file to add, for --append-to, --prepend-to, --insert-before and --insert-after.
+    clap(
+        long,
+        value_name = "FILE",
+        requires = "insertion",
+        conflicts_with_all = ["in_place", "delete", "files_with_matches", "count"]
+    )
+    pub content_file: Option<String>,
+
+    /// Include where each result came from in the Markdown source.
+    ///
+    /// In JSON output, each element gets a "position" field with the "line", "column" and byte "offset" of its "start"
+    /// and "end". In Markdown and plain output, each result is preceded by its span, as in "3:1-5:12".
+    clap(long)
+    pub with_positions: bool,
+
+    /// Parse `$inline math$` and `$$` math blocks, so that the `$math$` and `$$` selectors can find them.
+    ///
+    /// This is off by default because with it, any text with two dollar signs is math: in "costs $5 or $10", the "5 or "
+    /// would be inline math.
+    clap(long)
+    pub math: bool,
+
     // See: tree.rs > Lookups::unknown_markdown.
     clap(long, hide = true)
     pub allow_unknown_markdown: bool,
@@ -187,12 +322,17 @@ create_options_structs! {
         add_breaks,
 
         /// The selectors string
+        ///
+        /// Selectors that start with a thematic break, as in "--- text", have to come after a "--" argument, so that they
+        /// aren't read as options. So do selectors that start with a list item, if file paths follow them:
+        /// `mdq -- '- [ ] todo' file.md`.
         selectors,
 
         /// An optional list of Markdown files to parse, by path. If not provided, standard input will be used.
         ///
         /// If these are provided, mdq will act as if they were all concatenated into a single file. For example, if you
-        /// use --link-pos=doc, the link definitions for all input files will be at the very end of the output.
+        /// use --link-pos=doc, the link definitions for all input files will be at the very end of the output. The
+        /// --with-filename, --files-with-matches and --count options instead process each file on its own.
         ///
         /// A path of "-" represents standard input.
         ///
@@ -217,6 +357,24 @@ impl Default for RunOptions {
             quiet: false,
             enhanced_errors: false,
             repl: false,
+            repl_script: None,
+            where_predicate: None,
+            recursive: false,
+            include: vec![],
+            exclude: vec![],
+            with_filename: false,
+            files_with_matches: false,
+            count: false,
+            in_place: false,
+            backup_suffix: None,
+            delete: false,
+            append_to: None,
+            prepend_to: None,
+            insert_before: None,
+            insert_after: None,
+            content_file: None,
+            with_positions: false,
+            math: false,
             allow_unknown_markdown: false,
             markdown_file_paths: vec![],
         }
@@ -238,7 +396,34 @@ impl From<&RunOptions> for output::MdWriterOptions {
     }
 }
 
+impl From<&RunOptions> for ParseOptions {
+    fn from(cli: &RunOptions) -> Self {
+        ParseOptions {
+            allow_unknown_markdown: cli.allow_unknown_markdown,
+            math: cli.math,
+            ..ParseOptions::default()
+        }
+    }
+}
+
 impl RunOptions {
+    /// Whether each input file should be parsed and selected on its own, rather than concatenated into one document.
+    pub fn per_file(&self) -> bool {
+        self.with_filename || self.files_with_matches || self.count
+    }
+
+    /// Where to add `--content-file`'s content, and the selectors for where to add it, if an insertion option is set.
+    pub(crate) fn insertion(&self) -> Option<(Placement, &str)> {
+        [
+            (Placement::AppendTo, &self.append_to),
+            (Placement::PrependTo, &self.prepend_to),
+            (Placement::Before, &self.insert_before),
+            (Placement::After, &self.insert_after),
+        ]
+        .into_iter()
+        .find_map(|(placement, selectors)| Some((placement, selectors.as_deref()?)))
+    }
+
     pub fn should_add_breaks(&self) -> bool {
         self.add_breaks.unwrap_or(match self.output {
             OutputFormat::Json => false,
@@ -282,6 +467,11 @@ impl CliOptions {
         self.repl
     }
 
+    /// Gets the REPL script path, if there is one
+    pub fn repl_script(&self) -> Option<&str> {
+        self.repl_script.as_deref()
+    }
+
     /// Gets the markdown file paths
     pub fn markdown_file_paths(&self) -> &[String] {
         &self.markdown_file_paths
@@ -289,10 +479,11 @@ impl CliOptions {
 }
 
 /// Output formats, analogous to `--output` in the CLI.
-#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, ValueEnum)]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, ValueEnum)]
 #[non_exhaustive]
 pub enum OutputFormat {
     /// Output results as Markdown.
+    #[default]
     Markdown,
 
     /// Alias for markdown
@@ -338,12 +529,6 @@ pub enum OutputFormat {
     Plain,
 }
 
-impl Default for OutputFormat {
-    fn default() -> Self {
-        Self::Markdown
-    }
-}
-
 impl Display for OutputFormat {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let self_str = match self {
@@ -358,6 +543,7 @@ impl Display for OutputFormat {
 #[cfg(test)]
 mod tests {
     use crate::run::cli::CliOptions;
+    use crate::run::rewrite::Placement;
     use crate::run::RunOptions;
     use crate::util::utils_for_test::*;
     use clap::{Error, Parser};
@@ -421,6 +607,27 @@ mod tests {
         );
     }
 
+    #[test]
+    fn start_with_thematic_break_selectors() {
+        let result = CliOptions::try_parse_from(["mdq", "--", "--- hello", "file.txt"]);
+        unwrap!(result, Ok(cli));
+        assert_eq!(cli.markdown_file_paths, ["file.txt"]);
+        let run_opts: RunOptions = cli.into();
+        assert_eq!(run_opts.selectors, "--- hello");
+    }
+
+    #[test]
+    fn thematic_break_selectors_need_double_dash() {
+        let result = CliOptions::try_parse_from(["mdq", "--- hello", "file.txt"]);
+        check_err(&result, "unexpected argument '--- hello' found");
+    }
+
+    #[test]
+    fn unknown_short_flag() {
+        let result = CliOptions::try_parse_from(["mdq", "-x", "file.md"]);
+        check_err(&result, "unexpected argument '-x' found");
+    }
+
     #[test]
     fn both_list_and_std_selectors() {
         let result = CliOptions::try_parse_from(["mdq", "# hello", "- world"]);
@@ -430,6 +637,48 @@ mod tests {
         )
     }
 
+    #[test]
+    fn insertion_takes_the_selectors() {
+        let result = CliOptions::try_parse_from([
+            "mdq",
+            "--append-to",
+            "# Changelog",
+            "--content-file",
+            "entry.md",
+            "CHANGELOG.md",
+            "other.md",
+        ]);
+        unwrap!(result, Ok(cli));
+        let run_opts: RunOptions = cli.into();
+        assert_eq!(run_opts.selectors, "");
+        assert_eq!(run_opts.markdown_file_paths, ["CHANGELOG.md", "other.md"]);
+        assert_eq!(run_opts.insertion(), Some((Placement::AppendTo, "# Changelog")));
+    }
+
+    #[test]
+    fn repl_takes_files() {
+        let result = CliOptions::try_parse_from(["mdq", "--repl", "README.md"]);
+        unwrap!(result, Ok(cli));
+        let run_opts: RunOptions = cli.into();
+        assert_eq!(run_opts.selectors, "");
+        assert_eq!(run_opts.markdown_file_paths, ["README.md"]);
+    }
+
+    #[test]
+    fn repl_script_takes_files() {
+        let result = CliOptions::try_parse_from(["mdq", "--repl-script", "audit.mdqs", "README.md"]);
+        unwrap!(result, Ok(cli));
+        assert_eq!(cli.repl_script(), Some("audit.mdqs"));
+        let run_opts: RunOptions = cli.into();
+        assert_eq!(run_opts.markdown_file_paths, ["README.md"]);
+    }
+
+    #[test]
+    fn insertion_requires_content_file() {
+        let result = CliOptions::try_parse_from(["mdq", "--insert-before", "# Changelog", "CHANGELOG.md"]);
+        check_err(&result, "the following required arguments were not provided:");
+    }
+
     #[test]
     fn both_br_and_no_br() {
         let result = CliOptions::try_parse_from(["mdq", "--br", "--no-br"]);
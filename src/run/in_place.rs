@@ -0,0 +1,613 @@
+use crate::md_elem::elem::*;
+use crate::md_elem::source_spans::SourceSpan;
+use crate::md_elem::{MdContext, MdDoc, MdElem, ParseOptions};
+use crate::run::{Error, Input};
+use crate::select::{SelectError, Selector, SelectorAdapter};
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// The result of applying a selector's replacements to one input.
+pub(crate) struct Edited {
+    /// Whether the selector selected anything.
+    pub(crate) found_any: bool,
+    /// The new contents of the input, or `None` if the replacements didn't change anything.
+    pub(crate) contents: Option<String>,
+    /// Anything the selector couldn't look at; see [`Selector::warnings`].
+    pub(crate) warnings: Vec<SelectError>,
+}
+
+/// Applies the selector's `!s/regex/replacement/` replacements to the Markdown source, changing only the text they
+/// replace.
+///
+/// This selects the elements twice: once as-is, and once without the replacements. Pairing those up tells us which
+/// text each replacement changed, and the [MdContext]'s source spans tell us where that text is.
+pub(crate) fn replace_in_source(
+    selector: &Selector,
+    input: &Input,
+    source: &str,
+    options: &ParseOptions,
+) -> Result<Edited, Error> {
+    let doc = MdDoc::parse(source, options).map_err(Error::MarkdownParse)?;
+    let warnings = selector.warnings(&doc);
+    let (replaced, ctx) = selector
+        .clone()
+        .find_nodes(doc.clone())
+        .map_err(Error::SelectionError)?;
+    let originals =
+        locate_originals(selector.clone().without_replacements(), doc, source.len()).map_err(Error::SelectionError)?;
+
+    let fail = |message: String| Error::InPlace(input.clone(), message);
+    if originals.len() != replaced.len() {
+        return Err(fail(
+            "the replacements changed which elements were selected".to_string(),
+        ));
+    }
+    let mut editor = SourceEditor {
+        source,
+        ctx: &ctx,
+        edits: Vec::new(),
+        offset: 0,
+        definitions: HashMap::new(),
+    };
+    for ((original, within), replaced) in originals.iter().zip(&replaced) {
+        editor.edit_elem(original, replaced, within).map_err(fail)?;
+    }
+    Ok(Edited {
+        found_any: !replaced.is_empty(),
+        contents: editor.apply(),
+        warnings,
+    })
+}
+
+/// Selects the elements, along with the range of the source that each one is within.
+///
+/// This runs a chain's stages one element at a time, so that each stage's results are known to be within the element
+/// they came from. That matters when the document has identical elements: in `# Two | P: hello`, only the "hello"
+/// paragraph in the "Two" section is selected, even if there's an identical one before it.
+pub(super) fn locate_originals(
+    selector: Selector,
+    doc: MdDoc,
+    source_len: usize,
+) -> crate::select::Result<Vec<(MdElem, Range<usize>)>> {
+    let MdDoc { roots, ctx } = doc;
+    let stages = match selector {
+        Selector::Chain(stages) => stages,
+        other => vec![other],
+    };
+    let mut results = vec![(MdElem::Doc(roots), 0..source_len)];
+    for stage in stages {
+        if let Selector::Position(position) = stage {
+            results = position.select(results);
+            continue;
+        }
+        let adapter = SelectorAdapter::from(stage);
+        let mut stage_results = Vec::with_capacity(results.len());
+        for (elem, within) in results {
+            let mut cursor = within.start;
+            for found in adapter.find_nodes(&ctx, vec![elem])? {
+                let found_within = match span_within(&ctx, &found, cursor..within.end) {
+                    Some(span) => {
+                        cursor = span.end.offset;
+                        span.start.offset..span.end.offset
+                    }
+                    None => cursor..within.end,
+                };
+                stage_results.push((found, found_within));
+            }
+        }
+        results = stage_results;
+    }
+    Ok(results)
+}
+
+/// The first span of the element that's within the given range.
+pub(super) fn span_within(ctx: &MdContext, elem: &MdElem, within: Range<usize>) -> Option<SourceSpan> {
+    ctx.get_source_spans(elem)
+        .iter()
+        .filter(|span| within.start <= span.start.offset && span.end.offset <= within.end)
+        .min_by_key(|span| span.start.offset)
+        .copied()
+}
+
+/// Replace the bytes in `range` with `text`.
+struct SourceEdit {
+    range: Range<usize>,
+    text: String,
+}
+
+struct SourceEditor<'a> {
+    source: &'a str,
+    ctx: &'a MdContext,
+    edits: Vec<SourceEdit>,
+    /// The end of the last element that we edited. Selected elements are in document order, so each one starts after
+    /// this.
+    offset: usize,
+    /// The link definitions that we edited, by where they start, and the URL and title that we changed each to.
+    definitions: HashMap<usize, (String, Option<String>)>,
+}
+
+impl SourceEditor<'_> {
+    fn edit_elem(&mut self, original: &MdElem, replaced: &MdElem, within: &Range<usize>) -> Result<(), String> {
+        if original == replaced {
+            return Ok(());
+        }
+        let Some(span) = span_within(self.ctx, original, within.start.max(self.offset)..within.end) else {
+            // Some selectors (like "--- text") select a Doc of elements, rather than an element from the source.
+            return match (original, replaced) {
+                (MdElem::Doc(originals), MdElem::Doc(replaced)) if originals.len() == replaced.len() => {
+                    for (original, replaced) in originals.iter().zip(replaced) {
+                        self.edit_elem(original, replaced, within)?;
+                    }
+                    Ok(())
+                }
+                _ => Err(format!(
+                    "couldn't find the selected {} in the source",
+                    elem_name(original)
+                )),
+            };
+        };
+
+        self.edit_task(original, replaced, span)?;
+
+        let mut original_texts = Vec::new();
+        texts(original, &mut original_texts);
+        let mut replaced_texts = Vec::new();
+        texts(replaced, &mut replaced_texts);
+        if original_texts.len() != replaced_texts.len() {
+            return Err(format!(
+                "the replacement changed the structure of the {}",
+                elem_name(original)
+            ));
+        }
+
+        self.edit_texts(original_texts, replaced_texts, span.start.offset..span.end.offset)?;
+        self.edit_link_definitions(original, replaced, span)?;
+        self.offset = span.end.offset;
+        Ok(())
+    }
+
+    /// Changes each of the old texts to its new one, looking for them in order within the range of the source.
+    fn edit_texts(&mut self, olds: Vec<&str>, news: Vec<&str>, range: Range<usize>) -> Result<(), String> {
+        let mut cursor = range.start;
+        for (old, new) in olds.into_iter().zip(news) {
+            let region = &self.source[cursor..range.end];
+            if old == new {
+                // Skip past the unchanged text, so that a later text can't be mistaken for it.
+                if let Some(idx) = region.find(old).filter(|_| !old.is_empty()) {
+                    cursor += idx + old.len();
+                }
+                continue;
+            }
+            let (edit, end) = locate_change(region, old, new).ok_or_else(|| {
+                format!("couldn't find {old:?} as-is in the source (it may be escaped or span several lines)")
+            })?;
+            self.edits.push(SourceEdit {
+                range: cursor + edit.range.start..cursor + edit.range.end,
+                text: edit.text,
+            });
+            cursor += end;
+        }
+        Ok(())
+    }
+
+    /// Changes the URLs and titles of reference-style links and images. Those aren't within the element, but in the
+    /// link's definition elsewhere in the source, as in the `[1]: https://example.com` of `[text][1]`.
+    fn edit_link_definitions(&mut self, original: &MdElem, replaced: &MdElem, span: SourceSpan) -> Result<(), String> {
+        let mut originals = Vec::new();
+        reference_links(original, &mut originals);
+        let mut replacements = Vec::new();
+        reference_links(replaced, &mut replacements);
+
+        let mut cursor = span.start.offset;
+        for ((link, original), (_, replaced)) in originals.into_iter().zip(replacements) {
+            let link_span = span_within(self.ctx, &link, cursor..span.end.offset);
+            if let Some(link_span) = link_span {
+                cursor = link_span.end.offset;
+            }
+            if (&original.url, &original.title) == (&replaced.url, &replaced.title) {
+                continue;
+            }
+            let definition = link_span
+                .and_then(|link_span| self.ctx.get_link_definition_span(&link_span))
+                .ok_or_else(|| format!("couldn't find the definition of the selected {}", elem_name(&link)))?;
+            // Several links can share a definition; it only gets changed once, and they all have to agree on how.
+            match self.definitions.get(&definition.start.offset) {
+                Some(already) if already == &(replaced.url.clone(), replaced.title.clone()) => continue,
+                Some(_) => {
+                    return Err(format!(
+                        "the replacements changed the definition of {:?} in different ways",
+                        original.url
+                    ))
+                }
+                None => {}
+            }
+            let mut original_texts = Vec::new();
+            link_texts(original, &mut original_texts);
+            let mut replaced_texts = Vec::new();
+            link_texts(replaced, &mut replaced_texts);
+            self.edit_texts(
+                original_texts,
+                replaced_texts,
+                definition.start.offset..definition.end.offset,
+            )?;
+            self.definitions
+                .insert(definition.start.offset, (replaced.url.clone(), replaced.title.clone()));
+        }
+        Ok(())
+    }
+
+    /// Changes the `[ ]` or `[x]` of a list item whose task was checked or unchecked.
+    fn edit_task(&mut self, original: &MdElem, replaced: &MdElem, span: SourceSpan) -> Result<(), String> {
+        let (MdElem::List(original), MdElem::List(replaced)) = (original, replaced) else {
+            return Ok(());
+        };
+        let checked_changed = |(old, new): (&ListItem, &ListItem)| old.checked != new.checked;
+        if !original.items.iter().zip(&replaced.items).any(checked_changed) {
+            return Ok(());
+        }
+        // Tasks get changed one item at a time, as single-item lists; so the item is at the start of the span.
+        let ([_], [replaced_item]) = (original.items.as_slice(), replaced.items.as_slice()) else {
+            return Err("can't change the tasks of a whole list at once".to_string());
+        };
+        let item_source = &self.source[span.start.offset..span.end.offset];
+        let checkbox = item_source
+            .find('[')
+            .filter(|&idx| matches!(item_source.get(idx..idx + 3), Some("[ ]" | "[x]" | "[X]")))
+            .ok_or_else(|| format!("couldn't find the task's checkbox in {item_source:?}"))?;
+        let mark_offset = span.start.offset + checkbox + 1;
+        let mark = if replaced_item.checked == Some(true) { "x" } else { " " };
+        self.edits.push(SourceEdit {
+            range: mark_offset..mark_offset + 1,
+            text: mark.to_string(),
+        });
+        Ok(())
+    }
+
+    fn apply(mut self) -> Option<String> {
+        if self.edits.is_empty() {
+            return None;
+        }
+        // Link definitions can be anywhere in the document, so their edits can be out of order.
+        self.edits.sort_by_key(|edit| edit.range.start);
+        let mut result = String::with_capacity(self.source.len());
+        let mut copied_to = 0;
+        for edit in self.edits {
+            result.push_str(&self.source[copied_to..edit.range.start]);
+            result.push_str(&edit.text);
+            copied_to = edit.range.end;
+        }
+        result.push_str(&self.source[copied_to..]);
+        Some(result)
+    }
+}
+
+/// Finds where `old` is within `region`, and returns the edit (relative to the region) that changes it to `new`, along
+/// with where in the region `old` ends.
+///
+/// The edit only covers the part of `old` that actually changed. If `old` isn't in the region as-is, as happens with
+/// paragraphs whose lines are indented or prefixed by `>`, this looks for just the line of it that changed.
+fn locate_change(region: &str, old: &str, new: &str) -> Option<(SourceEdit, usize)> {
+    let prefix = common_prefix_len(old, new);
+    let suffix = common_suffix_len(&old[prefix..], &new[prefix..]);
+    let (changed_start, changed_end) = (prefix, old.len() - suffix);
+    let text = new[prefix..new.len() - suffix].to_string();
+
+    let (search, search_start) = match region.find(old).filter(|_| !old.is_empty()) {
+        Some(_) => (old, 0),
+        None => {
+            let line_start = old[..changed_start].rfind('\n').map(|idx| idx + 1).unwrap_or(0);
+            let line_end = old[changed_end..]
+                .find('\n')
+                .map(|idx| changed_end + idx)
+                .unwrap_or(old.len());
+            let line = &old[line_start..line_end];
+            if line.is_empty() || line.contains('\n') {
+                return None;
+            }
+            (line, line_start)
+        }
+    };
+    let found = region.find(search)?;
+    let edit = SourceEdit {
+        range: found + changed_start - search_start..found + changed_end - search_start,
+        text,
+    };
+    Some((edit, found + search.len()))
+}
+
+fn common_prefix_len(a: &str, b: &str) -> usize {
+    a.char_indices()
+        .zip(b.chars())
+        .find(|((_, a_char), b_char)| a_char != b_char)
+        .map(|((idx, _), _)| idx)
+        .unwrap_or(a.len().min(b.len()))
+}
+
+fn common_suffix_len(a: &str, b: &str) -> usize {
+    a.char_indices()
+        .rev()
+        .zip(b.chars().rev())
+        .find(|((_, a_char), b_char)| a_char != b_char)
+        .map(|((idx, a_char), _)| a.len() - idx - a_char.len_utf8())
+        .unwrap_or(a.len().min(b.len()))
+}
+
+/// Collects the text within an element, in the order it appears in the source.
+fn texts<'a>(elem: &'a MdElem, out: &mut Vec<&'a str>) {
+    match elem {
+        MdElem::Doc(elems) => elems.iter().for_each(|elem| texts(elem, out)),
+        MdElem::BlockQuote(block) => block.body.iter().for_each(|elem| texts(elem, out)),
+        MdElem::FootnoteDefinition(def) => def.body.iter().for_each(|elem| texts(elem, out)),
+        MdElem::List(list) => {
+            for item in &list.items {
+                item.item.iter().for_each(|elem| texts(elem, out));
+            }
+        }
+        MdElem::Section(section) => {
+            inline_texts(&section.title, out);
+            section.body.iter().for_each(|elem| texts(elem, out));
+        }
+        MdElem::CodeBlock(block) => {
+            match &block.variant {
+                CodeVariant::Code(Some(opts)) => {
+                    out.push(&opts.language);
+                    out.extend(opts.metadata.as_deref());
+                }
+                CodeVariant::Math { metadata } => out.extend(metadata.as_deref()),
+                CodeVariant::Code(None) => {}
+            }
+            out.push(&block.value);
+        }
+        MdElem::FrontMatter(front_matter) => out.push(&front_matter.body),
+        MdElem::FrontMatterValue(value) => out.push(value.text()),
+        MdElem::Paragraph(paragraph) => inline_texts(&paragraph.body, out),
+        MdElem::Table(table) => {
+            for row in &table.rows {
+                row.iter().for_each(|cell| inline_texts(cell, out));
+            }
+        }
+        MdElem::Inline(inline) => inline_texts(std::slice::from_ref(inline), out),
+        MdElem::BlockHtml(html) => out.push(&html.value),
+        MdElem::ThematicBreak => {}
+    }
+}
+
+fn inline_texts<'a>(inlines: &'a [Inline], out: &mut Vec<&'a str>) {
+    for inline in inlines {
+        match inline {
+            Inline::Span(span) => inline_texts(&span.children, out),
+            Inline::Text(text) => out.push(&text.value),
+            // A reference-style link's URL and title are in its definition; see SourceEditor::edit_link_definitions.
+            Inline::Link(Link::Standard(link)) => {
+                inline_texts(&link.display, out);
+                if link.link.reference == LinkReference::Inline {
+                    link_texts(&link.link, out);
+                }
+            }
+            Inline::Link(Link::Autolink(autolink)) => out.push(&autolink.url),
+            Inline::Image(image) => {
+                out.push(&image.alt);
+                if image.link.reference == LinkReference::Inline {
+                    link_texts(&image.link, out);
+                }
+            }
+            Inline::Footnote(footnote) => out.push(footnote.as_str()),
+        }
+    }
+}
+
+/// Collects the reference-style links and images within an element, in the order they appear in the source.
+fn reference_links<'a>(elem: &'a MdElem, out: &mut Vec<(MdElem, &'a LinkDefinition)>) {
+    match elem {
+        MdElem::Doc(elems) => elems.iter().for_each(|elem| reference_links(elem, out)),
+        MdElem::BlockQuote(block) => block.body.iter().for_each(|elem| reference_links(elem, out)),
+        MdElem::FootnoteDefinition(def) => def.body.iter().for_each(|elem| reference_links(elem, out)),
+        MdElem::List(list) => {
+            for item in &list.items {
+                item.item.iter().for_each(|elem| reference_links(elem, out));
+            }
+        }
+        MdElem::Section(section) => {
+            inline_reference_links(&section.title, out);
+            section.body.iter().for_each(|elem| reference_links(elem, out));
+        }
+        MdElem::Paragraph(paragraph) => inline_reference_links(&paragraph.body, out),
+        MdElem::Table(table) => {
+            for row in &table.rows {
+                row.iter().for_each(|cell| inline_reference_links(cell, out));
+            }
+        }
+        MdElem::Inline(inline) => inline_reference_links(std::slice::from_ref(inline), out),
+        MdElem::CodeBlock(_)
+        | MdElem::FrontMatter(_)
+        | MdElem::FrontMatterValue(_)
+        | MdElem::BlockHtml(_)
+        | MdElem::ThematicBreak => {}
+    }
+}
+
+fn inline_reference_links<'a>(inlines: &'a [Inline], out: &mut Vec<(MdElem, &'a LinkDefinition)>) {
+    for inline in inlines {
+        match inline {
+            Inline::Span(span) => inline_reference_links(&span.children, out),
+            Inline::Link(Link::Standard(link)) => {
+                if link.link.reference != LinkReference::Inline {
+                    out.push((MdElem::Inline(inline.clone()), &link.link));
+                }
+                inline_reference_links(&link.display, out);
+            }
+            Inline::Image(image) if image.link.reference != LinkReference::Inline => {
+                out.push((MdElem::Inline(inline.clone()), &image.link));
+            }
+            Inline::Image(_) | Inline::Link(Link::Autolink(_)) | Inline::Text(_) | Inline::Footnote(_) => {}
+        }
+    }
+}
+
+fn link_texts<'a>(link: &'a LinkDefinition, out: &mut Vec<&'a str>) {
+    out.push(&link.url);
+    out.extend(link.title.as_deref());
+}
+
+pub(super) fn elem_name(elem: &MdElem) -> &'static str {
+    match elem {
+        MdElem::Doc(_) => "elements",
+        MdElem::BlockQuote(_) => "block quote",
+        MdElem::FootnoteDefinition(_) => "footnote",
+        MdElem::List(_) => "list item",
+        MdElem::Section(_) => "section",
+        MdElem::CodeBlock(_) => "code block",
+        MdElem::FrontMatter(_) | MdElem::FrontMatterValue(_) => "front matter",
+        MdElem::Paragraph(_) => "paragraph",
+        MdElem::Table(_) => "table",
+        MdElem::ThematicBreak => "thematic break",
+        MdElem::Inline(Inline::Link(_)) => "link",
+        MdElem::Inline(Inline::Image(_)) => "image",
+        MdElem::Inline(_) => "inline element",
+        MdElem::BlockHtml(_) => "html",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    #[test]
+    fn link_url_keeps_other_formatting() {
+        let source = indoc! {r"
+            | Name | Link                     |
+            |------|--------------------------|
+            | one  | [docs](https://old.example.com/a) |
+
+            * a  list [here](https://old.example.com/b)
+        "};
+
+        let edited = edit("[](!s/old.example.com/new.example.com/)", source);
+
+        assert_eq!(
+            edited,
+            indoc! {r"
+                | Name | Link                     |
+                |------|--------------------------|
+                | one  | [docs](https://new.example.com/a) |
+
+                * a  list [here](https://new.example.com/b)
+            "}
+        );
+    }
+
+    #[test]
+    fn url_same_as_display_text() {
+        let edited = edit("[](!s/http:/https:/)", "see [http://example.com](http://example.com)");
+
+        assert_eq!(edited, "see [http://example.com](https://example.com)");
+    }
+
+    #[test]
+    fn reference_link_url_in_definition() {
+        let edited = edit("[](!s/old/new/)", "[x][r]\n\n[r]: http://old.com/r");
+
+        assert_eq!(edited, "[x][r]\n\n[r]: http://new.com/r");
+    }
+
+    #[test]
+    fn shared_definition_edited_once() {
+        let source = indoc! {r#"
+            [one][r], [two][r] and ![image][]
+
+            [r]: http://old.com/r "old title"
+            [image]: http://old.com/image.png"#};
+
+        let edited = edit("[](!s/old/new/)", source);
+
+        assert_eq!(
+            edited,
+            indoc! {r#"
+                [one][r], [two][r] and ![image][]
+
+                [r]: http://new.com/r "old title"
+                [image]: http://old.com/image.png"#}
+        );
+    }
+
+    #[test]
+    fn reference_link_display_text() {
+        let edited = edit("[!s/old/new/](*)", "[old][old]\n\n[old]: http://old.com");
+
+        assert_eq!(edited, "[new][old]\n\n[old]: http://old.com");
+    }
+
+    #[test]
+    fn only_selected_duplicate() {
+        let source = indoc! {r"
+            # One
+
+            hello
+
+            # Two
+
+            hello"};
+
+        let edited = edit("# Two | P: !s/hello/world/", source);
+
+        assert_eq!(
+            edited,
+            indoc! {r"
+                # One
+
+                hello
+
+                # Two
+
+                world"}
+        );
+    }
+
+    #[test]
+    fn task_checkbox() {
+        let edited = edit("- [ ] deploy | check", "* [x] test\n* [ ]  deploy  now\n");
+
+        assert_eq!(edited, "* [x] test\n* [x]  deploy  now\n");
+    }
+
+    #[test]
+    fn task_checkbox_and_text() {
+        let edited = edit("1. [?] !s/todo/done/ | uncheck", "1. [X] todo\n");
+
+        assert_eq!(edited, "1. [ ] done\n");
+    }
+
+    #[test]
+    fn line_within_block_quote() {
+        let edited = edit("> !s/two/2/", "> one\n> two\n> three");
+
+        assert_eq!(edited, "> one\n> 2\n> three");
+    }
+
+    #[test]
+    fn escaped_text_fails() {
+        let selector = Selector::try_from("P: !s/a.b/c/").unwrap();
+        let result = replace_in_source(
+            &selector,
+            &Input::FilePath("x.md".to_string()),
+            r"a\*b",
+            &ParseOptions::default(),
+        );
+
+        assert!(result.is_err_and(|err| matches!(err, Error::InPlace(..))));
+    }
+
+    fn edit(selector: &str, source: &str) -> String {
+        let selector = Selector::try_from(selector).unwrap();
+        let edited = replace_in_source(
+            &selector,
+            &Input::FilePath("x.md".to_string()),
+            source,
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        assert!(edited.found_any);
+        edited.contents.expect("expected a change")
+    }
+}
@@ -50,7 +50,11 @@
 //! # }
 //! ```
 mod cli;
+mod in_place;
+mod rewrite;
 mod run_main;
+mod walk;
+mod where_predicate;
 
 pub use cli::*;
 pub use run_main::*;
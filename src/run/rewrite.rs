@@ -0,0 +1,600 @@
+use crate::md_elem::elem::*;
+use crate::md_elem::source_spans::SpanCursor;
+use crate::md_elem::{MdContext, MdDoc, MdElem, SourceSpan};
+use crate::run::in_place::{elem_name, locate_originals, span_within};
+use crate::select::Selector;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+
+/// A document, with the selected elements removed from it or changed.
+pub(crate) struct Rewritten {
+    /// Whether the selector selected anything.
+    pub(crate) found_any: bool,
+    pub(crate) roots: Vec<MdElem>,
+    pub(crate) ctx: MdContext,
+}
+
+/// Removes the elements that the selector selects from the document, and returns what's left.
+///
+/// Elements don't know where they are in the document, so this uses their source spans: it finds the span of each
+/// selected element, and then removes the elements with those spans. That way, an element that's identical to a
+/// selected one, but wasn't itself selected, stays in the document.
+pub(crate) fn delete_selected(selector: &Selector, doc: MdDoc, source_len: usize) -> Result<Rewritten, String> {
+    let originals = locate_originals(selector.clone().without_replacements(), doc.clone(), source_len)
+        .map_err(|err| err.to_string())?;
+    let mut targets = HashMap::with_capacity(originals.len());
+    for (original, within) in &originals {
+        add_targets(&doc.ctx, original, None, within, &mut targets)?;
+    }
+    rewrite(doc, &targets, "delete", !originals.is_empty())
+}
+
+/// Replaces the elements that the selector selects with what the selector turns them into (for example, by checking a
+/// task), and returns the whole document.
+///
+/// Like [`delete_selected`], this uses the elements' source spans to find them in the document.
+pub(crate) fn change_selected(selector: &Selector, doc: MdDoc, source_len: usize) -> Result<Rewritten, String> {
+    let (replaced, _) = selector
+        .clone()
+        .find_nodes(doc.clone())
+        .map_err(|err| err.to_string())?;
+    let originals = locate_originals(selector.clone().without_replacements(), doc.clone(), source_len)
+        .map_err(|err| err.to_string())?;
+    if originals.len() != replaced.len() {
+        return Err("the changes changed which elements were selected".to_string());
+    }
+    let mut targets = HashMap::with_capacity(originals.len());
+    for ((original, within), replaced) in originals.iter().zip(replaced) {
+        add_targets(&doc.ctx, original, Some(replaced), within, &mut targets)?;
+    }
+    rewrite(doc, &targets, "change", !originals.is_empty())
+}
+
+/// Where to add content, relative to each selected element.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Placement {
+    /// At the end of a section's body.
+    AppendTo,
+    /// At the start of a section's body, just after its title.
+    PrependTo,
+    /// Just before the element.
+    Before,
+    /// Just after the element.
+    After,
+}
+
+/// Adds the content to (or next to) each element that the selector selects, and returns the whole document.
+///
+/// Like [`delete_selected`], this uses the elements' source spans to find them in the document. An element that gets
+/// content next to it is replaced with a [`MdElem::Doc`] of it and the content, which then goes in among its siblings.
+pub(crate) fn insert_content(
+    selector: &Selector,
+    doc: MdDoc,
+    source_len: usize,
+    placement: Placement,
+    content: &MdDoc,
+) -> Result<Rewritten, String> {
+    let originals = locate_originals(selector.clone().without_replacements(), doc.clone(), source_len)
+        .map_err(|err| err.to_string())?;
+    let mut targets = HashMap::with_capacity(originals.len());
+    for (original, within) in &originals {
+        add_insertion_target(&doc.ctx, original, placement, &content.roots, within, &mut targets)?;
+    }
+    let mut doc = doc;
+    doc.ctx.add_footnotes(&content.ctx).map_err(|id| {
+        format!(
+            "the content's footnote [^{}] is different from the document's",
+            id.as_str()
+        )
+    })?;
+    rewrite(doc, &targets, "add content to", !originals.is_empty())
+}
+
+/// What to put in place of the element at each span: `None` to remove it.
+type Targets = HashMap<Range<usize>, Option<MdElem>>;
+
+fn rewrite(doc: MdDoc, targets: &Targets, verb: &str, found_any: bool) -> Result<Rewritten, String> {
+    let MdDoc { roots, ctx } = doc;
+    let mut rewriter = Rewriter {
+        spans: SpanCursor::new(&ctx),
+        targets,
+        rewritten: HashSet::with_capacity(targets.len()),
+        non_items: false,
+    };
+    let roots = rewriter.retain(roots);
+    if rewriter.non_items {
+        return Err("can only add lists next to a list item".to_string());
+    }
+    if let Some(missed) = targets.keys().find(|span| !rewriter.rewritten.contains(span)) {
+        // The rewriter only goes through the document's body, not its footnotes.
+        let in_footnote = ctx
+            .footnote_bodies()
+            .flatten()
+            .flat_map(|elem| ctx.get_source_spans(elem))
+            .any(|span| span.start.offset < missed.end && missed.start < span.end.offset);
+        return Err(if in_footnote {
+            format!("can't {verb} footnotes or the elements within them")
+        } else {
+            format!("couldn't find the selected elements to {verb} in the document")
+        });
+    }
+    Ok(Rewritten { found_any, roots, ctx })
+}
+
+fn add_targets(
+    ctx: &MdContext,
+    elem: &MdElem,
+    replacement: Option<MdElem>,
+    within: &Range<usize>,
+    targets: &mut Targets,
+) -> Result<(), String> {
+    if let Some(span) = span_within(ctx, elem, within.clone()) {
+        targets.insert(span.start.offset..span.end.offset, replacement);
+        return Ok(());
+    }
+    match (elem, replacement) {
+        // Some selectors (like "--- text") select a Doc of elements, rather than an element from the source.
+        (MdElem::Doc(elems), None) => elems
+            .iter()
+            .try_for_each(|elem| add_targets(ctx, elem, None, within, targets)),
+        (MdElem::Doc(elems), Some(MdElem::Doc(replacements))) if elems.len() == replacements.len() => elems
+            .iter()
+            .zip(replacements)
+            .try_for_each(|(elem, replacement)| add_targets(ctx, elem, Some(replacement), within, targets)),
+        _ => Err(not_found(elem)),
+    }
+}
+
+fn add_insertion_target(
+    ctx: &MdContext,
+    elem: &MdElem,
+    placement: Placement,
+    content: &[MdElem],
+    within: &Range<usize>,
+    targets: &mut Targets,
+) -> Result<(), String> {
+    let replacement = match (placement, elem) {
+        (Placement::AppendTo, MdElem::Section(section)) => MdElem::Section(Section {
+            body: section.body.iter().chain(content).cloned().collect(),
+            ..section.clone()
+        }),
+        (Placement::PrependTo, MdElem::Section(section)) => MdElem::Section(Section {
+            body: content.iter().chain(&section.body).cloned().collect(),
+            ..section.clone()
+        }),
+        (Placement::AppendTo | Placement::PrependTo, _) => {
+            return Err(format!(
+                "can only append or prepend to sections, not to the selected {}",
+                elem_name(elem)
+            ))
+        }
+        (_, MdElem::Inline(_)) => {
+            return Err(format!(
+                "can only add content next to blocks, not next to the selected {}",
+                elem_name(elem)
+            ))
+        }
+        // Some selectors (like "--- text") select a Doc of elements, rather than an element from the source.
+        (Placement::Before, MdElem::Doc(elems)) => {
+            return match elems.first() {
+                Some(first) => add_insertion_target(ctx, first, placement, content, within, targets),
+                None => Ok(()),
+            }
+        }
+        (Placement::After, MdElem::Doc(elems)) => {
+            return match elems.last() {
+                Some(last) => add_insertion_target(ctx, last, placement, content, within, targets),
+                None => Ok(()),
+            }
+        }
+        // List items are selected as single-item lists, so lists next to them become items of the same list.
+        (Placement::Before | Placement::After, MdElem::List(list)) if content.iter().all(is_list) => {
+            let mut items = Vec::with_capacity(list.items.len() + content.len());
+            if placement == Placement::After {
+                items.extend(list.items.iter().cloned());
+            }
+            items.extend(content.iter().flat_map(|elem| match elem {
+                MdElem::List(content_list) => content_list.items.clone(),
+                _ => Vec::new(),
+            }));
+            if placement == Placement::Before {
+                items.extend(list.items.iter().cloned());
+            }
+            MdElem::List(List {
+                starting_index: list.starting_index,
+                items,
+            })
+        }
+        (Placement::Before, _) => MdElem::Doc(content.iter().chain([elem]).cloned().collect()),
+        (Placement::After, _) => MdElem::Doc([elem].into_iter().chain(content).cloned().collect()),
+    };
+    let span = span_within(ctx, elem, within.clone()).ok_or_else(|| not_found(elem))?;
+    targets.insert(span.start.offset..span.end.offset, Some(replacement));
+    Ok(())
+}
+
+fn is_list(elem: &MdElem) -> bool {
+    matches!(elem, MdElem::List(_))
+}
+
+fn not_found(elem: &MdElem) -> String {
+    format!(
+        "can't use the selected {}, because it's only part of an element in the source",
+        elem_name(elem)
+    )
+}
+
+struct Rewriter<'a> {
+    spans: SpanCursor<'a>,
+    targets: &'a Targets,
+    /// The spans of the targets that have been rewritten.
+    rewritten: HashSet<Range<usize>>,
+    /// Whether content that isn't a list was added next to a list item, where only lists can go.
+    non_items: bool,
+}
+
+impl Rewriter<'_> {
+    fn retain(&mut self, elems: Vec<MdElem>) -> Vec<MdElem> {
+        let mut kept = Vec::with_capacity(elems.len());
+        for elem in elems {
+            match self.keep(elem) {
+                // An element with content added next to it; see insert_content.
+                Some(MdElem::Doc(elems)) => kept.extend(elems),
+                Some(elem) => kept.push(elem),
+                None => {}
+            }
+        }
+        kept
+    }
+
+    /// Returns the element with any selected elements within it rewritten. If the element itself was selected, this
+    /// returns its replacement, or `None` if it's removed.
+    fn keep(&mut self, elem: MdElem) -> Option<MdElem> {
+        let span = self.spans.enter(&elem);
+        if let Some(replacement) = self.target(span) {
+            self.spans.exit(span);
+            return replacement;
+        }
+        let kept = match elem {
+            MdElem::Doc(body) => Some(MdElem::Doc(self.retain(body))),
+            MdElem::Section(section) => Some(MdElem::Section(Section {
+                body: self.retain(section.body),
+                ..section
+            })),
+            MdElem::BlockQuote(block) => Some(MdElem::BlockQuote(BlockQuote {
+                body: self.retain(block.body),
+            })),
+            MdElem::List(list) => self.keep_list(list),
+            MdElem::Paragraph(paragraph) => {
+                let body = self.retain_inlines(paragraph.body);
+                (!body.is_empty()).then_some(MdElem::Paragraph(Paragraph { body }))
+            }
+            MdElem::Table(table) => {
+                let rows = table
+                    .rows
+                    .into_iter()
+                    .map(|row| row.into_iter().map(|cell| self.retain_inlines(cell)).collect())
+                    .collect();
+                Some(MdElem::Table(Table {
+                    alignments: table.alignments,
+                    rows,
+                }))
+            }
+            MdElem::Inline(inline) => self.keep_inline(inline).map(MdElem::Inline),
+            other @ (MdElem::CodeBlock(_)
+            | MdElem::FootnoteDefinition(_)
+            | MdElem::FrontMatter(_)
+            | MdElem::FrontMatterValue(_)
+            | MdElem::BlockHtml(_)
+            | MdElem::ThematicBreak) => Some(other),
+        };
+        self.spans.exit(span);
+        kept
+    }
+
+    /// Rewrites the selected list items. If that removes all of them, this removes the list, too.
+    fn keep_list(&mut self, list: List) -> Option<MdElem> {
+        let List { starting_index, items } = list;
+        let mut kept = Vec::with_capacity(items.len());
+        for (idx, item) in items.into_iter().enumerate() {
+            // Each item's span is keyed as a single-item list; see MdContext::get_source_spans.
+            let item_index = starting_index.map(|start| start + idx as u32);
+            let span = self.spans.enter(&MdElem::List(List {
+                starting_index: item_index,
+                items: vec![item.clone()],
+            }));
+            if let Some(replacement) = self.target(span) {
+                self.spans.exit(span);
+                // Items are selected as single-item lists, so that's what they're replaced with.
+                match replacement {
+                    Some(MdElem::List(replacement)) => kept.extend(replacement.items),
+                    // Content that isn't a list, next to an item; see insert_content.
+                    Some(MdElem::Doc(_)) => self.non_items = true,
+                    _ => {}
+                }
+                continue;
+            }
+            let item = ListItem {
+                item: self.retain(item.item),
+                ..item
+            };
+            self.spans.exit(span);
+            kept.push(item);
+        }
+        (!kept.is_empty()).then_some(MdElem::List(List {
+            starting_index,
+            items: kept,
+        }))
+    }
+
+    fn retain_inlines(&mut self, inlines: Vec<Inline>) -> Vec<Inline> {
+        inlines
+            .into_iter()
+            .filter_map(|inline| match self.keep(MdElem::Inline(inline)) {
+                Some(MdElem::Inline(inline)) => Some(inline),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn keep_inline(&mut self, inline: Inline) -> Option<Inline> {
+        match inline {
+            Inline::Span(span) => {
+                let children = self.retain_inlines(span.children);
+                (!children.is_empty()).then_some(Inline::Span(Span {
+                    variant: span.variant,
+                    children,
+                }))
+            }
+            Inline::Link(Link::Standard(link)) => {
+                let display = self.retain_inlines(link.display);
+                Some(Inline::Link(Link::Standard(StandardLink { display, ..link })))
+            }
+            other => Some(other),
+        }
+    }
+
+    /// If the span is one of the targets, returns what to replace its element with.
+    fn target(&mut self, span: Option<SourceSpan>) -> Option<Option<MdElem>> {
+        let span = span?;
+        let range = span.start.offset..span.end.offset;
+        let replacement = self.targets.get(&range)?;
+        self.rewritten.insert(range);
+        Some(replacement.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::md_elem::ParseOptions;
+    use crate::output::{MdWriter, MdWriterOptions};
+    use indoc::indoc;
+
+    #[test]
+    fn completed_tasks() {
+        let deleted = delete(
+            "- [x]",
+            indoc! {r"
+                - [x] done
+                - [ ] not done
+                - [x] also done"},
+        );
+
+        assert_eq!(deleted, "- [ ] not done\n");
+    }
+
+    #[test]
+    fn inline_html() {
+        let deleted = delete("</>", "Inline <span>x</span> here.\n\n<span>only html</span>");
+
+        assert_eq!(deleted, "Inline x here.\n\nonly html\n");
+    }
+
+    #[test]
+    fn whole_list() {
+        let deleted = delete("- [x]", "before\n\n- [x] done\n\nafter");
+
+        assert_eq!(deleted, "before\n\nafter\n");
+    }
+
+    #[test]
+    fn only_selected_duplicate() {
+        let deleted = delete(
+            "# Two | P: hello",
+            indoc! {r"
+                # One
+
+                hello
+
+                # Two
+
+                hello
+
+                world"},
+        );
+
+        assert_eq!(
+            deleted,
+            indoc! {r"
+                # One
+
+                hello
+
+                # Two
+
+                world
+            "}
+        );
+    }
+
+    #[test]
+    fn link_within_paragraph() {
+        let deleted = delete(
+            "[](^https://internal)",
+            "see [this](https://internal.example.com) and more",
+        );
+
+        assert_eq!(deleted, "see  and more\n");
+    }
+
+    #[test]
+    fn checks_only_selected_duplicate() {
+        let source = indoc! {r"
+            # One
+
+            - [ ] deploy
+
+            # Two
+
+            - [ ] deploy"};
+        let doc = MdDoc::parse(source, &ParseOptions::default()).unwrap();
+        let selector = Selector::try_from("# two | - [ ] deploy | check").unwrap();
+
+        let changed = change_selected(&selector, doc, source.len()).unwrap();
+
+        assert_eq!(
+            write(changed),
+            indoc! {r"
+                # One
+
+                - [ ] deploy
+
+                # Two
+
+                - [x] deploy
+            "}
+        );
+    }
+
+    #[test]
+    fn prepends_to_section() {
+        let inserted = insert(
+            "## Unreleased",
+            Placement::PrependTo,
+            "- new entry",
+            indoc! {r"
+                # Changelog
+
+                ## Unreleased
+
+                - old entry
+
+                ## 1.0.0
+
+                - first release"},
+        );
+
+        assert_eq!(
+            inserted.unwrap(),
+            indoc! {r"
+                # Changelog
+
+                ## Unreleased
+
+                - new entry
+
+                - old entry
+
+                ## 1.0.0
+
+                - first release
+            "}
+        );
+    }
+
+    #[test]
+    fn inserts_after_only_selected_duplicate() {
+        let inserted = insert(
+            "# Two | P: hello",
+            Placement::After,
+            "inserted",
+            indoc! {r"
+                # One
+
+                hello
+
+                # Two
+
+                hello"},
+        );
+
+        assert_eq!(
+            inserted.unwrap(),
+            indoc! {r"
+                # One
+
+                hello
+
+                # Two
+
+                hello
+
+                inserted
+            "}
+        );
+    }
+
+    #[test]
+    fn inserts_items_before_list_item() {
+        let inserted = insert("- two", Placement::Before, "- one and a half", "- one\n- two\n- three");
+
+        assert_eq!(inserted.unwrap(), "- one\n- one and a half\n- two\n- three\n");
+    }
+
+    #[test]
+    fn only_lists_next_to_list_item() {
+        let inserted = insert("- two", Placement::After, "a paragraph", "- one\n- two");
+
+        assert_eq!(
+            inserted.err().as_deref(),
+            Some("can only add lists next to a list item")
+        );
+    }
+
+    #[test]
+    fn append_only_to_sections() {
+        let inserted = insert("P: hello", Placement::AppendTo, "more", "hello");
+
+        assert_eq!(
+            inserted.err().as_deref(),
+            Some("can only append or prepend to sections, not to the selected paragraph")
+        );
+    }
+
+    #[test]
+    fn table_slice_fails() {
+        let doc = MdDoc::parse("| a | b |\n|---|---|\n| 1 | 2 |", &ParseOptions::default()).unwrap();
+        let selector = Selector::try_from(":-: a :-: *").unwrap();
+
+        let result = delete_selected(&selector, doc, 29);
+
+        assert_eq!(
+            result.err().as_deref(),
+            Some("can't use the selected table, because it's only part of an element in the source")
+        );
+    }
+
+    fn delete(selector: &str, source: &str) -> String {
+        let doc = MdDoc::parse(source, &ParseOptions::default()).unwrap();
+        let selector = Selector::try_from(selector).unwrap();
+        write(delete_selected(&selector, doc, source.len()).unwrap())
+    }
+
+    fn insert(selector: &str, placement: Placement, content: &str, source: &str) -> Result<String, String> {
+        let doc = MdDoc::parse(source, &ParseOptions::default()).unwrap();
+        let content = MdDoc::parse(content, &ParseOptions::default()).unwrap();
+        let selector = Selector::try_from(selector).unwrap();
+        insert_content(&selector, doc, source.len(), placement, &content).map(write)
+    }
+
+    fn write(rewritten: Rewritten) -> String {
+        let mut out = String::new();
+        MdWriter::with_options(MdWriterOptions::default()).write(
+            &rewritten.ctx,
+            &[MdElem::Doc(rewritten.roots)],
+            &mut out,
+        );
+        out
+    }
+}
@@ -1,11 +1,20 @@
-use crate::md_elem::{InvalidMd, ParseOptions};
-use crate::output::{MdWriter, MdWriterOptions, SerializableMd};
+use crate::md_elem::elem::{BlockHtml, Inline, Paragraph, Text, TextVariant};
+use crate::md_elem::source_spans::SpanCursor;
+use crate::md_elem::{InvalidMd, MdContext, MdDoc, MdElem, ParseOptions};
+use crate::output::{InlineElemOptions, MdWriter, MdWriterOptions, SerializableMd};
 use crate::query::{InnerParseError, ParseError};
 use crate::run::cli::OutputFormat;
+use crate::run::in_place;
+use crate::run::rewrite;
+use crate::run::rewrite::Placement;
+use crate::run::walk;
+use crate::run::where_predicate::WherePredicate;
 use crate::run::RunOptions;
 use crate::select::{SelectError, Selector};
 use crate::{md_elem, output, query};
 use pest::Span;
+use serde::Serialize;
+use std::borrow::Cow;
 use std::fmt::{Display, Formatter};
 use std::io;
 use std::io::Write;
@@ -27,11 +36,36 @@ pub enum Error {
     /// Couldn't read an input file.
     FileReadError(Input, io::Error),
 
+    /// Couldn't write an input file back, for [`RunOptions::in_place`].
+    FileWriteError(Input, io::Error),
+
+    /// Couldn't apply the replacements to an input's source, for [`RunOptions::in_place`].
+    InPlace(Input, String),
+
+    /// Couldn't remove the selected elements from the document, for [`RunOptions::delete`].
+    Delete(String),
+
+    /// Couldn't change the tasks that a [`Selector::TaskAction`] selected within the document.
+    ChangeTasks(String),
+
+    /// Couldn't add the content to the document, for [`RunOptions::append_to`] and the like.
+    Insert(String),
+
     /// An error occurred during selection processing.
     SelectionError(SelectError),
 
+    /// User provided an invalid `--where` predicate.
+    WherePredicate(String),
+
     /// Other error types (used for enhanced error reporting).
     Other(String),
+
+    /// Part of the document that the selector couldn't look at, like front matter that doesn't parse when selecting
+    /// its keys.
+    ///
+    /// Unlike the other variants, this doesn't make [`run`] fail: it reports these through [`OsFacade::write_error`]
+    /// as it goes, and carries on.
+    Warning(SelectError),
 }
 
 impl std::error::Error for Error {}
@@ -74,6 +108,16 @@ pub enum Input {
     FilePath(String),
 }
 
+impl Input {
+    /// The name to show for this input in per-file output.
+    pub(crate) fn name(&self) -> &str {
+        match self {
+            Input::Stdin => "(standard input)",
+            Input::FilePath(file) => file,
+        }
+    }
+}
+
 impl Error {
     pub(crate) fn from_io_error(error: io::Error, file: Input) -> Self {
         Error::FileReadError(file, error)
@@ -112,13 +156,47 @@ impl Display for Error {
                 }
                 Ok(())
             }
+            Error::FileWriteError(file, err) => {
+                #[cfg(test)]
+                {
+                    writeln!(f, "{err} while writing {file}")?
+                }
+                #[cfg(not(test))]
+                {
+                    writeln!(f, "{} while writing {file}", err.kind())?
+                }
+                Ok(())
+            }
+            Error::InPlace(file, err) => {
+                writeln!(f, "Couldn't edit {file} in place:")?;
+                writeln!(f, "{err}")
+            }
+            Error::Delete(err) => {
+                writeln!(f, "Couldn't delete the selected elements:")?;
+                writeln!(f, "{err}")
+            }
+            Error::ChangeTasks(err) => {
+                writeln!(f, "Couldn't change the selected tasks:")?;
+                writeln!(f, "{err}")
+            }
+            Error::Insert(err) => {
+                writeln!(f, "Couldn't add the content:")?;
+                writeln!(f, "{err}")
+            }
             Error::SelectionError(err) => {
                 writeln!(f, "Selection error:")?;
                 writeln!(f, "{err}")
             }
+            Error::WherePredicate(err) => {
+                writeln!(f, "Invalid --where predicate:")?;
+                writeln!(f, "{err}")
+            }
             Error::Other(msg) => {
                 write!(f, "{}", msg)
             }
+            Error::Warning(err) => {
+                writeln!(f, "warning: {err}")
+            }
         }
     }
 }
@@ -139,6 +217,24 @@ pub trait OsFacade {
     /// Handle an error.
     fn write_error(&mut self, err: Error);
 
+    /// List the entries of a directory (or your mock of one), by name.
+    ///
+    /// This returns `Ok(None)` if the path isn't a directory. The default implementation treats every path as a file,
+    /// so you need to override this for [`RunOptions::recursive`] to do anything.
+    fn read_dir(&self, _path: &str) -> io::Result<Option<Vec<String>>> {
+        Ok(None)
+    }
+
+    /// Replace a file's (or your mock of one's) contents.
+    ///
+    /// The default implementation always fails, so you need to override this for [`RunOptions::in_place`] to work.
+    fn write_file(&mut self, _path: &str, _contents: &str) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "writing files isn't supported",
+        ))
+    }
+
     /// Read a slice of file paths into a single, concatenated `String`.
     ///
     /// The default implementation (which you should feel free to use) treats the file path `"-"` as stdin. The first
@@ -148,26 +244,44 @@ pub trait OsFacade {
             return self.read_stdin().map_err(|err| Error::from_io_error(err, Input::Stdin));
         }
         let mut contents = String::new();
+        for (_, path_contents) in self.read_each(markdown_file_paths)? {
+            contents.push_str(&path_contents);
+            contents.push('\n');
+        }
+        Ok(contents)
+    }
+
+    /// Read a slice of file paths into a `String` per input.
+    ///
+    /// Like [`Self::read_all`], the default implementation treats the file path `"-"` as stdin, and ignores all but
+    /// the first `"-"`. An empty slice reads just stdin.
+    fn read_each(&self, markdown_file_paths: &[String]) -> Result<Vec<(Input, String)>, Error> {
+        if markdown_file_paths.is_empty() {
+            let contents = self
+                .read_stdin()
+                .map_err(|err| Error::from_io_error(err, Input::Stdin))?;
+            return Ok(vec![(Input::Stdin, contents)]);
+        }
+        let mut results = Vec::with_capacity(markdown_file_paths.len());
         let mut have_read_stdin = false;
         for path in markdown_file_paths {
             if path == "-" {
                 if !have_read_stdin {
-                    contents.push_str(
-                        &self
-                            .read_stdin()
-                            .map_err(|err| Error::from_io_error(err, Input::Stdin))?,
-                    );
+                    let contents = self
+                        .read_stdin()
+                        .map_err(|err| Error::from_io_error(err, Input::Stdin))?;
+                    results.push((Input::Stdin, contents));
                     have_read_stdin = true
                 }
             } else {
-                let path_contents = self
+                let input = Input::FilePath(path.to_string());
+                let contents = self
                     .read_file(path)
-                    .map_err(|err| Error::from_io_error(err, Input::FilePath(path.to_string())))?;
-                contents.push_str(&path_contents);
+                    .map_err(|err| Error::from_io_error(err, input.clone()))?;
+                results.push((input, contents));
             }
-            contents.push('\n');
         }
-        Ok(contents)
+        Ok(results)
     }
 }
 
@@ -177,67 +291,460 @@ pub trait OsFacade {
 /// string in [`RunOptions::selectors`], and then writes them to the given [`OsFacade`] in the format specified by
 /// [`RunOptions::output`].
 pub fn run(cli: &RunOptions, os: &mut impl OsFacade) -> bool {
-            match run_or_error(cli, os) {
-            Ok(ok) => ok,
-            Err(err) => {
-                if cli.enhanced_errors {
-                    let enhanced_error = display_error_with_enhancements(&err, true);
-                    // Write the enhanced error through the OsFacade for testing
-                    os.write_error(Error::Other(enhanced_error));
-                } else {
-                    os.write_error(err);
-                }
-                false
+    match run_or_error(cli, os) {
+        Ok(ok) => ok,
+        Err(err) => {
+            if cli.enhanced_errors {
+                let enhanced_error = display_error_with_enhancements(&err, true);
+                // Write the enhanced error through the OsFacade for testing
+                os.write_error(Error::Other(enhanced_error));
+            } else {
+                os.write_error(err);
             }
+            false
         }
+    }
 }
 
 fn run_or_error(cli: &RunOptions, os: &mut impl OsFacade) -> Result<bool, Error> {
-    let contents_str = os.read_all(&cli.markdown_file_paths)?;
-    let options = ParseOptions::default();
-    let options = ParseOptions {
-        allow_unknown_markdown: cli.allow_unknown_markdown,
-        ..options
+    let options = ParseOptions::from(cli);
+    let paths = input_paths(os, cli)?;
+    if cli.in_place {
+        return run_in_place(cli, os, paths.as_deref(), &options);
+    }
+    if cli.per_file() {
+        return run_per_file(cli, os, paths.as_deref(), &options);
+    }
+    let contents_str = match (&paths, &cli.where_predicate) {
+        (None, _) => String::new(),
+        (Some(paths), None) => os.read_all(paths)?,
+        (Some(paths), Some(predicate)) => read_matching(os, paths, predicate, &options)?,
     };
     let md_doc = md_elem::MdDoc::parse(&contents_str, &options).map_err(Error::MarkdownParse)?;
 
-    let selectors_str = &cli.selectors;
-    let selectors: Selector = match selectors_str.try_into() {
-        Ok(selectors) => selectors,
-        Err(error) => {
-            return Err(Error::QueryParse(QueryParseError {
-                query_string: selectors_str.to_string(),
-                error,
-            }));
+    let (selectors, insertion) = selectors_and_insertion(cli, os, &options)?;
+    write_warnings(os, &selectors, &md_doc);
+
+    let selected = select_output(cli, selectors, insertion.as_ref(), md_doc, contents_str.len())?;
+
+    if !cli.quiet {
+        write_results(cli, &selected.nodes, &selected.starts, &selected.ctx, &mut os.stdout());
+    }
+
+    Ok(selected.found_any)
+}
+
+fn write_warnings(os: &mut impl OsFacade, selectors: &Selector, md_doc: &MdDoc) {
+    for warning in selectors.warnings(md_doc) {
+        os.write_error(Error::Warning(warning));
+    }
+}
+
+/// The paths to read, after expanding any `--recursive` directories.
+///
+/// This is `None` if the directories didn't have any files to read. That's different from an empty `Vec`, which means
+/// to read stdin.
+fn input_paths(os: &impl OsFacade, cli: &RunOptions) -> Result<Option<Vec<String>>, Error> {
+    if !cli.recursive {
+        return Ok(Some(cli.markdown_file_paths.clone()));
+    }
+    let paths = walk::expand_paths(os, cli)?;
+    Ok(if paths.is_empty() { None } else { Some(paths) })
+}
+
+/// What [`select_output`] selected.
+struct Selected {
+    nodes: Vec<MdElem>,
+    ctx: MdContext,
+    /// With `--with-positions`, the byte offset that each node starts at or after; see [`with_position_headers`]. This
+    /// is empty if they aren't known.
+    starts: Vec<usize>,
+    /// Whether the selector selected anything.
+    found_any: bool,
+}
+
+impl From<rewrite::Rewritten> for Selected {
+    fn from(rewritten: rewrite::Rewritten) -> Self {
+        Self {
+            nodes: vec![MdElem::Doc(rewritten.roots)],
+            ctx: rewritten.ctx,
+            starts: Vec::new(),
+            found_any: rewritten.found_any,
         }
+    }
+}
+
+/// The selected elements; or with `--delete`, a document of everything else; or if the selector changes tasks or
+/// there's content to add, the whole document with those changes.
+fn select_output(
+    cli: &RunOptions,
+    selectors: Selector,
+    insertion: Option<&(Placement, MdDoc)>,
+    md_doc: MdDoc,
+    source_len: usize,
+) -> Result<Selected, Error> {
+    if let Some((placement, content)) = insertion {
+        let inserted =
+            rewrite::insert_content(&selectors, md_doc, source_len, *placement, content).map_err(Error::Insert)?;
+        return Ok(inserted.into());
+    }
+    if cli.delete {
+        let deleted = rewrite::delete_selected(&selectors, md_doc, source_len).map_err(Error::Delete)?;
+        return Ok(deleted.into());
+    }
+    if selectors.changes_tasks() {
+        let changed = rewrite::change_selected(&selectors, md_doc, source_len).map_err(Error::ChangeTasks)?;
+        return Ok(changed.into());
+    }
+    let starts = match cli.with_positions {
+        true => result_starts(&selectors, md_doc.clone(), source_len)?,
+        false => Vec::new(),
     };
+    let (nodes, ctx) = selectors.find_nodes(md_doc).map_err(Error::SelectionError)?;
+    let found_any = !nodes.is_empty();
+    // A replacement that changes which elements are selected would put the starts out of step with the nodes.
+    let starts = if starts.len() == nodes.len() {
+        starts
+    } else {
+        Vec::new()
+    };
+    Ok(Selected {
+        nodes,
+        ctx,
+        starts,
+        found_any,
+    })
+}
+
+/// Where in the source each of the selector's results starts. This follows each result through the chain's stages, so
+/// that a result that's identical to an earlier element in the document still gets its own position.
+fn result_starts(selectors: &Selector, md_doc: MdDoc, source_len: usize) -> Result<Vec<usize>, Error> {
+    let originals = in_place::locate_originals(selectors.clone().without_replacements(), md_doc, source_len)
+        .map_err(Error::SelectionError)?;
+    Ok(originals.into_iter().map(|(_, within)| within.start).collect())
+}
+
+/// The selectors to run, and the content to add if `--append-to` or one of its siblings is set.
+///
+/// Those options take the place of [`RunOptions::selectors`], so if one of them is set, its selectors are the ones
+/// to run.
+fn selectors_and_insertion(
+    cli: &RunOptions,
+    os: &impl OsFacade,
+    options: &ParseOptions,
+) -> Result<(Selector, Option<(Placement, MdDoc)>), Error> {
+    let Some((placement, selectors_str)) = cli.insertion() else {
+        return Ok((parse_selectors(&cli.selectors)?, None));
+    };
+    let selectors = parse_selectors(selectors_str)?;
+    let Some(path) = &cli.content_file else {
+        return Err(Error::Other(
+            "--append-to, --prepend-to, --insert-before and --insert-after require --content-file\n".to_string(),
+        ));
+    };
+    let contents = os
+        .read_file(path)
+        .map_err(|err| Error::from_io_error(err, Input::FilePath(path.to_string())))?;
+    let content = md_elem::MdDoc::parse(&contents, options).map_err(Error::MarkdownParse)?;
+    Ok((selectors, Some((placement, content))))
+}
 
-    let (pipeline_nodes, ctx) = selectors.find_nodes(md_doc).map_err(Error::SelectionError)?;
+fn parse_selectors(selectors_str: &str) -> Result<Selector, Error> {
+    selectors_str.try_into().map_err(|error| {
+        Error::QueryParse(QueryParseError {
+            query_string: selectors_str.to_string(),
+            error,
+        })
+    })
+}
 
+/// Writes the selected elements in [`RunOptions::output`]'s format. The REPL uses this, too.
+///
+/// `starts` are where each element starts in the source, for `--with-positions`; see [`with_position_headers`].
+pub(crate) fn write_results(
+    cli: &RunOptions,
+    nodes: &[MdElem],
+    starts: &[usize],
+    ctx: &MdContext,
+    out: &mut impl Write,
+) {
     let md_options: MdWriterOptions = cli.into();
+    match cli.output {
+        OutputFormat::Markdown | OutputFormat::Md => {
+            let nodes = with_position_headers(cli, nodes, starts, ctx);
+            MdWriter::with_options(md_options).write(ctx, &nodes, &mut output::IoAdapter(out));
+        }
+        OutputFormat::Json => {
+            let inline_options = md_options.inline_options;
+            serde_json::to_writer(out, &serializable_md(cli, nodes, starts, ctx, inline_options)).unwrap();
+        }
+        OutputFormat::Plain => {
+            let nodes = with_position_headers(cli, nodes, starts, ctx);
+            output::PlainWriter::with_options(output::PlainWriterOptions {
+                include_breaks: cli.should_add_breaks(),
+            })
+            .write(nodes.iter(), out);
+        }
+    }
+}
 
-    let found_any = !pipeline_nodes.is_empty();
+fn serializable_md<'md>(
+    cli: &RunOptions,
+    nodes: &'md [MdElem],
+    starts: &[usize],
+    ctx: &'md MdContext,
+    inline_options: InlineElemOptions,
+) -> SerializableMd<'md> {
+    if cli.with_positions {
+        SerializableMd::with_positions_from(nodes, ctx, inline_options, starts)
+    } else {
+        SerializableMd::new(nodes, ctx, inline_options)
+    }
+}
 
-    if !cli.quiet {
-        let mut stdout = os.stdout();
-        match cli.output {
-            OutputFormat::Markdown | OutputFormat::Md => {
-                MdWriter::with_options(md_options).write(&ctx, &pipeline_nodes, &mut output::IoAdapter(&mut stdout));
-            }
-            OutputFormat::Json => {
-                let inline_options = md_options.inline_options;
-                serde_json::to_writer(&mut stdout, &SerializableMd::new(&pipeline_nodes, &ctx, inline_options))
-                    .unwrap();
-            }
-            OutputFormat::Plain => {
-                output::PlainWriter::with_options(output::PlainWriterOptions {
-                    include_breaks: cli.should_add_breaks(),
-                })
-                .write(&pipeline_nodes, &mut stdout);
+/// With `--with-positions`, puts each result's source span (as in `3:1-5:12`) just before it.
+///
+/// For Markdown output, the span is in an HTML comment so that the output is still the same Markdown. Results that
+/// aren't in the source as-is don't get a span.
+///
+/// Each result's span is the first of its spans that starts at or after its entry in `starts`. Without those (if
+/// `starts` is empty), it's the first one after the previous result's.
+fn with_position_headers<'md>(
+    cli: &RunOptions,
+    nodes: &'md [MdElem],
+    starts: &[usize],
+    ctx: &MdContext,
+) -> Cow<'md, [MdElem]> {
+    if !cli.with_positions {
+        return Cow::Borrowed(nodes);
+    }
+    let mut spans = SpanCursor::new(ctx);
+    let mut results = Vec::with_capacity(nodes.len());
+    for (idx, node) in nodes.iter().enumerate() {
+        if let Some(start) = starts.get(idx) {
+            spans.seek(*start);
+        }
+        let span = spans.enter(node);
+        spans.exit(span);
+        let Some(span) = span else {
+            results.push(node.clone());
+            continue;
+        };
+        let span = format!(
+            "{}:{}-{}:{}",
+            span.start.line, span.start.column, span.end.line, span.end.column
+        );
+        let header = match cli.output {
+            OutputFormat::Plain => MdElem::Paragraph(Paragraph {
+                body: vec![Inline::Text(Text {
+                    variant: TextVariant::Plain,
+                    value: span,
+                })],
+            }),
+            _ => MdElem::BlockHtml(BlockHtml {
+                value: format!("<!-- {span} -->"),
+            }),
+        };
+        results.push(MdElem::Doc(vec![header, node.clone()]));
+    }
+    Cow::Owned(results)
+}
+
+/// Reads each input, and concatenates the ones whose front matter matches the `--where` predicate.
+///
+/// Each input is parsed on its own to find its front matter, since only the first input's front matter would be
+/// recognized once they're all concatenated.
+fn read_matching(
+    os: &impl OsFacade,
+    markdown_file_paths: &[String],
+    predicate: &str,
+    options: &ParseOptions,
+) -> Result<String, Error> {
+    let predicate = WherePredicate::parse(predicate).map_err(Error::WherePredicate)?;
+    let mut contents = String::new();
+    for (_, input_contents) in os.read_each(markdown_file_paths)? {
+        let input_doc = md_elem::MdDoc::parse(&input_contents, options).map_err(Error::MarkdownParse)?;
+        if doc_matches(&predicate, &input_doc)? {
+            contents.push_str(&input_contents);
+            contents.push('\n');
+        }
+    }
+    Ok(contents)
+}
+
+fn doc_matches(predicate: &WherePredicate, doc: &MdDoc) -> Result<bool, Error> {
+    let front_matter = match doc.roots.first() {
+        Some(MdElem::FrontMatter(front_matter)) => Some(front_matter),
+        _ => None,
+    };
+    predicate.matches(front_matter).map_err(Error::SelectionError)
+}
+
+/// One input's results, in per-file mode.
+struct InputResults {
+    input: Input,
+    selected: Selected,
+}
+
+#[derive(Serialize)]
+struct SerializableInputs<'md> {
+    files: Vec<SerializableInput<'md>>,
+}
+
+#[derive(Serialize)]
+struct SerializableInput<'md> {
+    file: &'md str,
+    #[serde(flatten)]
+    md: SerializableMd<'md>,
+}
+
+/// Parses and selects each input on its own, and writes each one's results along with its name.
+fn run_per_file(
+    cli: &RunOptions,
+    os: &mut impl OsFacade,
+    paths: Option<&[String]>,
+    options: &ParseOptions,
+) -> Result<bool, Error> {
+    let predicate = match &cli.where_predicate {
+        Some(predicate) => Some(WherePredicate::parse(predicate).map_err(Error::WherePredicate)?),
+        None => None,
+    };
+    let inputs = match paths {
+        Some(paths) => os.read_each(paths)?,
+        None => Vec::new(),
+    };
+    let mut docs = Vec::with_capacity(inputs.len());
+    for (input, contents) in inputs {
+        let md_doc = md_elem::MdDoc::parse(&contents, options).map_err(Error::MarkdownParse)?;
+        if let Some(predicate) = &predicate {
+            if !doc_matches(predicate, &md_doc)? {
+                continue;
             }
         }
+        docs.push((input, md_doc, contents.len()));
+    }
+
+    let (selectors, insertion) = selectors_and_insertion(cli, os, options)?;
+
+    let mut results = Vec::with_capacity(docs.len());
+    for (input, md_doc, source_len) in docs {
+        write_warnings(os, &selectors, &md_doc);
+        let selected = select_output(cli, selectors.clone(), insertion.as_ref(), md_doc, source_len)?;
+        results.push(InputResults { input, selected });
+    }
+
+    let found_any = results.iter().any(|result| result.selected.found_any);
+
+    if !cli.quiet {
+        let whole_documents = cli.delete || insertion.is_some() || selectors.changes_tasks();
+        write_per_file(cli, whole_documents, &results, &mut os.stdout())
+            .map_err(|err| Error::Other(format!("couldn't write output: {err}\n")))?;
+    }
+
+    Ok(found_any)
+}
+
+/// With `whole_documents`, each result is a whole (changed) document, so every file has output.
+fn write_per_file(
+    cli: &RunOptions,
+    whole_documents: bool,
+    results: &[InputResults],
+    out: &mut impl Write,
+) -> io::Result<()> {
+    if cli.files_with_matches {
+        for result in results.iter().filter(|result| result.selected.found_any) {
+            writeln!(out, "{}", result.input.name())?;
+        }
+        return Ok(());
+    }
+    if cli.count {
+        for result in results {
+            writeln!(out, "{}:{}", result.input.name(), result.selected.nodes.len())?;
+        }
+        return Ok(());
+    }
+    let with_matches = results
+        .iter()
+        .filter(|result| whole_documents || result.selected.found_any);
+    if let OutputFormat::Json = cli.output {
+        let inline_options = MdWriterOptions::from(cli).inline_options;
+        let files = with_matches
+            .map(|result| SerializableInput {
+                file: result.input.name(),
+                md: serializable_md(
+                    cli,
+                    &result.selected.nodes,
+                    &result.selected.starts,
+                    &result.selected.ctx,
+                    inline_options,
+                ),
+            })
+            .collect();
+        serde_json::to_writer(out, &SerializableInputs { files })?;
+        return Ok(());
+    }
+    for (idx, result) in with_matches.enumerate() {
+        if idx > 0 {
+            writeln!(out)?;
+        }
+        writeln!(out, "==> {} <==", result.input.name())?;
+        let selected = &result.selected;
+        write_results(cli, &selected.nodes, &selected.starts, &selected.ctx, out);
     }
+    Ok(())
+}
 
+/// Applies the selector's replacements to each input file, and writes them back to the files.
+///
+/// Only the text that the replacements changed is written; everything else in the file stays as it was. This doesn't
+/// write anything to stdout.
+fn run_in_place(
+    cli: &RunOptions,
+    os: &mut impl OsFacade,
+    paths: Option<&[String]>,
+    options: &ParseOptions,
+) -> Result<bool, Error> {
+    let paths = paths.unwrap_or_default();
+    if paths.is_empty() || paths.iter().any(|path| path == "-") {
+        return Err(Error::Other(
+            "--in-place requires input files, and can't edit stdin\n".to_string(),
+        ));
+    }
+    let predicate = match &cli.where_predicate {
+        Some(predicate) => Some(WherePredicate::parse(predicate).map_err(Error::WherePredicate)?),
+        None => None,
+    };
+    let selectors = parse_selectors(&cli.selectors)?;
+
+    // Edit all the files before writing any of them, so that an error doesn't leave only some of them edited.
+    let mut writes = Vec::new();
+    let mut found_any = false;
+    for (input, contents) in os.read_each(paths)? {
+        if let Some(predicate) = &predicate {
+            let md_doc = md_elem::MdDoc::parse(&contents, options).map_err(Error::MarkdownParse)?;
+            if !doc_matches(predicate, &md_doc)? {
+                continue;
+            }
+        }
+        let edited = in_place::replace_in_source(&selectors, &input, &contents, options)?;
+        edited
+            .warnings
+            .into_iter()
+            .for_each(|warning| os.write_error(Error::Warning(warning)));
+        found_any |= edited.found_any;
+        if let (Input::FilePath(path), Some(new_contents)) = (input, edited.contents) {
+            writes.push((path, contents, new_contents));
+        }
+    }
+    for (path, original, new_contents) in writes {
+        if let Some(suffix) = &cli.backup_suffix {
+            let backup_path = format!("{path}{suffix}");
+            os.write_file(&backup_path, &original)
+                .map_err(|err| Error::FileWriteError(Input::FilePath(backup_path), err))?;
+        }
+        os.write_file(&path, &new_contents)
+            .map_err(|err| Error::FileWriteError(Input::FilePath(path), err))?;
+    }
     Ok(found_any)
 }
 
@@ -251,11 +758,11 @@ pub(crate) fn display_error_with_enhancements(err: &Error, enhanced_errors: bool
         Error::QueryParse(query_err) => {
             let mut result = String::new();
             result.push_str("Syntax error in select specifier:\n");
-            
+
             // Use enhanced error reporting with suggestions
             let enhanced_error = query_err.error.to_string_with_suggestions(&query_err.query_string);
             result.push_str(&enhanced_error);
-            
+
             result
         }
         _ => err.to_string(),
@@ -0,0 +1,174 @@
+use crate::run::{Error, Input, OsFacade, RunOptions};
+
+/// The files to use from `--recursive` directory walks, as given by `--include` and `--exclude`.
+struct Filters {
+    include: Vec<Glob>,
+    exclude: Vec<Glob>,
+}
+
+const DEFAULT_INCLUDES: [&str; 2] = ["*.md", "*.markdown"];
+
+/// Expands the input paths for `--recursive`: each directory turns into the files within it, sorted by path.
+///
+/// Paths that aren't directories are kept as they are, even if they don't match `--include`. If there are no input
+/// paths at all, this walks the current directory.
+pub(crate) fn expand_paths(os: &impl OsFacade, cli: &RunOptions) -> Result<Vec<String>, Error> {
+    let includes: Vec<&str> = if cli.include.is_empty() {
+        DEFAULT_INCLUDES.to_vec()
+    } else {
+        cli.include.iter().map(String::as_str).collect()
+    };
+    let filters = Filters {
+        include: includes.into_iter().map(Glob::new).collect(),
+        exclude: cli.exclude.iter().map(|s| Glob::new(s)).collect(),
+    };
+    let roots = if cli.markdown_file_paths.is_empty() {
+        vec![".".to_string()]
+    } else {
+        cli.markdown_file_paths.clone()
+    };
+    let mut paths = Vec::new();
+    for root in roots {
+        if root == "-" {
+            paths.push(root);
+            continue;
+        }
+        match read_dir(os, &root)? {
+            None => paths.push(root),
+            Some(entries) => walk(os, &filters, &root, "", entries, &mut paths)?,
+        }
+    }
+    Ok(paths)
+}
+
+fn walk(
+    os: &impl OsFacade,
+    filters: &Filters,
+    root: &str,
+    dir: &str,
+    mut entries: Vec<String>,
+    out: &mut Vec<String>,
+) -> Result<(), Error> {
+    entries.sort();
+    for name in entries {
+        let relative = if dir.is_empty() {
+            name.clone()
+        } else {
+            format!("{dir}/{name}")
+        };
+        let full = format!("{}/{relative}", root.trim_end_matches('/'));
+        match read_dir(os, &full)? {
+            Some(sub_entries) => {
+                // "/" lets "vendor/**" exclude the vendor directory itself, and not just the files within it
+                let dir_with_slash = format!("{relative}/");
+                let excluded = filters
+                    .exclude
+                    .iter()
+                    .any(|glob| glob.matches(&relative, &name) || glob.matches(&dir_with_slash, &name));
+                if !excluded {
+                    walk(os, filters, root, &relative, sub_entries, out)?;
+                }
+            }
+            None => {
+                let included = filters.include.iter().any(|glob| glob.matches(&relative, &name));
+                let excluded = filters.exclude.iter().any(|glob| glob.matches(&relative, &name));
+                if included && !excluded {
+                    out.push(full);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn read_dir(os: &impl OsFacade, path: &str) -> Result<Option<Vec<String>>, Error> {
+    os.read_dir(path)
+        .map_err(|err| Error::from_io_error(err, Input::FilePath(path.to_string())))
+}
+
+/// A glob pattern for `--include` and `--exclude`.
+///
+/// `*` matches any characters other than `/`, `**` matches any characters including `/`, and `?` matches any one
+/// character other than `/`. A pattern without a `/` matches against a file or directory's name; otherwise, it matches
+/// against its path, relative to the directory being walked.
+#[derive(Debug)]
+struct Glob {
+    pattern: Vec<char>,
+    by_name: bool,
+}
+
+impl Glob {
+    fn new(pattern: &str) -> Self {
+        Self {
+            pattern: pattern.chars().collect(),
+            by_name: !pattern.contains('/'),
+        }
+    }
+
+    fn matches(&self, relative_path: &str, name: &str) -> bool {
+        let text: Vec<char> = if self.by_name { name } else { relative_path }.chars().collect();
+        glob_matches(&self.pattern, &text)
+    }
+}
+
+fn glob_matches(pattern: &[char], text: &[char]) -> bool {
+    match pattern {
+        [] => text.is_empty(),
+        ['*', '*', '/', rest @ ..] => {
+            // "**/" matches zero or more whole directories
+            glob_matches(rest, text) || (0..text.len()).any(|i| text[i] == '/' && glob_matches(rest, &text[i + 1..]))
+        }
+        ['*', '*', rest @ ..] => (0..=text.len()).any(|i| glob_matches(rest, &text[i..])),
+        ['*', rest @ ..] => (0..=text.len())
+            .take_while(|&i| i == 0 || text[i - 1] != '/')
+            .any(|i| glob_matches(rest, &text[i..])),
+        ['?', rest @ ..] => matches!(text.first(), Some(c) if *c != '/') && glob_matches(rest, &text[1..]),
+        [c, rest @ ..] => text.first() == Some(c) && glob_matches(rest, &text[1..]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn star_stays_within_segment() {
+        assert!(glob("*.md", "README.md"));
+        assert!(!glob("*.md", "README.txt"));
+        assert!(!glob("docs/*.md", "docs/nested/README.md"));
+        assert!(glob("docs/*.md", "docs/README.md"));
+    }
+
+    #[test]
+    fn double_star_crosses_segments() {
+        assert!(glob("node_modules/**", "node_modules/pkg/README.md"));
+        assert!(glob("node_modules/**", "node_modules/"));
+        assert!(!glob("node_modules/**", "src/node_modules/README.md"));
+        assert!(glob("**/node_modules/**", "src/node_modules/README.md"));
+    }
+
+    #[test]
+    fn double_star_slash_matches_no_directories() {
+        assert!(glob("docs/**/*.md", "docs/README.md"));
+        assert!(glob("docs/**/*.md", "docs/a/b/README.md"));
+    }
+
+    #[test]
+    fn question_mark() {
+        assert!(glob("v?.md", "v1.md"));
+        assert!(!glob("v?.md", "v10.md"));
+        assert!(!glob("a?b", "a/b"));
+    }
+
+    #[test]
+    fn name_patterns_ignore_directories() {
+        let glob = Glob::new("*.md");
+        assert!(glob.matches("docs/nested/README.md", "README.md"));
+    }
+
+    fn glob(pattern: &str, path: &str) -> bool {
+        let pattern: Vec<char> = pattern.chars().collect();
+        let path: Vec<char> = path.chars().collect();
+        glob_matches(&pattern, &path)
+    }
+}
@@ -0,0 +1,215 @@
+use crate::md_elem::elem::FrontMatter;
+use crate::select;
+use crate::select::{FrontMatterKey, SelectError};
+use serde_yaml_ng::Value;
+
+/// A `--where` predicate, which checks a key in each input file's front matter.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct WherePredicate {
+    path: Vec<FrontMatterKey>,
+    op: WhereOp,
+    expect: String,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum WhereOp {
+    Eq,
+    NotEq,
+    Contains,
+}
+
+impl WhereOp {
+    const ALL: [(&'static str, Self); 3] = [("==", Self::Eq), ("!=", Self::NotEq), (" contains ", Self::Contains)];
+}
+
+impl WherePredicate {
+    /// Parses a predicate like `draft == false`, `author.name != "Jane Doe"` or `tags contains rust`.
+    pub(crate) fn parse(text: &str) -> Result<Self, String> {
+        let Some((op_start, op_str, op)) = WhereOp::ALL
+            .iter()
+            .filter_map(|(op_str, op)| text.find(op_str).map(|idx| (idx, *op_str, *op)))
+            .min_by_key(|(idx, _, _)| *idx)
+        else {
+            return Err(r#"expected "==", "!=" or "contains""#.to_string());
+        };
+        let path = parse_key_path(text[..op_start].trim())?;
+        let expect = unquote(text[op_start + op_str.len()..].trim());
+        Ok(Self { path, op, expect })
+    }
+
+    /// Whether the front matter matches this predicate. A file without front matter matches as if none of its keys
+    /// were present.
+    pub(crate) fn matches(&self, front_matter: Option<&FrontMatter>) -> Result<bool, SelectError> {
+        let value = match front_matter {
+            Some(front_matter) => select::parse_front_matter(front_matter)?,
+            None => Value::Null,
+        };
+        let found = select::find_value(&value, &self.path);
+        Ok(match self.op {
+            WhereOp::Eq => found.is_some_and(|v| self.scalar_matches(v)),
+            WhereOp::NotEq => !found.is_some_and(|v| self.scalar_matches(v)),
+            WhereOp::Contains => found.is_some_and(|v| self.contains(v)),
+        })
+    }
+
+    fn scalar_matches(&self, value: &Value) -> bool {
+        select::scalar_text(value).is_some_and(|text| text == self.expect)
+    }
+
+    fn contains(&self, value: &Value) -> bool {
+        match value {
+            Value::String(s) => s.contains(&self.expect),
+            Value::Sequence(seq) => seq.iter().any(|elem| self.scalar_matches(elem)),
+            Value::Mapping(mapping) => mapping.contains_key(self.expect.as_str()),
+            Value::Tagged(tagged) => self.contains(&tagged.value),
+            Value::Null | Value::Bool(_) | Value::Number(_) => false,
+        }
+    }
+}
+
+/// Parses a key path like `tags`, `author.name` or `tags[0]`. A leading `.` is allowed, as in the `+++ .tags` selector.
+fn parse_key_path(text: &str) -> Result<Vec<FrontMatterKey>, String> {
+    let invalid = || format!("invalid front matter key path: {text:?}");
+    let mut path = Vec::new();
+    let mut remaining = text.strip_prefix('.').unwrap_or(text);
+    while !remaining.is_empty() {
+        if let Some(after_bracket) = remaining.strip_prefix('[') {
+            let (index, rest) = after_bracket.split_once(']').ok_or_else(invalid)?;
+            path.push(FrontMatterKey::Index(index.parse().map_err(|_| invalid())?));
+            remaining = rest;
+        } else {
+            let key = remaining.strip_prefix('.').unwrap_or(remaining);
+            if !path.is_empty() && key.len() == remaining.len() {
+                return Err(invalid());
+            }
+            let key_end = key
+                .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '-'))
+                .unwrap_or(key.len());
+            if key_end == 0 {
+                return Err(invalid());
+            }
+            path.push(FrontMatterKey::Field(key[..key_end].to_string()));
+            remaining = &key[key_end..];
+        }
+    }
+    if path.is_empty() {
+        return Err("missing front matter key".to_string());
+    }
+    Ok(path)
+}
+
+fn unquote(text: &str) -> String {
+    for quote in ['"', '\''] {
+        if let Some(inner) = text.strip_prefix(quote).and_then(|s| s.strip_suffix(quote)) {
+            return inner.to_string();
+        }
+    }
+    text.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::md_elem::elem::FrontMatterVariant;
+
+    #[test]
+    fn parse_eq() {
+        assert_eq!(
+            WherePredicate::parse("draft == false"),
+            Ok(WherePredicate {
+                path: vec![FrontMatterKey::Field("draft".to_string())],
+                op: WhereOp::Eq,
+                expect: "false".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn parse_not_eq_without_spaces() {
+        assert_eq!(
+            WherePredicate::parse(r#".author.name!="Jane Doe""#),
+            Ok(WherePredicate {
+                path: vec![
+                    FrontMatterKey::Field("author".to_string()),
+                    FrontMatterKey::Field("name".to_string()),
+                ],
+                op: WhereOp::NotEq,
+                expect: "Jane Doe".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn parse_contains_with_index() {
+        assert_eq!(
+            WherePredicate::parse("series[0] contains rust"),
+            Ok(WherePredicate {
+                path: vec![FrontMatterKey::Field("series".to_string()), FrontMatterKey::Index(0)],
+                op: WhereOp::Contains,
+                expect: "rust".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            WherePredicate::parse("draft"),
+            Err(r#"expected "==", "!=" or "contains""#.to_string())
+        );
+        assert_eq!(
+            WherePredicate::parse(" == false"),
+            Err("missing front matter key".to_string())
+        );
+        assert_eq!(
+            WherePredicate::parse("tags[x] == rust"),
+            Err(r#"invalid front matter key path: "tags[x]""#.to_string())
+        );
+        assert_eq!(
+            WherePredicate::parse("my key == value"),
+            Err(r#"invalid front matter key path: "my key""#.to_string())
+        );
+    }
+
+    #[test]
+    fn eq_against_bool_and_string() {
+        let front_matter = yaml("draft: false\ntitle: Hello");
+
+        assert!(matches("draft == false", Some(&front_matter)));
+        assert!(!matches("draft == true", Some(&front_matter)));
+        assert!(matches("title == 'Hello'", Some(&front_matter)));
+        assert!(!matches("title != Hello", Some(&front_matter)));
+    }
+
+    #[test]
+    fn missing_keys() {
+        let front_matter = yaml("title: Hello");
+
+        assert!(!matches("draft == false", Some(&front_matter)));
+        assert!(matches("draft != true", Some(&front_matter)));
+        assert!(!matches("tags contains rust", Some(&front_matter)));
+        assert!(matches("draft != true", None));
+        assert!(!matches("draft == false", None));
+    }
+
+    #[test]
+    fn contains() {
+        let front_matter = yaml("tags: [rust, markdown]\ntitle: Hello, world\nauthor:\n  name: Me");
+
+        assert!(matches("tags contains rust", Some(&front_matter)));
+        assert!(!matches("tags contains rus", Some(&front_matter)));
+        assert!(matches("title contains world", Some(&front_matter)));
+        assert!(matches("author contains name", Some(&front_matter)));
+    }
+
+    fn matches(predicate: &str, front_matter: Option<&FrontMatter>) -> bool {
+        WherePredicate::parse(predicate).unwrap().matches(front_matter).unwrap()
+    }
+
+    fn yaml(body: &str) -> FrontMatter {
+        FrontMatter {
+            variant: FrontMatterVariant::Yaml,
+            body: body.to_string(),
+        }
+    }
+}
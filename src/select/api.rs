@@ -2,20 +2,34 @@ use crate::md_elem::elem::*;
 use crate::md_elem::*;
 use crate::select::sel_chain::ChainSelector;
 use crate::select::sel_code_block::CodeBlockSelector;
+use crate::select::sel_footnote::FootnoteSelector;
+use crate::select::sel_front_matter_key::FrontMatterKeySelector;
+use crate::select::sel_has::HasSelector;
 use crate::select::sel_link_like::ImageSelector;
 use crate::select::sel_link_like::LinkSelector;
 use crate::select::sel_list_item::ListItemSelector;
+use crate::select::sel_not::NotSelector;
+use crate::select::sel_position::PositionSelector;
 use crate::select::sel_section::SectionSelector;
+use crate::select::sel_section::SectionSlugSelector;
 use crate::select::sel_single_matcher::BlockQuoteSelector;
 use crate::select::sel_single_matcher::FrontMatterSelector;
 use crate::select::sel_single_matcher::HtmlSelector;
+use crate::select::sel_single_matcher::InlineCodeSelector;
+use crate::select::sel_single_matcher::InlineMathSelector;
+use crate::select::sel_single_matcher::MathBlockSelector;
 use crate::select::sel_single_matcher::ParagraphSelector;
+use crate::select::sel_single_matcher::SpanSelector;
 use crate::select::sel_table::TableSelector;
+use crate::select::sel_task_action::TaskActionSelector;
+use crate::select::sel_thematic_break::ThematicBreakSelector;
+use crate::select::sel_union::UnionSelector;
 use crate::select::Selector;
 use paste::paste;
 use std::collections::HashSet;
 use std::error::Error;
 use std::fmt::{Display, Formatter};
+use std::iter;
 
 /// Represents the result of a selection operation.
 #[derive(Debug, PartialEq)]
@@ -58,7 +72,11 @@ pub(crate) trait TrySelector<I: Into<MdElem>> {
 }
 
 macro_rules! adapters {
-    { $($name:ident => $md_elem:ident),+ , <inlines> { $($inline:ident),+ , } } => {
+    {
+        $($name:ident => $md_elem:ident),+ ,
+        <inlines> { $($inline:ident => $inline_elem:ident),+ , }
+        <nodes> { $($node:ident),+ , }
+    } => {
 
         #[derive(Debug)]
         pub(crate) enum SelectorAdapter {
@@ -68,6 +86,9 @@ macro_rules! adapters {
             $(
             $inline( paste!{[<$inline Selector>]} ),
             )+
+            $(
+            $node( paste!{[<$node Selector>]} ),
+            )+
         }
 
         impl From<Selector> for SelectorAdapter {
@@ -79,18 +100,24 @@ macro_rules! adapters {
                     $(
                     Selector::$inline(matcher) => Self::$inline(matcher.into()),
                     )+
+                    $(
+                    Selector::$node(matcher) => Self::$node(matcher.into()),
+                    )+
                 }
             }
         }
 
         impl SelectorAdapter {
-            fn try_select_node(&self, ctx: &MdContext, node: MdElem) -> Result<Select> {
+            pub(crate) fn try_select_node(&self, ctx: &MdContext, node: MdElem) -> Result<Select> {
                 match (&self, node) {
                     $(
                     (Self::$name(adapter), MdElem::$md_elem(elem)) => adapter.try_select(ctx, elem),
                     )+
                     $(
-                    (Self::$inline(adapter), MdElem::Inline(Inline::$inline(elem))) => adapter.try_select(ctx, elem),
+                    (Self::$inline(adapter), MdElem::Inline(Inline::$inline_elem(elem))) => adapter.try_select(ctx, elem),
+                    )+
+                    $(
+                    (Self::$node(adapter), node) => adapter.try_select(ctx, node),
                     )+
                     (_, unmatched) => Ok(Select::Miss(unmatched)),
                 }
@@ -102,16 +129,31 @@ macro_rules! adapters {
 adapters! {
     Chain => Doc,
     Section => Section,
+    SectionSlug => Section,
     ListItem => List,
     BlockQuote => BlockQuote,
     CodeBlock => CodeBlock,
+    MathBlock => CodeBlock,
     FrontMatter => FrontMatter,
+    FrontMatterKey => FrontMatter,
     Html => BlockHtml,
     Paragraph => Paragraph,
     Table => Table,
+    TaskAction => List,
     <inlines> {
-        Link,
-        Image,
+        Link => Link,
+        Image => Image,
+        Footnote => Footnote,
+        InlineCode => Text,
+        InlineMath => Text,
+        Span => Span,
+    }
+    <nodes> {
+        ThematicBreak,
+        Union,
+        Not,
+        Has,
+        Position,
     }
 }
 
@@ -125,7 +167,34 @@ impl SelectorAdapter {
         Ok(result)
     }
 
+    /// Whether any of this node's descendants (but not the node itself) match this selector.
+    ///
+    /// This only clones each of the node's children when it gets to it, and stops at the first match.
+    pub(crate) fn matches_within(&self, ctx: &MdContext, node: &MdElem) -> Result<bool> {
+        let mut result = Vec::new();
+        let mut search_context = SearchContext::new(ctx);
+        for child in Self::find_children_of_ref(ctx, node) {
+            self.build_output(&mut result, &mut search_context, child)?;
+            if !result.is_empty() {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
     fn build_output(&self, out: &mut Vec<MdElem>, ctx: &mut SearchContext, node: MdElem) -> Result<()> {
+        let footnote = match &node {
+            MdElem::Inline(Inline::Footnote(footnote)) => Some(footnote),
+            // a definition that an earlier stage selected counts the same as a reference to it
+            MdElem::FootnoteDefinition(definition) => Some(&definition.id),
+            _ => None,
+        };
+        if let Some(footnote) = footnote {
+            // guard against cycles, and against selecting the same footnote more than once
+            if !ctx.seen_footnotes.insert(footnote.clone()) {
+                return Ok(());
+            }
+        }
         match self.try_select_node(ctx.md_context, node)? {
             Select::Hit(mut found) => out.append(&mut found),
             Select::Miss(not_found) => {
@@ -144,60 +213,39 @@ impl SelectorAdapter {
     /// only the body nodes are relevant for select recursion. `MdqNode` shouldn't need to know about that oddity; it
     /// belongs here.
     fn find_children(ctx: &mut SearchContext, node: MdElem) -> Vec<MdElem> {
+        // build_output has already checked any footnote against ctx.seen_footnotes
+        Self::find_children_of_ref(ctx.md_context, &node).collect()
+    }
+
+    /// Like [Self::find_children], but for a borrowed node: each child is cloned as the iterator gets to it.
+    fn find_children_of_ref<'a>(ctx: &'a MdContext, node: &'a MdElem) -> Box<dyn Iterator<Item = MdElem> + 'a> {
         match node {
-            MdElem::Doc(body) => {
-                let mut wrapped = Vec::with_capacity(body.len());
-                for elem in body {
-                    wrapped.push(elem);
-                }
-                wrapped
-            }
-            MdElem::Section(s) => vec![MdElem::Doc(s.body)],
-            MdElem::Paragraph(p) => p.body.into_iter().map(MdElem::Inline).collect(),
-            MdElem::BlockQuote(b) => vec![MdElem::Doc(b.body)],
-            MdElem::List(list) => {
-                let mut result = Vec::with_capacity(list.items.len());
-                for mut item in list.items {
-                    result.append(&mut item.item);
-                }
-                result
-            }
-            MdElem::Table(table) => {
-                let rows = table.rows();
-                let first_row_cols = rows.first().map(Vec::len).unwrap_or(0);
-                let count_estimate = rows.len() * first_row_cols;
-                let mut result = Vec::with_capacity(count_estimate);
-                for row in table.rows {
-                    for col in row {
-                        for cell in col {
-                            result.push(MdElem::Inline(cell));
-                        }
-                    }
-                }
-                result
-            }
+            MdElem::Doc(body) => Box::new(body.iter().cloned()),
+            MdElem::Section(s) => Box::new(iter::once_with(|| MdElem::Doc(s.body.clone()))),
+            MdElem::Paragraph(p) => Box::new(p.body.iter().cloned().map(MdElem::Inline)),
+            MdElem::BlockQuote(b) => Box::new(iter::once_with(|| MdElem::Doc(b.body.clone()))),
+            MdElem::FootnoteDefinition(def) => Box::new(iter::once_with(|| MdElem::Doc(def.body.clone()))),
+            MdElem::List(list) => Box::new(list.items.iter().flat_map(|item| item.item.iter().cloned())),
+            MdElem::Table(table) => Box::new(table.rows.iter().flatten().flatten().cloned().map(MdElem::Inline)),
             MdElem::Inline(inline) => match inline {
-                Inline::Span(Span { children, .. }) => children.into_iter().map(MdElem::Inline).collect(),
+                Inline::Span(Span { children, .. }) => Box::new(children.iter().cloned().map(MdElem::Inline)),
                 Inline::Footnote(footnote) => {
-                    // guard against cycles
-                    if ctx.seen_footnotes.insert(footnote.clone()) {
-                        vec![MdElem::Doc(Vec::clone(ctx.md_context.get_footnote(&footnote)))]
-                    } else {
-                        Vec::new()
-                    }
+                    Box::new(iter::once_with(|| MdElem::Doc(Vec::clone(ctx.get_footnote(footnote)))))
                 }
                 Inline::Text(Text {
                     variant: TextVariant::InlineHtml,
                     value,
-                }) => {
-                    vec![MdElem::BlockHtml(value.into())]
-                }
+                }) => Box::new(iter::once_with(|| MdElem::BlockHtml(value.clone().into()))),
                 Inline::Link(Link::Standard(standard_link)) => {
-                    standard_link.display.into_iter().map(MdElem::Inline).collect()
+                    Box::new(standard_link.display.iter().cloned().map(MdElem::Inline))
                 }
-                Inline::Text(_) | Inline::Image(_) | Inline::Link(Link::Autolink(_)) => Vec::new(),
+                Inline::Text(_) | Inline::Image(_) | Inline::Link(Link::Autolink(_)) => Box::new(iter::empty()),
             },
-            MdElem::ThematicBreak | MdElem::CodeBlock(_) | MdElem::FrontMatter(_) | MdElem::BlockHtml(_) => Vec::new(),
+            MdElem::ThematicBreak
+            | MdElem::CodeBlock(_)
+            | MdElem::FrontMatter(_)
+            | MdElem::FrontMatterValue(_)
+            | MdElem::BlockHtml(_) => Box::new(iter::empty()),
         }
     }
 }
@@ -10,15 +10,24 @@ mod match_selector;
 mod matcher;
 mod sel_chain;
 mod sel_code_block;
+mod sel_footnote;
+mod sel_front_matter_key;
+mod sel_has;
 mod sel_link_like;
 mod sel_list_item;
+mod sel_not;
+mod sel_position;
 mod sel_section;
 mod sel_single_matcher;
 mod sel_table;
+mod sel_task_action;
+mod sel_thematic_break;
+mod sel_union;
 mod selector;
 mod string_matcher;
 
 pub(crate) use api::*;
+pub(crate) use sel_front_matter_key::{find_value, parse_front_matter, scalar_text};
 
 pub use crate::query::ParseError;
 pub use api::{Result, SelectError};
@@ -23,7 +23,10 @@ impl TrySelector<Vec<MdElem>> for ChainSelector {
             return Ok(Select::Hit(vec![MdElem::Doc(items)]));
         }
         for adapter in &self.chain {
-            items = adapter.find_nodes(ctx, items)?;
+            items = match adapter {
+                SelectorAdapter::Position(position) => position.select_all(items),
+                _ => adapter.find_nodes(ctx, items)?,
+            };
         }
         Ok(Select::Hit(items))
     }
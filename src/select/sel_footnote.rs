@@ -0,0 +1,139 @@
+use crate::md_elem::elem::*;
+use crate::md_elem::{MdContext, MdElem};
+use crate::select::string_matcher::StringMatcher;
+use crate::select::{FootnoteMatcher, Select, TrySelector};
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct FootnoteSelector {
+    label_matcher: StringMatcher,
+    text_matcher: StringMatcher,
+}
+
+impl From<FootnoteMatcher> for FootnoteSelector {
+    fn from(value: FootnoteMatcher) -> Self {
+        Self {
+            label_matcher: value.label.into(),
+            text_matcher: value.text.into(),
+        }
+    }
+}
+
+impl TrySelector<FootnoteId> for FootnoteSelector {
+    fn try_select(&self, ctx: &MdContext, item: FootnoteId) -> crate::select::Result<Select> {
+        let label_sel = self
+            .label_matcher
+            .match_replace_string(item.id.clone())
+            .map_err(|e| e.to_select_error("footnote"))?;
+        if !label_sel.matched_any {
+            return Ok(Select::Miss(item.into()));
+        }
+        let text_sel = self
+            .text_matcher
+            .match_replace_any(Vec::clone(ctx.get_footnote(&item)))
+            .map_err(|e| e.to_select_error("footnote"))?;
+
+        // A hit is the footnote's definition, not its reference; a miss is the reference, so that the search can
+        // continue into the definition via the usual footnote recursion. The writers give the definition its label.
+        Ok(if text_sel.matched_any {
+            Select::Hit(vec![MdElem::FootnoteDefinition(FootnoteDefinition {
+                id: item,
+                body: text_sel.item,
+            })])
+        } else {
+            Select::Miss(item.into())
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::md_elem::*;
+    use crate::select::{MatchReplace, Select, TrySelector};
+
+    #[test]
+    fn both_match() {
+        let result = FootnoteSelector::from(FootnoteMatcher {
+            label: MatchReplace::match_any(),
+            text: MatchReplace::match_any(),
+        })
+        .try_select(&new_ctx(), "a".into())
+        .unwrap();
+
+        assert_eq!(result, definition("a", md_elems!("the footnote text")));
+    }
+
+    #[test]
+    fn label_match_misses() {
+        let result = FootnoteSelector::from(FootnoteMatcher {
+            label: MatchReplace::build(|b| b.match_regex("b")),
+            text: MatchReplace::match_any(),
+        })
+        .try_select(&new_ctx(), "a".into())
+        .unwrap();
+
+        assert_eq!(result, Select::Miss(MdElem::Inline(Inline::Footnote("a".into()))));
+    }
+
+    #[test]
+    fn text_match_misses() {
+        let result = FootnoteSelector::from(FootnoteMatcher {
+            label: MatchReplace::match_any(),
+            text: MatchReplace::build(|b| b.match_regex("other")),
+        })
+        .try_select(&new_ctx(), "a".into())
+        .unwrap();
+
+        assert_eq!(result, Select::Miss(MdElem::Inline(Inline::Footnote("a".into()))));
+    }
+
+    #[test]
+    fn text_replacement() {
+        let result = FootnoteSelector::from(FootnoteMatcher {
+            label: MatchReplace::match_any(),
+            text: MatchReplace::build(|b| b.match_regex("footnote").replacement("citation")),
+        })
+        .try_select(&new_ctx(), "a".into())
+        .unwrap();
+
+        assert_eq!(result, definition("a", md_elems!("the citation text")));
+    }
+
+    #[test]
+    fn two_footnotes_have_their_labels() {
+        let selector = FootnoteSelector::from(FootnoteMatcher {
+            label: MatchReplace::match_any(),
+            text: MatchReplace::match_any(),
+        });
+        let ctx = new_ctx().with("b", md_elems!("another footnote"));
+
+        let a = selector.try_select(&ctx, "a".into()).unwrap();
+        let b = selector.try_select(&ctx, "b".into()).unwrap();
+
+        assert_eq!(a, definition("a", md_elems!("the footnote text")));
+        assert_eq!(b, definition("b", md_elems!("another footnote")));
+    }
+
+    #[test]
+    fn text_matches_the_body_alone() {
+        let result = FootnoteSelector::from(FootnoteMatcher {
+            label: MatchReplace::match_any(),
+            text: MatchReplace::build(|b| b.match_regex("^the")),
+        })
+        .try_select(&new_ctx(), "a".into())
+        .unwrap();
+
+        assert_eq!(result, definition("a", md_elems!("the footnote text")));
+    }
+
+    fn definition(id: &str, body: Vec<MdElem>) -> Select {
+        Select::Hit(vec![MdElem::FootnoteDefinition(FootnoteDefinition {
+            id: id.into(),
+            body,
+        })])
+    }
+
+    fn new_ctx() -> MdContext {
+        MdContext::empty().with("a", md_elems!("the footnote text"))
+    }
+}
@@ -0,0 +1,310 @@
+use crate::md_elem::elem::*;
+use crate::md_elem::{MdContext, MdElem};
+use crate::select::{FrontMatterKey, FrontMatterKeyMatcher, Result, Select, SelectError, TrySelector};
+use serde_yaml_ng::Value;
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct FrontMatterKeySelector {
+    variant: Option<FrontMatterVariant>,
+    path: Vec<FrontMatterKey>,
+}
+
+impl From<FrontMatterKeyMatcher> for FrontMatterKeySelector {
+    fn from(value: FrontMatterKeyMatcher) -> Self {
+        Self {
+            variant: value.variant,
+            path: value.path,
+        }
+    }
+}
+
+impl TrySelector<FrontMatter> for FrontMatterKeySelector {
+    fn try_select(&self, _: &MdContext, item: FrontMatter) -> Result<Select> {
+        if self.variant.is_some_and(|selected| selected != item.variant) {
+            return Ok(Select::Miss(item.into()));
+        }
+        // Front matter we can't parse doesn't have any keys to select, but it's not worth failing the whole run over.
+        // Selector::warnings reports it instead.
+        let Ok(value) = parse_front_matter(&item) else {
+            return Ok(Select::Miss(item.into()));
+        };
+        match find_value(&value, &self.path) {
+            Some(found) => Ok(Select::Hit(vec![value_to_elem(item.variant, found)?])),
+            None => Ok(Select::Miss(item.into())),
+        }
+    }
+}
+
+/// Parses the front matter's body into a structured value.
+///
+/// All variants parse into the same (YAML) value, which keeps the keys in their original order. TOML dates and times
+/// are represented as their strings.
+pub(crate) fn parse_front_matter(front_matter: &FrontMatter) -> Result<Value> {
+    let body = &front_matter.body;
+    let parsed = match front_matter.variant {
+        FrontMatterVariant::Toml => toml::from_str::<toml::Table>(body)
+            .map(|table| toml_to_value(toml::Value::Table(table)))
+            .map_err(|e| e.message().to_string()),
+        FrontMatterVariant::Yaml => serde_yaml_ng::from_str::<Value>(body).map_err(|e| e.to_string()),
+        FrontMatterVariant::Json => serde_json::from_str::<Value>(body).map_err(|e| e.to_string()),
+    };
+    parsed.map_err(|e| {
+        SelectError::new(format!(
+            "couldn't parse {} front matter: {e}",
+            front_matter.variant.name()
+        ))
+    })
+}
+
+/// Follows the path into the value, or returns `None` if any step of it isn't there.
+pub(crate) fn find_value<'a>(value: &'a Value, path: &[FrontMatterKey]) -> Option<&'a Value> {
+    let mut found = value;
+    for key in path {
+        found = match key {
+            FrontMatterKey::Field(name) => found.get(name)?,
+            FrontMatterKey::Index(idx) => found.get(idx)?,
+        };
+    }
+    Some(found)
+}
+
+/// The text of a scalar value, or `None` if the value is a sequence or mapping.
+pub(crate) fn scalar_text(value: &Value) -> Option<String> {
+    scalar(value).map(|scalar| scalar.text().to_string())
+}
+
+/// The scalar value, or `None` if the value is a sequence or mapping.
+fn scalar(value: &Value) -> Option<FrontMatterValue> {
+    match value {
+        Value::Null => Some(FrontMatterValue::Null),
+        Value::Bool(b) => Some(FrontMatterValue::Bool(*b)),
+        Value::Number(n) => Some(FrontMatterValue::Number(n.to_string())),
+        Value::String(s) => Some(FrontMatterValue::String(s.clone())),
+        Value::Tagged(tagged) => scalar(&tagged.value),
+        Value::Sequence(_) | Value::Mapping(_) => None,
+    }
+}
+
+fn toml_to_value(value: toml::Value) -> Value {
+    match value {
+        toml::Value::String(s) => Value::String(s),
+        toml::Value::Integer(i) => Value::from(i),
+        toml::Value::Float(f) => Value::from(f),
+        toml::Value::Boolean(b) => Value::Bool(b),
+        toml::Value::Datetime(dt) => Value::String(dt.to_string()),
+        toml::Value::Array(arr) => Value::Sequence(arr.into_iter().map(toml_to_value).collect()),
+        toml::Value::Table(table) => Value::Mapping(
+            table
+                .into_iter()
+                .map(|(k, v)| (Value::String(k), toml_to_value(v)))
+                .collect(),
+        ),
+    }
+}
+
+/// Turns a value from the front matter into an element: scalars become [`FrontMatterValue`]s, sequences become lists,
+/// and mappings become front matter of the original variant.
+fn value_to_elem(variant: FrontMatterVariant, value: &Value) -> Result<MdElem> {
+    match value {
+        Value::Tagged(tagged) => value_to_elem(variant, &tagged.value),
+        Value::Sequence(seq) => {
+            let mut items = Vec::with_capacity(seq.len());
+            for elem in seq {
+                items.push(ListItem {
+                    checked: None,
+                    item: vec![value_to_elem(variant, elem)?],
+                });
+            }
+            Ok(MdElem::List(List {
+                starting_index: None,
+                items,
+            }))
+        }
+        Value::Mapping(_) => {
+            let body = match variant {
+                FrontMatterVariant::Toml => toml::to_string(value).map_err(|e| e.to_string()),
+                FrontMatterVariant::Yaml => serde_yaml_ng::to_string(value).map_err(|e| e.to_string()),
+                FrontMatterVariant::Json => serde_json::to_string_pretty(value).map_err(|e| e.to_string()),
+            }
+            .map_err(|e| SelectError::new(format!("couldn't write {} front matter: {e}", variant.name())))?;
+            Ok(MdElem::FrontMatter(FrontMatter {
+                variant,
+                body: body.trim_end().to_string(),
+            }))
+        }
+        Value::Null | Value::Bool(_) | Value::Number(_) | Value::String(_) => Ok(MdElem::FrontMatterValue(
+            scalar(value).unwrap_or(FrontMatterValue::Null),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use indoc::indoc;
+
+    #[test]
+    fn yaml_top_level_key() {
+        let result = select(
+            None,
+            vec![FrontMatterKey::Field("title".to_string())],
+            yaml("title: Hello\nauthor: Me"),
+        );
+
+        assert_eq!(result, Select::Hit(vec![string("Hello")]));
+    }
+
+    #[test]
+    fn yaml_array_index() {
+        let result = select(
+            Some(FrontMatterVariant::Yaml),
+            vec![FrontMatterKey::Field("tags".to_string()), FrontMatterKey::Index(1)],
+            yaml("tags: [alpha, bravo]"),
+        );
+
+        assert_eq!(result, Select::Hit(vec![string("bravo")]));
+    }
+
+    #[test]
+    fn yaml_array_as_list() {
+        let result = select(
+            None,
+            vec![FrontMatterKey::Field("tags".to_string())],
+            yaml("tags: [alpha, 2]"),
+        );
+
+        assert_eq!(
+            result,
+            Select::Hit(vec![MdElem::List(List {
+                starting_index: None,
+                items: vec![
+                    ListItem {
+                        checked: None,
+                        item: vec![string("alpha")],
+                    },
+                    ListItem {
+                        checked: None,
+                        item: vec![MdElem::FrontMatterValue(FrontMatterValue::Number("2".to_string()))],
+                    },
+                ],
+            })])
+        );
+    }
+
+    #[test]
+    fn yaml_object_as_front_matter() {
+        let result = select(
+            None,
+            vec![FrontMatterKey::Field("author".to_string())],
+            yaml(indoc! {r#"
+                author:
+                  name: Me
+                  email: me@example.com"#}),
+        );
+
+        assert_eq!(
+            result,
+            Select::Hit(vec![MdElem::FrontMatter(FrontMatter {
+                variant: FrontMatterVariant::Yaml,
+                body: "name: Me\nemail: me@example.com".to_string(),
+            })])
+        );
+    }
+
+    #[test]
+    fn toml_datetime_as_string() {
+        let result = select(
+            None,
+            vec![FrontMatterKey::Field("date".to_string())],
+            FrontMatter {
+                variant: FrontMatterVariant::Toml,
+                body: "date = 2024-01-15T10:00:00Z".to_string(),
+            },
+        );
+
+        assert_eq!(result, Select::Hit(vec![string("2024-01-15T10:00:00Z")]));
+    }
+
+    #[test]
+    fn missing_key() {
+        let front_matter = yaml("title: Hello");
+        let result = select(
+            None,
+            vec![FrontMatterKey::Field("tags".to_string()), FrontMatterKey::Index(0)],
+            front_matter.clone(),
+        );
+
+        assert_eq!(result, Select::Miss(MdElem::FrontMatter(front_matter)));
+    }
+
+    #[test]
+    fn wrong_variant() {
+        let front_matter = yaml("title: Hello");
+        let result = select(
+            Some(FrontMatterVariant::Toml),
+            vec![FrontMatterKey::Field("title".to_string())],
+            front_matter.clone(),
+        );
+
+        assert_eq!(result, Select::Miss(MdElem::FrontMatter(front_matter)));
+    }
+
+    #[test]
+    fn invalid_front_matter() {
+        let front_matter = FrontMatter {
+            variant: FrontMatterVariant::Toml,
+            body: "title: Hello".to_string(),
+        };
+        let result = select(
+            None,
+            vec![FrontMatterKey::Field("title".to_string())],
+            front_matter.clone(),
+        );
+
+        assert_eq!(result, Select::Miss(MdElem::FrontMatter(front_matter)));
+    }
+
+    #[test]
+    fn typed_scalars() {
+        let front_matter = yaml("draft: false\nversion: 3\nnothing: ~");
+        let key = |name: &str| {
+            select(
+                None,
+                vec![FrontMatterKey::Field(name.to_string())],
+                front_matter.clone(),
+            )
+        };
+
+        assert_eq!(
+            key("draft"),
+            Select::Hit(vec![MdElem::FrontMatterValue(FrontMatterValue::Bool(false))])
+        );
+        assert_eq!(
+            key("version"),
+            Select::Hit(vec![MdElem::FrontMatterValue(FrontMatterValue::Number(
+                "3".to_string()
+            ))])
+        );
+        assert_eq!(
+            key("nothing"),
+            Select::Hit(vec![MdElem::FrontMatterValue(FrontMatterValue::Null)])
+        );
+    }
+
+    fn select(variant: Option<FrontMatterVariant>, path: Vec<FrontMatterKey>, item: FrontMatter) -> Select {
+        FrontMatterKeySelector::from(FrontMatterKeyMatcher { variant, path })
+            .try_select(&MdContext::empty(), item)
+            .unwrap()
+    }
+
+    fn yaml(body: &str) -> FrontMatter {
+        FrontMatter {
+            variant: FrontMatterVariant::Yaml,
+            body: body.to_string(),
+        }
+    }
+
+    fn string(text: &str) -> MdElem {
+        MdElem::FrontMatterValue(FrontMatterValue::String(text.to_string()))
+    }
+}
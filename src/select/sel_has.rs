@@ -0,0 +1,132 @@
+use crate::md_elem::{MdContext, MdElem};
+use crate::select::{Result, Select, Selector, SelectorAdapter, TrySelector};
+
+#[derive(Debug)]
+pub(crate) struct HasSelector {
+    inner: Box<SelectorAdapter>,
+}
+
+impl From<Box<Selector>> for HasSelector {
+    fn from(inner: Box<Selector>) -> Self {
+        Self {
+            inner: Box::new((*inner).into()),
+        }
+    }
+}
+
+impl TrySelector<MdElem> for HasSelector {
+    fn try_select(&self, ctx: &MdContext, item: MdElem) -> Result<Select> {
+        // If this element doesn't contain a match, none of its children can, either; so rather than a Miss (which would
+        // have the search recurse, and look through each child's descendants again), this is an empty Hit. Selectors
+        // like not(...) treat that as a miss.
+        Ok(if self.inner.matches_within(ctx, &item)? {
+            Select::Hit(vec![item])
+        } else {
+            Select::Hit(Vec::new())
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::md_elem::elem::*;
+    use crate::md_elem::*;
+    use crate::select::{MatchReplace, ParagraphMatcher, SectionMatcher};
+
+    #[test]
+    fn child_matches() {
+        let section = section("title", vec![paragraph("alpha")]);
+
+        let result = new_has_paragraph("alpha")
+            .try_select(&MdContext::empty(), section.clone())
+            .unwrap();
+
+        assert_eq!(result, Select::Hit(vec![section]));
+    }
+
+    #[test]
+    fn deeper_descendant_matches() {
+        let section = section(
+            "title",
+            vec![MdElem::BlockQuote(BlockQuote {
+                body: vec![paragraph("alpha")],
+            })],
+        );
+
+        let result = new_has_paragraph("alpha")
+            .try_select(&MdContext::empty(), section.clone())
+            .unwrap();
+
+        assert_eq!(result, Select::Hit(vec![section]));
+    }
+
+    #[test]
+    fn no_descendants_match() {
+        let section = section("title", vec![paragraph("bravo")]);
+
+        let result = new_has_paragraph("alpha")
+            .try_select(&MdContext::empty(), section.clone())
+            .unwrap();
+
+        assert_eq!(result, Select::Hit(vec![]));
+    }
+
+    #[test]
+    fn only_nested_section_matches() {
+        let inner = section("inner", vec![paragraph("alpha")]);
+        let outer = section("outer", vec![paragraph("bravo"), inner.clone()]);
+        let has = new_has_paragraph("alpha");
+
+        let outer_result = has.try_select(&MdContext::empty(), outer.clone()).unwrap();
+        let inner_result = has.try_select(&MdContext::empty(), inner.clone()).unwrap();
+        let sibling_result = has
+            .try_select(&MdContext::empty(), section("sibling", vec![paragraph("bravo")]))
+            .unwrap();
+
+        assert_eq!(outer_result, Select::Hit(vec![outer]));
+        assert_eq!(inner_result, Select::Hit(vec![inner]));
+        assert_eq!(sibling_result, Select::Hit(vec![]));
+    }
+
+    #[test]
+    fn elem_itself_does_not_count() {
+        let has = HasSelector::from(Box::new(Selector::Section(SectionMatcher {
+            title: MatchReplace::build(|b| b.match_regex("title")),
+            depth: None,
+            subsections: Vec::new(),
+        })));
+
+        let section = section("title", vec![paragraph("alpha")]);
+
+        let result = has.try_select(&MdContext::empty(), section).unwrap();
+
+        assert_eq!(result, Select::Hit(vec![]));
+    }
+
+    fn new_has_paragraph(text: &str) -> HasSelector {
+        HasSelector::from(Box::new(Selector::Paragraph(ParagraphMatcher {
+            text: MatchReplace::build(|b| b.match_regex(text)),
+        })))
+    }
+
+    fn section(title: &str, body: Vec<MdElem>) -> MdElem {
+        MdElem::Section(Section {
+            depth: 1,
+            title: vec![Inline::Text(Text {
+                variant: TextVariant::Plain,
+                value: title.to_string(),
+            })],
+            body,
+        })
+    }
+
+    fn paragraph(text: &str) -> MdElem {
+        MdElem::Paragraph(Paragraph {
+            body: vec![Inline::Text(Text {
+                variant: TextVariant::Plain,
+                value: text.to_string(),
+            })],
+        })
+    }
+}
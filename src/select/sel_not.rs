@@ -0,0 +1,126 @@
+use crate::md_elem::{MdContext, MdElem};
+use crate::select::{Result, Select, Selector, SelectorAdapter, TrySelector};
+
+#[derive(Debug)]
+pub(crate) struct NotSelector {
+    inner: Box<SelectorAdapter>,
+}
+
+impl From<Box<Selector>> for NotSelector {
+    fn from(inner: Box<Selector>) -> Self {
+        Self {
+            inner: Box::new((*inner).into()),
+        }
+    }
+}
+
+impl TrySelector<MdElem> for NotSelector {
+    fn try_select(&self, ctx: &MdContext, item: MdElem) -> Result<Select> {
+        // Either way, this is a Hit: we never want the search to recurse into the element's children.
+        // An empty Hit from the inner selector means it looked at the element but selected nothing (for example, an
+        // inner not(...) that dropped it), so that counts as a miss; we need a copy of the element to keep in that case.
+        Ok(match self.inner.try_select_node(ctx, item.clone())? {
+            Select::Hit(found) if !found.is_empty() => Select::Hit(Vec::new()),
+            _ => Select::Hit(vec![item]),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::md_elem::elem::*;
+    use crate::md_elem::*;
+    use crate::select::{MatchReplace, ParagraphMatcher, SectionMatcher};
+
+    #[test]
+    fn matching_elem_is_dropped() {
+        let result = new_not_paragraph("alpha")
+            .try_select(&MdContext::empty(), paragraph("alpha"))
+            .unwrap();
+
+        assert_eq!(result, Select::Hit(vec![]));
+    }
+
+    #[test]
+    fn non_matching_elem_is_kept() {
+        let result = new_not_paragraph("alpha")
+            .try_select(&MdContext::empty(), paragraph("bravo"))
+            .unwrap();
+
+        assert_eq!(result, Select::Hit(vec![paragraph("bravo")]));
+    }
+
+    #[test]
+    fn double_negation() {
+        let not_not = NotSelector::from(Box::new(Selector::Not(Box::new(Selector::Paragraph(
+            ParagraphMatcher {
+                text: MatchReplace::build(|b| b.match_regex("alpha")),
+            },
+        )))));
+
+        let alpha = not_not.try_select(&MdContext::empty(), paragraph("alpha")).unwrap();
+        let bravo = not_not.try_select(&MdContext::empty(), paragraph("bravo")).unwrap();
+
+        assert_eq!(alpha, Select::Hit(vec![paragraph("alpha")]));
+        assert_eq!(bravo, Select::Hit(vec![]));
+    }
+
+    #[test]
+    fn children_are_not_searched() {
+        let section = MdElem::Section(Section {
+            depth: 1,
+            title: vec![mdq_inline!("title")],
+            body: vec![paragraph("alpha")],
+        });
+
+        let result = new_not_paragraph("alpha")
+            .try_select(&MdContext::empty(), section.clone())
+            .unwrap();
+
+        // the section isn't a paragraph, so it's kept as-is (including the "alpha" paragraph within it)
+        assert_eq!(result, Select::Hit(vec![section]));
+    }
+
+    #[test]
+    fn filters_stream() {
+        let not = NotSelector::from(Box::new(Selector::Section(SectionMatcher {
+            title: MatchReplace::build(|b| b.match_regex("deprecated")),
+            depth: None,
+            subsections: Vec::new(),
+        })));
+        let sections = vec![section("current"), section("deprecated"), section("future")];
+
+        let result = SelectorAdapter::Not(not)
+            .find_nodes(&MdContext::empty(), sections)
+            .unwrap();
+
+        assert_eq!(result, vec![section("current"), section("future")]);
+    }
+
+    fn new_not_paragraph(text: &str) -> NotSelector {
+        NotSelector::from(Box::new(Selector::Paragraph(ParagraphMatcher {
+            text: MatchReplace::build(|b| b.match_regex(text)),
+        })))
+    }
+
+    fn section(title: &str) -> MdElem {
+        MdElem::Section(Section {
+            depth: 1,
+            title: vec![Inline::Text(Text {
+                variant: TextVariant::Plain,
+                value: title.to_string(),
+            })],
+            body: vec![paragraph("body")],
+        })
+    }
+
+    fn paragraph(text: &str) -> MdElem {
+        MdElem::Paragraph(Paragraph {
+            body: vec![Inline::Text(Text {
+                variant: TextVariant::Plain,
+                value: text.to_string(),
+            })],
+        })
+    }
+}
@@ -0,0 +1,87 @@
+use crate::md_elem::{MdContext, MdElem};
+use crate::select::{Position, Result, Select, TrySelector};
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct PositionSelector {
+    position: Position,
+}
+
+impl From<Position> for PositionSelector {
+    fn from(position: Position) -> Self {
+        Self { position }
+    }
+}
+
+impl PositionSelector {
+    /// Keeps only the items at this selector's position. [`ChainSelector`](crate::select::sel_chain::ChainSelector)
+    /// uses this to apply the position to a stage's results as a whole.
+    pub(crate) fn select_all(&self, items: Vec<MdElem>) -> Vec<MdElem> {
+        self.position.select(items)
+    }
+}
+
+impl TrySelector<MdElem> for PositionSelector {
+    fn try_select(&self, _: &MdContext, item: MdElem) -> Result<Select> {
+        // We only get here if the position isn't within a chain (since ChainSelector uses select_all instead). In that
+        // case, the item is the whole document: treat it as a stream of one.
+        Ok(Select::Hit(self.select_all(vec![item])))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::md_elem::elem::*;
+    use crate::md_elem::*;
+
+    #[test]
+    fn select_all_slice() {
+        let items = md_elems!["alpha", "bravo", "charlie", "delta"];
+
+        let result = PositionSelector::from(Position::Slice {
+            start: Some(1),
+            end: Some(3),
+        })
+        .select_all(items);
+
+        assert_eq!(result, md_elems!["bravo", "charlie"]);
+    }
+
+    #[test]
+    fn select_all_past_the_end() {
+        let items = md_elems!["alpha", "bravo"];
+
+        assert_eq!(
+            PositionSelector::from(Position::Nth(3)).select_all(items.clone()),
+            vec![]
+        );
+        assert_eq!(
+            PositionSelector::from(Position::Slice {
+                start: Some(1),
+                end: Some(10),
+            })
+            .select_all(items),
+            md_elems!["bravo"]
+        );
+    }
+
+    #[test]
+    fn last_of_empty() {
+        assert_eq!(PositionSelector::from(Position::Last).select_all(vec![]), vec![]);
+    }
+
+    #[test]
+    fn try_select_is_stream_of_one() {
+        let doc = MdElem::Doc(md_elems!["alpha", "bravo"]);
+
+        let first = PositionSelector::from(Position::First)
+            .try_select(&MdContext::empty(), doc.clone())
+            .unwrap();
+        let second = PositionSelector::from(Position::Nth(2))
+            .try_select(&MdContext::empty(), doc.clone())
+            .unwrap();
+
+        assert_eq!(first, Select::Hit(vec![doc]));
+        assert_eq!(second, Select::Hit(vec![]));
+    }
+}
@@ -1,48 +1,124 @@
 use crate::md_elem::elem::*;
-use crate::md_elem::MdContext;
+use crate::md_elem::slugs::title_slug;
+use crate::md_elem::{MdContext, MdElem};
 use crate::select::match_selector::make_select_result;
 use crate::select::string_matcher::StringMatcher;
-use crate::select::{SectionMatcher, Select, TrySelector};
+use crate::select::{SectionDepth, SectionMatcher, SectionSlugMatcher, Select, TrySelector};
 
 #[derive(Debug, PartialEq)]
 pub(crate) struct SectionSelector {
     matcher: StringMatcher,
+    depth: Option<SectionDepth>,
+    subsections: Vec<StringMatcher>,
 }
 
 impl From<SectionMatcher> for SectionSelector {
     fn from(value: SectionMatcher) -> Self {
         Self {
             matcher: value.title.into(),
+            depth: value.depth,
+            subsections: value.subsections.into_iter().map(StringMatcher::from).collect(),
         }
     }
 }
 
 impl TrySelector<Section> for SectionSelector {
     fn try_select(&self, _: &MdContext, item: Section) -> crate::select::Result<Select> {
-        match self.matcher.match_replace_inlines(item.title) {
-            Ok(replacements) => {
-                let result = Section {
-                    title: replacements.item,
-                    depth: item.depth,
-                    body: item.body,
-                };
-                Ok(make_select_result(result, replacements.matched_any))
+        if let Some(depth) = self.depth {
+            if !depth.contains(item.depth) {
+                return Ok(Select::Miss(item.into()));
             }
-            Err(err) => Err(err.to_select_error("section")),
+        }
+        if self.subsections.is_empty() {
+            let (result, matched) = replace_title(&self.matcher, item)?;
+            return Ok(make_select_result(result, matched));
+        }
+
+        // Follow the path down, one level of direct children at a time. We need to keep the original item around, in
+        // case the path doesn't pan out: then it's a miss, and the search will recurse into it.
+        let (root, matched) = replace_title(&self.matcher, item.clone())?;
+        if !matched {
+            return Ok(Select::Miss(item.into()));
+        }
+        let mut parents = vec![root];
+        for subsection_matcher in &self.subsections {
+            let mut children = Vec::new();
+            for parent in parents {
+                for child in parent.body {
+                    if let MdElem::Section(child) = child {
+                        let (child, matched) = replace_title(subsection_matcher, child)?;
+                        if matched {
+                            children.push(child);
+                        }
+                    }
+                }
+            }
+            parents = children;
+        }
+        Ok(if parents.is_empty() {
+            Select::Miss(item.into())
+        } else {
+            Select::Hit(parents.into_iter().map(MdElem::Section).collect())
+        })
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct SectionSlugSelector {
+    slug: String,
+}
+
+impl From<SectionSlugMatcher> for SectionSlugSelector {
+    fn from(value: SectionSlugMatcher) -> Self {
+        Self {
+            slug: value.slug.to_lowercase(),
         }
     }
 }
 
+impl TrySelector<Section> for SectionSlugSelector {
+    /// A section's slugs come from the whole document (see [`MdContext::get_section_slugs`]), so a section whose title
+    /// is the same as an earlier one's matches its suffixed slug in any stage. A section that isn't in the document
+    /// as-is (for example, because a replacement changed its title) only has the slug from its title.
+    fn try_select(&self, ctx: &MdContext, item: Section) -> crate::select::Result<Select> {
+        let own_slug = title_slug(&item.title);
+        let item = MdElem::Section(item);
+        let slugs = ctx.get_section_slugs(&item);
+        let matched = if slugs.is_empty() {
+            own_slug == self.slug
+        } else {
+            slugs.contains(&self.slug.as_str())
+        };
+        Ok(make_select_result(item, matched))
+    }
+}
+
+fn replace_title(matcher: &StringMatcher, section: Section) -> crate::select::Result<(Section, bool)> {
+    let Section { depth, title, body } = section;
+    let replacements = matcher
+        .match_replace_inlines(title)
+        .map_err(|err| err.to_select_error("section"))?;
+    let result = Section {
+        title: replacements.item,
+        depth,
+        body,
+    };
+    Ok((result, replacements.matched_any))
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::md_elem::{inlines, MdElem};
-    use crate::select::{MatchReplace, SelectError};
+    use crate::md_elem::{inlines, MdDoc, MdElem, ParseOptions};
+    use crate::select::api::SelectorAdapter;
+    use crate::select::{MatchReplace, SelectError, Selector};
 
     #[test]
     fn section_replacement_matches_on_title() {
         let section_matcher = SectionMatcher {
             title: MatchReplace::build(|b| b.match_regex("Some").replacement("Great")),
+            depth: None,
+            subsections: Vec::new(),
         };
 
         let section = Section {
@@ -68,6 +144,8 @@ mod test {
     fn section_replacement_misses_on_title() {
         let section_matcher = SectionMatcher {
             title: MatchReplace::build(|b| b.match_regex("Unmatched").replacement("Great")),
+            depth: None,
+            subsections: Vec::new(),
         };
 
         let section = Section {
@@ -93,6 +171,8 @@ mod test {
     fn section_replacement_invalid_on_title() {
         let section_matcher = SectionMatcher {
             title: MatchReplace::build(|b| b.match_regex("crosses boundary").replacement("Broken")),
+            depth: None,
+            subsections: Vec::new(),
         };
 
         let section = Section {
@@ -116,6 +196,8 @@ mod test {
     fn section_regex_matches() {
         let section_matcher = SectionMatcher {
             title: MatchReplace::build(|b| b.match_regex("Great")),
+            depth: None,
+            subsections: Vec::new(),
         };
 
         let section = Section {
@@ -141,6 +223,8 @@ mod test {
     fn section_regex_doesnt_match() {
         let section_matcher = SectionMatcher {
             title: MatchReplace::build(|b| b.match_regex("Awesome")),
+            depth: None,
+            subsections: Vec::new(),
         };
 
         let section = Section {
@@ -161,4 +245,220 @@ mod test {
             })),
         );
     }
+
+    #[test]
+    fn section_depth_matches() {
+        let section_matcher = SectionMatcher {
+            title: MatchReplace::build(|b| b.match_regex("Great")),
+            depth: Some(SectionDepth { min: 2, max: 3 }),
+            subsections: Vec::new(),
+        };
+
+        let section = Section {
+            depth: 3,
+            title: inlines!["Great title"],
+            body: vec![],
+        };
+
+        let section_selector = SectionSelector::from(section_matcher);
+        let selected = section_selector.try_select(&MdContext::default(), section).unwrap();
+
+        assert_eq!(
+            selected,
+            Select::Hit(vec![MdElem::Section(Section {
+                depth: 3,
+                title: inlines!["Great title"],
+                body: vec![],
+            })]),
+        );
+    }
+
+    #[test]
+    fn section_path_matches_direct_child() {
+        let section_matcher = SectionMatcher {
+            title: MatchReplace::build(|b| b.match_regex("API")),
+            depth: None,
+            subsections: vec![MatchReplace::build(|b| b.match_regex("Errors").replacement("Failures"))],
+        };
+
+        let section = Section {
+            depth: 1,
+            title: inlines!["API"],
+            body: vec![
+                MdElem::Section(Section {
+                    depth: 2,
+                    title: inlines!["Errors"],
+                    body: vec![],
+                }),
+                MdElem::Section(Section {
+                    depth: 2,
+                    title: inlines!["Examples"],
+                    body: vec![],
+                }),
+            ],
+        };
+
+        let section_selector = SectionSelector::from(section_matcher);
+        let selected = section_selector.try_select(&MdContext::default(), section).unwrap();
+
+        assert_eq!(
+            selected,
+            Select::Hit(vec![MdElem::Section(Section {
+                depth: 2,
+                title: inlines!["Failures"],
+                body: vec![],
+            })]),
+        );
+    }
+
+    #[test]
+    fn section_path_ignores_deeper_descendants() {
+        let section_matcher = SectionMatcher {
+            title: MatchReplace::build(|b| b.match_regex("API")),
+            depth: None,
+            subsections: vec![MatchReplace::build(|b| b.match_regex("Errors"))],
+        };
+
+        let section = Section {
+            depth: 1,
+            title: inlines!["API"],
+            body: vec![MdElem::Section(Section {
+                depth: 2,
+                title: inlines!["Examples"],
+                body: vec![MdElem::Section(Section {
+                    depth: 3,
+                    title: inlines!["Errors"],
+                    body: vec![],
+                })],
+            })],
+        };
+
+        let section_selector = SectionSelector::from(section_matcher);
+        let selected = section_selector
+            .try_select(&MdContext::default(), section.clone())
+            .unwrap();
+
+        assert_eq!(selected, Select::Miss(MdElem::Section(section)));
+    }
+
+    #[test]
+    fn section_depth_doesnt_match() {
+        let section_matcher = SectionMatcher {
+            title: MatchReplace::build(|b| b.match_regex("Great").replacement("Awesome")),
+            depth: Some(SectionDepth { min: 2, max: 2 }),
+            subsections: Vec::new(),
+        };
+
+        let section = Section {
+            depth: 1,
+            title: inlines!["Great title"],
+            body: vec![],
+        };
+
+        let section_selector = SectionSelector::from(section_matcher);
+        let selected = section_selector.try_select(&MdContext::default(), section).unwrap();
+
+        // the title matches, but the depth doesn't; so it's a miss, and the replacement doesn't happen
+        assert_eq!(
+            selected,
+            Select::Miss(MdElem::Section(Section {
+                depth: 1,
+                title: inlines!["Great title"],
+                body: vec![],
+            })),
+        );
+    }
+
+    #[test]
+    fn slug_matches_with_suffix() {
+        let found = find_slug("Foo-1", "# Foo\n\none\n\n# Foo\n\ntwo\n");
+
+        assert_eq!(found, vec![section_elem(1, "Foo", "two")]);
+    }
+
+    #[test]
+    fn identical_sections_share_their_slugs() {
+        let md = "# Foo\n\nsame\n\n# Foo\n\nsame\n";
+
+        // They're indistinguishable, so each of them matches either slug.
+        assert_eq!(find_slug("foo", md).len(), 2);
+        assert_eq!(find_slug("foo-1", md).len(), 2);
+        assert_eq!(find_slug("foo-2", md), vec![]);
+    }
+
+    #[test]
+    fn nested_sections_in_document_order() {
+        let md = "# API\n\n## Examples\n\ninner\n\n# Examples\n\nouter\n";
+
+        assert_eq!(find_slug("examples", md), vec![section_elem(2, "Examples", "inner")]);
+        assert_eq!(find_slug("examples-1", md), vec![section_elem(1, "Examples", "outer")]);
+    }
+
+    #[test]
+    fn sections_within_a_match_take_up_their_slugs() {
+        let found = find_slug("bar-1", "# Foo\n\n## Bar\n\ninner\n\n# Bar\n\nouter\n");
+
+        assert_eq!(found, vec![section_elem(1, "Bar", "outer")]);
+    }
+
+    #[test]
+    fn slug_is_from_the_whole_document() {
+        let doc = MdDoc::parse(
+            "# Examples\n\nfirst\n\n# API\n\n## Examples\n\nsecond\n",
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let api = doc.roots[1].clone();
+        let MdElem::Section(api) = api else {
+            panic!("expected a section: {api:?}");
+        };
+        let MdElem::Section(examples) = api.body[0].clone() else {
+            panic!("expected a section: {:?}", api.body[0]);
+        };
+
+        let selected = slug_selector("examples-1")
+            .try_select(&doc.ctx, examples.clone())
+            .unwrap();
+        assert_eq!(selected, Select::Hit(vec![MdElem::Section(examples.clone())]));
+
+        let selected = slug_selector("examples")
+            .try_select(&doc.ctx, examples.clone())
+            .unwrap();
+        assert_eq!(selected, Select::Miss(MdElem::Section(examples)));
+    }
+
+    #[test]
+    fn slug_falls_back_to_title() {
+        let section = Section {
+            depth: 1,
+            title: inlines!["Running the Tests!"],
+            body: vec![],
+        };
+
+        let selected = slug_selector("running-the-tests")
+            .try_select(&MdContext::default(), section.clone())
+            .unwrap();
+
+        assert_eq!(selected, Select::Hit(vec![MdElem::Section(section)]));
+    }
+
+    fn find_slug(slug: &str, md: &str) -> Vec<MdElem> {
+        let doc = MdDoc::parse(md, &ParseOptions::default()).unwrap();
+        let selector = SelectorAdapter::from(Selector::SectionSlug(SectionSlugMatcher { slug: slug.to_string() }));
+        selector.find_nodes(&doc.ctx, doc.roots).unwrap()
+    }
+
+    fn slug_selector(slug: &str) -> SectionSlugSelector {
+        SectionSlugSelector::from(SectionSlugMatcher { slug: slug.to_string() })
+    }
+
+    fn section_elem(depth: u8, title: &str, body: &str) -> MdElem {
+        MdElem::Section(Section {
+            depth,
+            title: inlines![text[title]],
+            body: vec![MdElem::Paragraph(Paragraph {
+                body: inlines![text[body]],
+            })],
+        })
+    }
 }
@@ -2,7 +2,8 @@ use crate::md_elem::elem::*;
 use crate::md_elem::MdContext;
 use crate::select::string_matcher::StringMatcher;
 use crate::select::{
-    match_selector, BlockQuoteMatcher, FrontMatterMatcher, HtmlMatcher, ParagraphMatcher, Result, Select, TrySelector,
+    match_selector, BlockQuoteMatcher, FrontMatterMatcher, HtmlMatcher, InlineCodeMatcher, InlineMathMatcher,
+    MathBlockMatcher, ParagraphMatcher, Result, Select, SpanMatcher, TrySelector,
 };
 use paste::paste;
 
@@ -26,6 +27,9 @@ macro_rules! single_matcher_struct {
 single_matcher_struct! { BlockQuote {text} }
 single_matcher_struct! { Paragraph {text} }
 single_matcher_struct! { Html {html} }
+single_matcher_struct! { MathBlock {text} }
+single_matcher_struct! { InlineCode {text} }
+single_matcher_struct! { InlineMath {text} }
 
 impl TrySelector<BlockQuote> for BlockQuoteSelector {
     fn try_select(&self, _: &MdContext, item: BlockQuote) -> Result<Select> {
@@ -63,6 +67,84 @@ impl TrySelector<BlockHtml> for HtmlSelector {
     }
 }
 
+impl TrySelector<CodeBlock> for MathBlockSelector {
+    fn try_select(&self, _: &MdContext, item: CodeBlock) -> Result<Select> {
+        let CodeVariant::Math { metadata } = item.variant else {
+            return Ok(Select::Miss(item.into()));
+        };
+        let replaced = self
+            .matcher
+            .match_replace_string(item.value)
+            .map_err(|e| e.to_select_error("math block"))?;
+
+        let result = CodeBlock {
+            variant: CodeVariant::Math { metadata },
+            value: replaced.item,
+        };
+        Ok(match_selector::make_select_result(result, replaced.matched_any))
+    }
+}
+
+impl TrySelector<Text> for InlineCodeSelector {
+    fn try_select(&self, _: &MdContext, item: Text) -> Result<Select> {
+        try_select_text(&self.matcher, TextVariant::Code, item, "inline code")
+    }
+}
+
+impl TrySelector<Text> for InlineMathSelector {
+    fn try_select(&self, _: &MdContext, item: Text) -> Result<Select> {
+        try_select_text(&self.matcher, TextVariant::Math, item, "inline math")
+    }
+}
+
+fn try_select_text(matcher: &StringMatcher, variant: TextVariant, item: Text, selector_name: &str) -> Result<Select> {
+    if item.variant != variant {
+        return Ok(Select::Miss(item.into()));
+    }
+    let replaced = matcher
+        .match_replace_string(item.value)
+        .map_err(|e| e.to_select_error(selector_name))?;
+
+    let result = Text {
+        variant,
+        value: replaced.item,
+    };
+    Ok(match_selector::make_select_result(result, replaced.matched_any))
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct SpanSelector {
+    variant: SpanVariant,
+    matcher: StringMatcher,
+}
+
+impl From<SpanMatcher> for SpanSelector {
+    fn from(value: SpanMatcher) -> Self {
+        Self {
+            variant: value.variant,
+            matcher: value.text.into(),
+        }
+    }
+}
+
+impl TrySelector<Span> for SpanSelector {
+    fn try_select(&self, _: &MdContext, item: Span) -> Result<Select> {
+        if item.variant != self.variant {
+            return Ok(Select::Miss(item.into()));
+        }
+        let replaced = self
+            .matcher
+            .match_replace_inlines(item.children)
+            .map_err(|e| e.to_select_error("span"))?;
+
+        let result = Span {
+            variant: item.variant,
+            children: replaced.item,
+        };
+        Ok(match_selector::make_select_result(result, replaced.matched_any))
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub(crate) struct FrontMatterSelector {
     variant: Option<FrontMatterVariant>,
@@ -105,7 +187,10 @@ impl TrySelector<FrontMatter> for FrontMatterSelector {
 mod test {
     use super::*;
     use crate::md_elem::{
-        elem::{BlockHtml, BlockQuote, FrontMatter, FrontMatterVariant, Inline, Paragraph, Text, TextVariant},
+        elem::{
+            BlockHtml, BlockQuote, CodeBlock, CodeVariant, FrontMatter, FrontMatterVariant, Inline, Paragraph, Span,
+            SpanVariant, Text, TextVariant,
+        },
         MdContext, MdElem,
     };
     use crate::select::{MatchReplace, Select, TrySelector};
@@ -342,6 +427,228 @@ mod test {
         }
     }
 
+    mod math_block {
+        use super::*;
+
+        #[test]
+        fn math_block_find_matches() {
+            let math_block_selector = MathBlockSelector::from(MathBlockMatcher {
+                text: MatchReplace::build(|b| b.match_regex("frac")),
+            });
+
+            let selected = math_block_selector
+                .try_select(&MdContext::default(), new_math_block(r"\frac{1}{2}"))
+                .unwrap();
+
+            assert_eq!(
+                selected,
+                Select::Hit(vec![MdElem::CodeBlock(new_math_block(r"\frac{1}{2}"))])
+            );
+        }
+
+        #[test]
+        fn math_block_replacement_matches() {
+            let math_block_selector = MathBlockSelector::from(MathBlockMatcher {
+                text: MatchReplace::build(|b| b.match_regex("frac").replacement("dfrac")),
+            });
+
+            let selected = math_block_selector
+                .try_select(&MdContext::default(), new_math_block(r"\frac{1}{2}"))
+                .unwrap();
+
+            assert_eq!(
+                selected,
+                Select::Hit(vec![MdElem::CodeBlock(new_math_block(r"\dfrac{1}{2}"))])
+            );
+        }
+
+        #[test]
+        fn code_block_misses() {
+            let code_block = CodeBlock {
+                variant: CodeVariant::Code(None),
+                value: r"\frac{1}{2}".to_string(),
+            };
+
+            let math_block_selector = MathBlockSelector::from(MathBlockMatcher {
+                text: MatchReplace::match_any(),
+            });
+
+            let selected = math_block_selector
+                .try_select(&MdContext::default(), code_block.clone())
+                .unwrap();
+
+            assert_eq!(selected, Select::Miss(MdElem::CodeBlock(code_block)));
+        }
+
+        fn new_math_block(content: &str) -> CodeBlock {
+            CodeBlock {
+                variant: CodeVariant::Math { metadata: None },
+                value: content.to_string(),
+            }
+        }
+    }
+
+    mod inline_code {
+        use super::*;
+
+        #[test]
+        fn inline_code_find_matches() {
+            let inline_code_selector = InlineCodeSelector::from(InlineCodeMatcher {
+                text: MatchReplace::build(|b| b.match_regex("verbose")),
+            });
+
+            let selected = inline_code_selector
+                .try_select(&MdContext::default(), new_text(TextVariant::Code, "--verbose"))
+                .unwrap();
+
+            assert_eq!(
+                selected,
+                Select::Hit(vec![new_text(TextVariant::Code, "--verbose").into()])
+            );
+        }
+
+        #[test]
+        fn inline_code_replacement_matches() {
+            let inline_code_selector = InlineCodeSelector::from(InlineCodeMatcher {
+                text: MatchReplace::build(|b| b.match_regex("verbose").replacement("quiet")),
+            });
+
+            let selected = inline_code_selector
+                .try_select(&MdContext::default(), new_text(TextVariant::Code, "--verbose"))
+                .unwrap();
+
+            assert_eq!(
+                selected,
+                Select::Hit(vec![new_text(TextVariant::Code, "--quiet").into()])
+            );
+        }
+
+        #[test]
+        fn plain_text_misses() {
+            let inline_code_selector = InlineCodeSelector::from(InlineCodeMatcher {
+                text: MatchReplace::match_any(),
+            });
+
+            let selected = inline_code_selector
+                .try_select(&MdContext::default(), new_text(TextVariant::Plain, "--verbose"))
+                .unwrap();
+
+            assert_eq!(selected, Select::Miss(new_text(TextVariant::Plain, "--verbose").into()));
+        }
+    }
+
+    mod inline_math {
+        use super::*;
+
+        #[test]
+        fn inline_math_find_matches() {
+            let inline_math_selector = InlineMathSelector::from(InlineMathMatcher {
+                text: MatchReplace::build(|b| b.match_regex("mc")),
+            });
+
+            let selected = inline_math_selector
+                .try_select(&MdContext::default(), new_text(TextVariant::Math, "E = mc^2"))
+                .unwrap();
+
+            assert_eq!(
+                selected,
+                Select::Hit(vec![new_text(TextVariant::Math, "E = mc^2").into()])
+            );
+        }
+
+        #[test]
+        fn inline_code_misses() {
+            let inline_math_selector = InlineMathSelector::from(InlineMathMatcher {
+                text: MatchReplace::match_any(),
+            });
+
+            let selected = inline_math_selector
+                .try_select(&MdContext::default(), new_text(TextVariant::Code, "E = mc^2"))
+                .unwrap();
+
+            assert_eq!(selected, Select::Miss(new_text(TextVariant::Code, "E = mc^2").into()));
+        }
+    }
+
+    mod span {
+        use super::*;
+
+        #[test]
+        fn span_find_matches() {
+            let span_selector = SpanSelector::from(SpanMatcher {
+                variant: SpanVariant::Delete,
+                text: MatchReplace::build(|b| b.match_regex("old")),
+            });
+
+            let selected = span_selector
+                .try_select(&MdContext::default(), new_span(SpanVariant::Delete, "old text"))
+                .unwrap();
+
+            assert_eq!(
+                selected,
+                Select::Hit(vec![new_span(SpanVariant::Delete, "old text").into()])
+            );
+        }
+
+        #[test]
+        fn span_replacement_matches() {
+            let span_selector = SpanSelector::from(SpanMatcher {
+                variant: SpanVariant::Strong,
+                text: MatchReplace::build(|b| b.match_regex("old").replacement("new")),
+            });
+
+            let selected = span_selector
+                .try_select(&MdContext::default(), new_span(SpanVariant::Strong, "old text"))
+                .unwrap();
+
+            assert_eq!(
+                selected,
+                Select::Hit(vec![new_span(SpanVariant::Strong, "new text").into()])
+            );
+        }
+
+        #[test]
+        fn span_find_misses() {
+            let span_selector = SpanSelector::from(SpanMatcher {
+                variant: SpanVariant::Emphasis,
+                text: MatchReplace::build(|b| b.match_regex("other")),
+            });
+
+            let selected = span_selector
+                .try_select(&MdContext::default(), new_span(SpanVariant::Emphasis, "old text"))
+                .unwrap();
+
+            assert_eq!(
+                selected,
+                Select::Miss(new_span(SpanVariant::Emphasis, "old text").into())
+            );
+        }
+
+        #[test]
+        fn different_variant_misses() {
+            let span_selector = SpanSelector::from(SpanMatcher {
+                variant: SpanVariant::Strong,
+                text: MatchReplace::match_any(),
+            });
+
+            let selected = span_selector
+                .try_select(&MdContext::default(), new_span(SpanVariant::Emphasis, "old text"))
+                .unwrap();
+
+            assert_eq!(
+                selected,
+                Select::Miss(new_span(SpanVariant::Emphasis, "old text").into())
+            );
+        }
+
+        fn new_span(variant: SpanVariant, content: &str) -> Span {
+            Span {
+                variant,
+                children: vec![Inline::Text(new_text(TextVariant::Plain, content))],
+            }
+        }
+    }
+
     mod front_matter {
         use super::*;
 
@@ -430,4 +737,11 @@ mod test {
             }
         }
     }
+
+    fn new_text(variant: TextVariant, value: &str) -> Text {
+        Text {
+            variant,
+            value: value.to_string(),
+        }
+    }
 }
@@ -0,0 +1,110 @@
+use crate::md_elem::elem::List;
+use crate::md_elem::{MdContext, MdElem};
+use crate::select::{Result, Select, TaskAction, TrySelector};
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct TaskActionSelector {
+    action: TaskAction,
+}
+
+impl From<TaskAction> for TaskActionSelector {
+    fn from(action: TaskAction) -> Self {
+        Self { action }
+    }
+}
+
+impl TrySelector<List> for TaskActionSelector {
+    fn try_select(&self, _: &MdContext, item: List) -> Result<Select> {
+        // Like ListItemSelector, this splits multi-item lists into single-item lists, and then selects (and changes)
+        // each task among those. That way, the results are the same items that "- [?]" would select.
+        let List {
+            starting_index,
+            mut items,
+        } = item;
+        match items.as_mut_slice() {
+            [li] if li.checked.is_some() => {
+                li.checked = self.action.apply(li.checked);
+                Ok(Select::Hit(vec![MdElem::List(List { starting_index, items })]))
+            }
+            [_] => Ok(Select::Miss(MdElem::List(List { starting_index, items }))),
+            _ => {
+                let mut idx = starting_index;
+                let mut items_doc = Vec::with_capacity(items.len());
+                for item in items {
+                    items_doc.push(MdElem::List(List {
+                        starting_index: idx,
+                        items: vec![item],
+                    }));
+                    if let Some(idx) = idx.as_mut() {
+                        *idx += 1;
+                    }
+                }
+                Ok(Select::Miss(MdElem::Doc(items_doc)))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::md_elem::elem::{ListItem, Paragraph};
+    use crate::md_elem::{inlines, md_elem};
+
+    #[test]
+    fn checks_task() {
+        let result = TaskActionSelector::from(TaskAction::Check)
+            .try_select(&MdContext::empty(), list(&[Some(false)]))
+            .unwrap();
+
+        assert_eq!(result, Select::Hit(vec![MdElem::List(list(&[Some(true)]))]));
+    }
+
+    #[test]
+    fn toggles_task() {
+        let result = TaskActionSelector::from(TaskAction::Toggle)
+            .try_select(&MdContext::empty(), list(&[Some(true)]))
+            .unwrap();
+
+        assert_eq!(result, Select::Hit(vec![MdElem::List(list(&[Some(false)]))]));
+    }
+
+    #[test]
+    fn skips_non_task() {
+        let result = TaskActionSelector::from(TaskAction::Check)
+            .try_select(&MdContext::empty(), list(&[None]))
+            .unwrap();
+
+        assert_eq!(result, Select::Miss(MdElem::List(list(&[None]))));
+    }
+
+    #[test]
+    fn splits_multi_item_list() {
+        let result = TaskActionSelector::from(TaskAction::Check)
+            .try_select(&MdContext::empty(), list(&[Some(false), None]))
+            .unwrap();
+
+        assert_eq!(
+            result,
+            Select::Miss(MdElem::Doc(vec![
+                MdElem::List(list(&[Some(false)])),
+                MdElem::List(list(&[None])),
+            ]))
+        );
+    }
+
+    fn list(checked: &[Option<bool>]) -> List {
+        List {
+            starting_index: None,
+            items: checked
+                .iter()
+                .map(|&checked| ListItem {
+                    checked,
+                    item: vec![md_elem!(Paragraph {
+                        body: inlines!(text["task"])
+                    })],
+                })
+                .collect(),
+        }
+    }
+}
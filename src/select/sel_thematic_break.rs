@@ -0,0 +1,252 @@
+use crate::md_elem::elem::*;
+use crate::md_elem::{MdContext, MdElem};
+use crate::select::string_matcher::StringMatcher;
+use crate::select::{Result, Select, ThematicBreakMatcher, TrySelector};
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct ThematicBreakSelector {
+    between_matcher: Option<StringMatcher>,
+}
+
+impl From<ThematicBreakMatcher> for ThematicBreakSelector {
+    fn from(value: ThematicBreakMatcher) -> Self {
+        Self {
+            between_matcher: value.between.map(StringMatcher::from),
+        }
+    }
+}
+
+impl TrySelector<MdElem> for ThematicBreakSelector {
+    fn try_select(&self, _: &MdContext, item: MdElem) -> Result<Select> {
+        let Some(between_matcher) = &self.between_matcher else {
+            return Ok(match item {
+                MdElem::ThematicBreak => Select::Hit(vec![MdElem::ThematicBreak]),
+                other => Select::Miss(other),
+            });
+        };
+        if !contains_break(&item) {
+            return Ok(Select::Miss(item));
+        }
+        let elems = match item {
+            MdElem::Doc(elems) => elems,
+            other => vec![other],
+        };
+        let groups = split_at_breaks(elems);
+        let mut hits = Vec::new();
+        for group in &groups {
+            let replaced = between_matcher
+                .match_replace_any(group.clone())
+                .map_err(|e| e.to_select_error("thematic break"))?;
+            if replaced.matched_any {
+                hits.push(MdElem::Doc(replaced.item));
+            }
+        }
+        // If none of the groups match, the miss is the groups themselves, rather than the original item. That way, if
+        // the search recurses, it goes into each group (where there are no more breaks) instead of into the sections
+        // we just split, where it would find smaller groups.
+        Ok(if hits.is_empty() {
+            Select::Miss(MdElem::Doc(groups.into_iter().map(MdElem::Doc).collect()))
+        } else {
+            Select::Hit(hits)
+        })
+    }
+}
+
+/// Whether this element is, or contains, a thematic break that splits it into groups.
+///
+/// Sections are transparent for this purpose, since a `---` at the end of one "slide" typically gets parsed as part of
+/// the body of that slide's section. Other containers, like block quotes, are not: a break within one of them only
+/// splits that container.
+fn contains_break(elem: &MdElem) -> bool {
+    match elem {
+        MdElem::ThematicBreak => true,
+        MdElem::Doc(elems) => elems.iter().any(contains_break),
+        MdElem::Section(section) => section.body.iter().any(contains_break),
+        _ => false,
+    }
+}
+
+/// Splits the elements into the non-empty runs between thematic breaks.
+///
+/// A section that contains a break is itself split: its title and the part of its body before the break go into one
+/// group, and the rest of its body continues on into the next groups.
+fn split_at_breaks(elems: Vec<MdElem>) -> Vec<Vec<MdElem>> {
+    let mut groups = vec![Vec::new()];
+    split_into(elems, &mut groups);
+    groups.retain(|group| !group.is_empty());
+    groups
+}
+
+fn split_into(elems: Vec<MdElem>, groups: &mut Vec<Vec<MdElem>>) {
+    for elem in elems {
+        match elem {
+            MdElem::ThematicBreak => groups.push(Vec::new()),
+            MdElem::Doc(elems) => split_into(elems, groups),
+            MdElem::Section(section) if section.body.iter().any(contains_break) => {
+                let Section { depth, title, body } = section;
+                let mut body_groups = vec![Vec::new()];
+                split_into(body, &mut body_groups);
+                let mut body_groups = body_groups.into_iter();
+                let first_body = body_groups.next().unwrap_or_default();
+                push_to_last(
+                    groups,
+                    MdElem::Section(Section {
+                        depth,
+                        title,
+                        body: first_body,
+                    }),
+                );
+                groups.extend(body_groups);
+            }
+            other => push_to_last(groups, other),
+        }
+    }
+}
+
+fn push_to_last(groups: &mut [Vec<MdElem>], elem: MdElem) {
+    if let Some(last) = groups.last_mut() {
+        last.push(elem);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::md_elem::*;
+    use crate::select::MatchReplace;
+
+    #[test]
+    fn select_breaks() {
+        let result = ThematicBreakSelector::from(ThematicBreakMatcher { between: None })
+            .try_select(&MdContext::empty(), MdElem::ThematicBreak)
+            .unwrap();
+
+        assert_eq!(result, Select::Hit(vec![MdElem::ThematicBreak]));
+    }
+
+    #[test]
+    fn breaks_selector_misses_other_elems() {
+        let doc = MdElem::Doc(md_elems!["alpha", "bravo"]);
+
+        let result = ThematicBreakSelector::from(ThematicBreakMatcher { between: None })
+            .try_select(&MdContext::empty(), doc.clone())
+            .unwrap();
+
+        assert_eq!(result, Select::Miss(doc));
+    }
+
+    #[test]
+    fn between_selects_matching_groups() {
+        let doc = MdElem::Doc(vec![
+            paragraph("alpha"),
+            MdElem::ThematicBreak,
+            paragraph("bravo"),
+            paragraph("charlie"),
+            MdElem::ThematicBreak,
+            paragraph("delta"),
+        ]);
+
+        let result = ThematicBreakSelector::from(ThematicBreakMatcher {
+            between: Some(MatchReplace::build(|b| b.match_regex("charlie|delta"))),
+        })
+        .try_select(&MdContext::empty(), doc)
+        .unwrap();
+
+        assert_eq!(
+            result,
+            Select::Hit(vec![
+                MdElem::Doc(vec![paragraph("bravo"), paragraph("charlie")]),
+                MdElem::Doc(vec![paragraph("delta")]),
+            ])
+        );
+    }
+
+    #[test]
+    fn between_skips_empty_groups() {
+        let doc = MdElem::Doc(vec![
+            MdElem::ThematicBreak,
+            paragraph("alpha"),
+            MdElem::ThematicBreak,
+            MdElem::ThematicBreak,
+        ]);
+
+        let result = ThematicBreakSelector::from(ThematicBreakMatcher {
+            between: Some(MatchReplace::match_any()),
+        })
+        .try_select(&MdContext::empty(), doc)
+        .unwrap();
+
+        assert_eq!(result, Select::Hit(vec![MdElem::Doc(vec![paragraph("alpha")])]));
+    }
+
+    #[test]
+    fn between_splits_sections() {
+        let doc = MdElem::Doc(vec![MdElem::Section(Section {
+            depth: 1,
+            title: vec![mdq_inline!("first")],
+            body: vec![paragraph("alpha"), MdElem::ThematicBreak, paragraph("bravo")],
+        })]);
+
+        let result = ThematicBreakSelector::from(ThematicBreakMatcher {
+            between: Some(MatchReplace::match_any()),
+        })
+        .try_select(&MdContext::empty(), doc)
+        .unwrap();
+
+        assert_eq!(
+            result,
+            Select::Hit(vec![
+                MdElem::Doc(vec![MdElem::Section(Section {
+                    depth: 1,
+                    title: vec![mdq_inline!("first")],
+                    body: vec![paragraph("alpha")],
+                })]),
+                MdElem::Doc(vec![paragraph("bravo")]),
+            ])
+        );
+    }
+
+    #[test]
+    fn between_without_breaks_misses() {
+        let doc = MdElem::Doc(vec![MdElem::BlockQuote(BlockQuote {
+            body: vec![paragraph("alpha"), MdElem::ThematicBreak, paragraph("bravo")],
+        })]);
+
+        let result = ThematicBreakSelector::from(ThematicBreakMatcher {
+            between: Some(MatchReplace::match_any()),
+        })
+        .try_select(&MdContext::empty(), doc.clone())
+        .unwrap();
+
+        // the break is within the block quote, so it's only found when the search recurses into it
+        assert_eq!(result, Select::Miss(doc));
+    }
+
+    #[test]
+    fn between_with_no_matching_groups() {
+        let doc = MdElem::Doc(vec![paragraph("alpha"), MdElem::ThematicBreak, paragraph("bravo")]);
+
+        let result = ThematicBreakSelector::from(ThematicBreakMatcher {
+            between: Some(MatchReplace::build(|b| b.match_regex("charlie"))),
+        })
+        .try_select(&MdContext::empty(), doc)
+        .unwrap();
+
+        assert_eq!(
+            result,
+            Select::Miss(MdElem::Doc(vec![
+                MdElem::Doc(vec![paragraph("alpha")]),
+                MdElem::Doc(vec![paragraph("bravo")]),
+            ]))
+        );
+    }
+
+    fn paragraph(text: &str) -> MdElem {
+        MdElem::Paragraph(Paragraph {
+            body: vec![Inline::Text(Text {
+                variant: TextVariant::Plain,
+                value: text.to_string(),
+            })],
+        })
+    }
+}
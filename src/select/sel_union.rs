@@ -0,0 +1,105 @@
+use crate::md_elem::{MdContext, MdElem};
+use crate::select::{Result, Select, Selector, SelectorAdapter, TrySelector};
+
+#[derive(Debug)]
+pub(crate) struct UnionSelector {
+    alternatives: Vec<SelectorAdapter>,
+}
+
+impl From<Vec<Selector>> for UnionSelector {
+    fn from(alternatives: Vec<Selector>) -> Self {
+        Self {
+            alternatives: alternatives.into_iter().map(|s| s.into()).collect(),
+        }
+    }
+}
+
+impl TrySelector<MdElem> for UnionSelector {
+    fn try_select(&self, ctx: &MdContext, mut item: MdElem) -> Result<Select> {
+        // The first alternative to match wins. Since we only ever look at each node once, this keeps the results in
+        // document order, and never selects the same node twice.
+        for alternative in &self.alternatives {
+            match alternative.try_select_node(ctx, item)? {
+                hit @ Select::Hit(_) => return Ok(hit),
+                Select::Miss(missed) => item = missed,
+            }
+        }
+        Ok(Select::Miss(item))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::md_elem::elem::*;
+    use crate::md_elem::*;
+    use crate::select::{BlockQuoteMatcher, MatchReplace, ParagraphMatcher};
+
+    #[test]
+    fn first_alternative_matches() {
+        let result = new_union("alpha", "bravo")
+            .try_select(&MdContext::empty(), paragraph("alpha"))
+            .unwrap();
+
+        assert_eq!(result, Select::Hit(vec![paragraph("alpha")]));
+    }
+
+    #[test]
+    fn second_alternative_matches() {
+        let result = new_union("alpha", "bravo")
+            .try_select(&MdContext::empty(), paragraph("bravo"))
+            .unwrap();
+
+        assert_eq!(result, Select::Hit(vec![paragraph("bravo")]));
+    }
+
+    #[test]
+    fn no_alternatives_match() {
+        let result = new_union("alpha", "bravo")
+            .try_select(&MdContext::empty(), paragraph("charlie"))
+            .unwrap();
+
+        assert_eq!(result, Select::Miss(paragraph("charlie")));
+    }
+
+    #[test]
+    fn matches_of_different_types_in_document_order() {
+        let union = UnionSelector::from(vec![
+            Selector::BlockQuote(BlockQuoteMatcher {
+                text: MatchReplace::match_any(),
+            }),
+            Selector::Paragraph(ParagraphMatcher {
+                text: MatchReplace::match_any(),
+            }),
+        ]);
+        let quote = MdElem::BlockQuote(BlockQuote {
+            body: vec![paragraph("bravo")],
+        });
+        let doc = vec![paragraph("alpha"), quote.clone(), paragraph("charlie")];
+
+        let result = SelectorAdapter::Union(union)
+            .find_nodes(&MdContext::empty(), doc)
+            .unwrap();
+
+        // the block quote's paragraph isn't selected separately, since the block quote was already selected
+        assert_eq!(result, vec![paragraph("alpha"), quote, paragraph("charlie")]);
+    }
+
+    fn new_union(first: &str, second: &str) -> UnionSelector {
+        let paragraph_selector = |text: &str| {
+            Selector::Paragraph(ParagraphMatcher {
+                text: MatchReplace::build(|b| b.match_regex(text)),
+            })
+        };
+        UnionSelector::from(vec![paragraph_selector(first), paragraph_selector(second)])
+    }
+
+    fn paragraph(text: &str) -> MdElem {
+        MdElem::Paragraph(Paragraph {
+            body: vec![Inline::Text(Text {
+                variant: TextVariant::Plain,
+                value: text.to_string(),
+            })],
+        })
+    }
+}
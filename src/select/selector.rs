@@ -1,7 +1,7 @@
-use crate::md_elem::elem::FrontMatterVariant;
+use crate::md_elem::elem::{FrontMatterVariant, SpanVariant};
 use crate::md_elem::{MdContext, MdDoc, MdElem};
 use crate::query::ParseError;
-use crate::select::{MatchReplace, Result, SelectorAdapter};
+use crate::select::{parse_front_matter, MatchReplace, Matcher, Result, SelectError, SelectorAdapter};
 
 /// The completion state that a [`ListItemMatcher`] looks for.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -32,6 +32,132 @@ pub struct ListItemMatcher {
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct SectionMatcher {
     pub title: MatchReplace,
+    /// The heading depths this matches, or `None` to match sections of any depth.
+    pub depth: Option<SectionDepth>,
+    /// Titles for a path of sections under this one, each a direct child of the one before it: `# "API" > Errors` has a
+    /// `title` of `API` and `subsections` of `[Errors]`. (An unquoted title can contain `>`, so `# API > Errors` is just
+    /// a title.)
+    ///
+    /// If this is not empty, the selector selects the sections at the end of the path, rather than this section.
+    pub subsections: Vec<MatchReplace>,
+}
+
+/// An inclusive range of heading depths, for [`SectionMatcher::depth`].
+///
+/// `## foo` is equivalent to a range with `min` and `max` both `2`, while `#{2,3} foo` has a `min` of `2` and a `max`
+/// of `3`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SectionDepth {
+    pub min: u8,
+    pub max: u8,
+}
+
+impl SectionDepth {
+    /// Whether the given depth is within this range.
+    ///
+    /// ```
+    /// use mdq::select::SectionDepth;
+    ///
+    /// let depth = SectionDepth { min: 2, max: 3 };
+    /// assert!(!depth.contains(1));
+    /// assert!(depth.contains(2));
+    /// assert!(depth.contains(3));
+    /// assert!(!depth.contains(4));
+    /// ```
+    pub fn contains(self, depth: u8) -> bool {
+        self.min <= depth && depth <= self.max
+    }
+}
+
+/// matcher for [`Selector::SectionSlug`]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SectionSlugMatcher {
+    /// The anchor slug, not including the `#`. This is matched case-insensitively.
+    pub slug: String,
+}
+
+/// matcher for [`Selector::ThematicBreak`]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ThematicBreakMatcher {
+    /// If `None`, this matches the thematic breaks themselves. Otherwise, it matches the runs of elements between
+    /// thematic breaks (including the runs before the first break and after the last one), whose contents match.
+    pub between: Option<MatchReplace>,
+}
+
+/// The results that a [`Selector::Position`] keeps, from the previous stage of a [`Selector::Chain`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Position {
+    /// `:first`
+    First,
+    /// `:last`
+    Last,
+    /// `:nth(3)`, where the first result is `:nth(1)`
+    Nth(usize),
+    /// `[1..4]`, where the first result is at index `0`
+    ///
+    /// As with Rust ranges, the start is inclusive and the end is exclusive, and either may be omitted: `[..3]` keeps
+    /// the first three results, and `[1..]` keeps all but the first.
+    Slice { start: Option<usize>, end: Option<usize> },
+}
+
+impl Position {
+    /// Keeps only the items at this position.
+    ///
+    /// ```
+    /// use mdq::select::Position;
+    ///
+    /// let items = vec!['a', 'b', 'c', 'd'];
+    /// assert_eq!(Position::First.select(items.clone()), vec!['a']);
+    /// assert_eq!(Position::Nth(2).select(items.clone()), vec!['b']);
+    /// assert_eq!(Position::Slice { start: Some(1), end: Some(3) }.select(items.clone()), vec!['b', 'c']);
+    /// assert_eq!(Position::Slice { start: Some(5), end: None }.select(items), Vec::<char>::new());
+    /// ```
+    pub fn select<T>(self, items: Vec<T>) -> Vec<T> {
+        let len = items.len();
+        let (start, end) = match self {
+            Position::First => (0, 1),
+            Position::Last => (len.saturating_sub(1), len),
+            Position::Nth(n) => (n.saturating_sub(1), n),
+            Position::Slice { start, end } => (start.unwrap_or(0), end.unwrap_or(len)),
+        };
+        let end = end.min(len);
+        if start >= end {
+            return Vec::new();
+        }
+        items.into_iter().skip(start).take(end - start).collect()
+    }
+}
+
+/// How a [`Selector::TaskAction`] changes each task.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TaskAction {
+    /// `check`
+    Check,
+    /// `uncheck`
+    Uncheck,
+    /// `toggle`
+    Toggle,
+}
+
+impl TaskAction {
+    /// Applies this action to a list item's [`checked`](crate::md_elem::elem::ListItem::checked) state.
+    ///
+    /// List items that aren't tasks stay that way.
+    ///
+    /// ```
+    /// use mdq::select::TaskAction;
+    ///
+    /// assert_eq!(TaskAction::Check.apply(Some(false)), Some(true));
+    /// assert_eq!(TaskAction::Toggle.apply(Some(true)), Some(false));
+    /// assert_eq!(TaskAction::Uncheck.apply(None), None);
+    /// ```
+    pub fn apply(self, checked: Option<bool>) -> Option<bool> {
+        checked.map(|checked| match self {
+            TaskAction::Check => true,
+            TaskAction::Uncheck => false,
+            TaskAction::Toggle => !checked,
+        })
+    }
 }
 
 /// matcher for both [`Selector::Link`] and [`Selector::Image`]
@@ -41,6 +167,15 @@ pub struct LinklikeMatcher {
     pub url_matcher: MatchReplace,
 }
 
+/// matcher for [`Selector::Footnote`]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FootnoteMatcher {
+    /// Matches the footnote's label, not including the caret: `[^1]` has a label of `1`.
+    pub label: MatchReplace,
+    /// Matches the footnote's definition.
+    pub text: MatchReplace,
+}
+
 /// matcher for [`Selector::BlockQuote`]
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct BlockQuoteMatcher {
@@ -66,6 +201,31 @@ pub struct CodeBlockMatcher {
     pub contents: MatchReplace,
 }
 
+/// matcher for [`Selector::MathBlock`]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MathBlockMatcher {
+    pub text: MatchReplace,
+}
+
+/// matcher for [`Selector::InlineCode`]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct InlineCodeMatcher {
+    pub text: MatchReplace,
+}
+
+/// matcher for [`Selector::InlineMath`]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct InlineMathMatcher {
+    pub text: MatchReplace,
+}
+
+/// matcher for [`Selector::Span`]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SpanMatcher {
+    pub variant: SpanVariant,
+    pub text: MatchReplace,
+}
+
 /// matcher for [`Selector::FrontMatter`]
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct FrontMatterMatcher {
@@ -73,6 +233,22 @@ pub struct FrontMatterMatcher {
     pub text: MatchReplace,
 }
 
+/// matcher for [`Selector::FrontMatterKey`]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FrontMatterKeyMatcher {
+    pub variant: Option<FrontMatterVariant>,
+    pub path: Vec<FrontMatterKey>,
+}
+
+/// One step of a [`FrontMatterKeyMatcher`]'s path into the parsed front matter.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FrontMatterKey {
+    /// `.name`: the value of that key in a table or mapping
+    Field(String),
+    /// `[2]`: the array element at that index, starting from 0
+    Index(usize),
+}
+
 /// matcher for [`Selector::Table`]
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct TableMatcher {
@@ -86,26 +262,83 @@ pub struct TableMatcher {
 pub enum Selector {
     /// `foo | bar`
     Chain(Vec<Self>),
-    /// `# section title`
+    /// `(foo, bar)` or `(foo | bar)`: any of the alternatives
+    ///
+    /// Each element is selected by the first alternative that matches it, so the results are in document order, and an
+    /// element that matches several alternatives is only selected once.
+    Union(Vec<Self>),
+    /// `# section title`, or `## section title` (and so on) to only match a specific depth, or `#{2,3} section title`
+    /// to match a range of depths; or `# parent title / child title` to match only sections directly within a parent
     Section(SectionMatcher),
+    /// `#section-slug`, to match a section by its GitHub-style anchor
+    ///
+    /// Like GitHub, this adds `-1`, `-2` and so on to the slugs of sections whose titles would otherwise have the same
+    /// slug, in document order. Those suffixes count every section in the document, so a section has the same slug in
+    /// any stage of a chain, and within `(...)`, `not(...)` or `has(...)`. Sections that are identical (including their
+    /// bodies) can't be told apart, so each of them matches any of their slugs.
+    SectionSlug(SectionSlugMatcher),
+    /// `---` for thematic breaks, or `--- text` for the runs of elements between them
+    ThematicBreak(ThematicBreakMatcher),
     /// `1. ordered` or `- unordered` lists, or `- [ ] tasks`
     ListItem(ListItemMatcher),
     /// `[some](https://example.com/url)`
     Link(LinklikeMatcher),
     /// `![alt](https://example.com/image.png)`
     Image(LinklikeMatcher),
+    /// `[^label] footnote text`
+    ///
+    /// This selects the footnote's definition, rather than the `[^label]` reference to it. The text matches against
+    /// the definition's body; the output then labels it, as in `[^label]: footnote text`.
+    Footnote(FootnoteMatcher),
     /// `> block quote`
     BlockQuote(BlockQuoteMatcher),
     /// ` ```language contents `
     CodeBlock(CodeBlockMatcher),
+    /// `$$ math block contents`
+    MathBlock(MathBlockMatcher),
+    /// `` `inline code` ``
+    InlineCode(InlineCodeMatcher),
+    /// `$inline math$`
+    InlineMath(InlineMathMatcher),
+    /// `*emphasized*`, `**strong**` or `~~deleted~~` text
+    ///
+    /// `*emphasized*` matches both `*this*` and `_this_` style emphasis in the Markdown.
+    Span(SpanMatcher),
     /// `+++ front matter`
     FrontMatter(FrontMatterMatcher),
+    /// `+++ .title` or `+++yaml .tags[0]`: a value from within the parsed front matter
+    ///
+    /// Scalar values are selected as paragraphs of their text, arrays as lists, and tables or mappings as front matter
+    /// of their own. An empty path (`+++ .`) selects the front matter's entire value.
+    FrontMatterKey(FrontMatterKeyMatcher),
     /// `</> html-tags`
     Html(HtmlMatcher),
     /// `P: paragraph text`
     Paragraph(ParagraphMatcher),
     /// `:-: headers :-: rows`
     Table(TableMatcher),
+    /// `not(foo)`: drops the elements that match the inner selector, and keeps the rest
+    ///
+    /// Unlike the other selectors, this doesn't look within an element's children: an element that doesn't match is
+    /// kept as-is, even if some of its children do. This makes it most useful after another selector, as in
+    /// `# * | not(# deprecated)`.
+    Not(Box<Self>),
+    /// `has(foo)`: keeps the elements that contain a match for the inner selector, and drops the rest
+    ///
+    /// Like [`Selector::Not`], this selects the elements themselves, rather than anything within them. For example,
+    /// `# * | has(- [ ])` selects the sections that contain an uncompleted task.
+    Has(Box<Self>),
+    /// `:first`, `:last`, `:nth(3)` or `[1..4]`: keeps only the results at that position
+    ///
+    /// This applies to the results of the previous stage as a whole (as in `# Installation | ``` | :first`), rather than
+    /// to each element within them. It can only appear as a stage of its own, not within a union or other selector.
+    Position(Position),
+    /// `check`, `uncheck` or `toggle`: changes the task list items
+    ///
+    /// This selects each task list item from the previous stage (or within its results), as `- [?]` would, and changes
+    /// whether it's checked: `- [ ] deploy | check`. Like [`Selector::Position`], it can only appear as a stage of its
+    /// own.
+    TaskAction(TaskAction),
 }
 
 impl Selector {
@@ -124,6 +357,108 @@ impl Selector {
         let result_elems = SelectorAdapter::from(self).find_nodes(&ctx, vec![MdElem::Doc(roots)])?;
         Ok((result_elems, ctx))
     }
+
+    /// Problems with the document that keep this selector from looking at parts of it, but that aren't worth failing
+    /// over: currently, front matter that doesn't parse, if this selector selects front matter keys. Such front matter
+    /// just doesn't have any keys to select, so [`Self::find_nodes`] doesn't report it; it's up to the caller whether
+    /// (and how) to show these.
+    pub(crate) fn warnings(&self, doc: &MdDoc) -> Vec<SelectError> {
+        if !self.selects_front_matter_keys() {
+            return Vec::new();
+        }
+        doc.roots
+            .iter()
+            .filter_map(|elem| match elem {
+                MdElem::FrontMatter(front_matter) => parse_front_matter(front_matter).err(),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn selects_front_matter_keys(&self) -> bool {
+        match self {
+            Selector::FrontMatterKey(_) => true,
+            Selector::Chain(selectors) | Selector::Union(selectors) => {
+                selectors.iter().any(Self::selects_front_matter_keys)
+            }
+            Selector::Not(selector) | Selector::Has(selector) => selector.selects_front_matter_keys(),
+            _ => false,
+        }
+    }
+
+    /// Whether this selector ends by changing tasks. If it does, the CLI outputs the whole document with those changes,
+    /// rather than just the changed tasks.
+    pub(crate) fn changes_tasks(&self) -> bool {
+        match self {
+            Selector::TaskAction(_) => true,
+            Selector::Chain(stages) => matches!(stages.last(), Some(Selector::TaskAction(_))),
+            _ => false,
+        }
+    }
+
+    /// This selector, but without any `!s/regex/replacement/` replacements or [task actions](Selector::TaskAction).
+    ///
+    /// It matches the same elements as the original selector, but leaves them as they are.
+    pub(crate) fn without_replacements(mut self) -> Self {
+        self.remove_replacements();
+        self
+    }
+
+    fn remove_replacements(&mut self) {
+        fn remove(match_replace: &mut MatchReplace) {
+            match_replace.replacement = None;
+        }
+        match self {
+            Selector::Chain(selectors) | Selector::Union(selectors) => {
+                selectors.iter_mut().for_each(Self::remove_replacements);
+            }
+            Selector::Not(selector) | Selector::Has(selector) => selector.remove_replacements(),
+            Selector::Section(matcher) => {
+                remove(&mut matcher.title);
+                matcher.subsections.iter_mut().for_each(remove);
+            }
+            Selector::ThematicBreak(matcher) => matcher.between.iter_mut().for_each(remove),
+            Selector::ListItem(ListItemMatcher { matcher, .. }) => remove(matcher),
+            Selector::Link(matcher) | Selector::Image(matcher) => {
+                remove(&mut matcher.display_matcher);
+                remove(&mut matcher.url_matcher);
+            }
+            Selector::Footnote(matcher) => {
+                remove(&mut matcher.label);
+                remove(&mut matcher.text);
+            }
+            Selector::BlockQuote(BlockQuoteMatcher { text })
+            | Selector::Paragraph(ParagraphMatcher { text })
+            | Selector::MathBlock(MathBlockMatcher { text })
+            | Selector::InlineCode(InlineCodeMatcher { text })
+            | Selector::InlineMath(InlineMathMatcher { text })
+            | Selector::Span(SpanMatcher { text, .. })
+            | Selector::FrontMatter(FrontMatterMatcher { text, .. }) => remove(text),
+            Selector::Html(matcher) => remove(&mut matcher.html),
+            Selector::CodeBlock(matcher) => {
+                remove(&mut matcher.language);
+                remove(&mut matcher.contents);
+            }
+            Selector::Table(matcher) => {
+                remove(&mut matcher.headers);
+                remove(&mut matcher.rows);
+            }
+            Selector::TaskAction(_) => {
+                let any_task = |ordered| {
+                    Selector::ListItem(ListItemMatcher {
+                        ordered,
+                        task: ListItemTask::Either,
+                        matcher: MatchReplace {
+                            matcher: Matcher::Any { explicit: false },
+                            replacement: None,
+                        },
+                    })
+                };
+                *self = Selector::Union(vec![any_task(false), any_task(true)]);
+            }
+            Selector::SectionSlug(_) | Selector::FrontMatterKey(_) | Selector::Position(_) => {}
+        }
+    }
 }
 
 impl TryFrom<&'_ str> for Selector {
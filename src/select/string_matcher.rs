@@ -122,6 +122,16 @@ impl StringMatcher {
                     matched_any: replaced.matched_any,
                 })
             }
+            MdElem::FootnoteDefinition(def) => {
+                let replaced = self.match_replace_any(def.body)?;
+                Ok(Replaced {
+                    item: MdElem::FootnoteDefinition(FootnoteDefinition {
+                        id: def.id,
+                        body: replaced.item,
+                    }),
+                    matched_any: replaced.matched_any,
+                })
+            }
             MdElem::List(mut list) => {
                 let mut matched_any = false;
                 for item in &mut list.items {
@@ -193,6 +203,19 @@ impl StringMatcher {
                     matched_any: replaced.matched_any,
                 })
             }
+            MdElem::FrontMatterValue(value) => {
+                let replaced = self.match_replace_string(value.text().to_string())?;
+                // the value only becomes a string if the replacement changed it
+                let item = if replaced.item == value.text() {
+                    value
+                } else {
+                    FrontMatterValue::String(replaced.item)
+                };
+                Ok(Replaced {
+                    item: MdElem::FrontMatterValue(item),
+                    matched_any: replaced.matched_any,
+                })
+            }
 
             // Base cases: these don't recurse, so we say the StringMatcher doesn't match them. A Selector still may,
             // but that's Selector-specific logic, not StringMatcher logic.
@@ -27,6 +27,7 @@ impl<const N: usize> mdq::run::OsFacade for CaseRunner<'_, N> {
     }
 
     fn read_file(&self, path: &str) -> io::Result<String> {
+        let path = path.strip_prefix("./").unwrap_or(path);
         for (name, content) in self.case.files {
             if path == *name {
                 return Ok(content.to_string());
@@ -35,10 +36,36 @@ impl<const N: usize> mdq::run::OsFacade for CaseRunner<'_, N> {
         Err(io::Error::new(ErrorKind::NotFound, format!("File not found: {path}")))
     }
 
+    /// The files' names are paths, as in "docs/README.md"; any prefix of one of them is a directory.
+    fn read_dir(&self, path: &str) -> io::Result<Option<Vec<String>>> {
+        let prefix = match path.strip_prefix("./").unwrap_or(path).trim_end_matches('/') {
+            "." => String::new(),
+            dir => format!("{dir}/"),
+        };
+        let mut names: Vec<String> = self
+            .case
+            .files
+            .iter()
+            .filter_map(|(name, _)| name.strip_prefix(&prefix))
+            .filter_map(|rest| rest.split('/').next())
+            .map(str::to_string)
+            .collect();
+        names.sort();
+        names.dedup();
+        Ok(if names.is_empty() { None } else { Some(names) })
+    }
+
     fn stdout(&mut self) -> impl io::Write {
         &mut self.stdout
     }
 
+    /// Files don't actually get written; instead, their new contents go to stdout, so that the cases can check them.
+    fn write_file(&mut self, path: &str, contents: &str) -> io::Result<()> {
+        let written = format!("==> wrote {path} <==\n{contents}\n");
+        self.stdout.extend_from_slice(written.as_bytes());
+        Ok(())
+    }
+
     fn write_error(&mut self, err: Error) {
         self.stderr.push_str(&err.to_string())
     }